@@ -7,5 +7,7 @@ pub use error::*;
 pub use systray::*;
 pub(crate) use tray_spy::*;
 pub(crate) use util::*;
+// Exposed for other crates that convert window icons to images.
+pub use util::Util;
 
 pub type Result<T> = std::result::Result<T, Error>;
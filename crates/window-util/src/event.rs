@@ -0,0 +1,65 @@
+use serde::Serialize;
+
+/// Represents a foreground window event.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WindowEvent {
+  /// What caused the event.
+  pub kind: WindowEventKind,
+  /// Window handle on Windows, or the X window ID on X11.
+  pub hwnd: isize,
+  pub title: String,
+  /// Window class name (`WM_CLASS` on X11).
+  pub class: Option<String>,
+  /// ID of the process that owns the window.
+  pub pid: Option<u32>,
+  /// File name of the executable that owns the window (e.g.
+  /// `firefox.exe`).
+  pub process_name: Option<String>,
+  /// Position and size of the window in screen coordinates.
+  pub bounds: Option<WindowBounds>,
+  /// Name of the monitor that the window is on.
+  pub monitor: Option<String>,
+  /// Whether the window covers its entire monitor.
+  pub is_fullscreen: bool,
+}
+
+/// Cause of a `WindowEvent`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowEventKind {
+  /// A different window became the foreground window.
+  Focus,
+  /// The title of the foreground window changed.
+  TitleChange,
+  /// The foreground window was moved, resized, or entered or left
+  /// fullscreen.
+  BoundsChange,
+}
+
+/// Position and size of a window or monitor in screen coordinates.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct WindowBounds {
+  pub x: i32,
+  pub y: i32,
+  pub width: i32,
+  pub height: i32,
+}
+
+impl WindowBounds {
+  /// Whether the bounds fully cover the other bounds.
+  pub fn covers(&self, other: &WindowBounds) -> bool {
+    self.x <= other.x
+      && self.y <= other.y
+      && self.x + self.width >= other.x + other.width
+      && self.y + self.height >= other.y + other.height
+  }
+
+  /// Whether the center of the bounds is within the other bounds.
+  pub fn is_centered_in(&self, other: &WindowBounds) -> bool {
+    let center_x = self.x + self.width / 2;
+    let center_y = self.y + self.height / 2;
+
+    (other.x..other.x + other.width).contains(&center_x)
+      && (other.y..other.y + other.height).contains(&center_y)
+  }
+}
@@ -1,5 +1,21 @@
+mod event;
+#[cfg(target_os = "windows")]
 pub mod window;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+mod window_list;
+#[cfg(target_os = "windows")]
+mod window_list_windows;
+#[cfg(target_os = "linux")]
+mod window_list_x11;
+#[cfg(target_os = "linux")]
+pub mod x11;
 
+pub use event::*;
+#[cfg(target_os = "windows")]
 pub use window::*;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub use window_list::*;
+#[cfg(target_os = "linux")]
+pub use x11::*;
 
 pub type Result<T> = std::result::Result<T, anyhow::Error>;
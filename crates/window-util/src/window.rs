@@ -3,84 +3,84 @@ use tokio::{
     time::{sleep, Duration},
     sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
 };
-use std::sync::{Mutex};
+use std::sync::{
+    atomic::{AtomicIsize, Ordering},
+    Mutex,
+};
 use once_cell::sync::OnceCell;
 use windows::Win32::{
     Foundation::{
-        HWND,
+        HWND, RECT,
     },
     System::LibraryLoader::GetModuleHandleW,
     UI::{
-        Accessibility::{SetWinEventHook, UnhookWinEvent},
+        Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
         WindowsAndMessaging::{
-            DispatchMessageW, PeekMessageW, PM_REMOVE, GetWindowTextLengthW, GetWindowTextW, SetForegroundWindow,
-            TranslateMessage, EVENT_SYSTEM_FOREGROUND, MSG,
+            DispatchMessageW, PeekMessageW, PM_REMOVE, GetClassNameW, GetWindowRect, GetWindowTextLengthW, GetWindowTextW,
+            GetWindowThreadProcessId, SetForegroundWindow, TranslateMessage, EVENT_OBJECT_LOCATIONCHANGE,
+            EVENT_OBJECT_NAMECHANGE, EVENT_SYSTEM_FOREGROUND, MSG, OBJID_WINDOW,
         },
     },
 };
 
-/// Represents a foreground window event.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct WindowEvent {
-    pub hwnd: isize,
-    pub title: String,
-}
+use crate::{
+    window_list_windows::{monitor_info, process_path},
+    WindowBounds, WindowEvent, WindowEventKind,
+};
+
+/// Window classes of the desktop, which covers the entire monitor without
+/// being fullscreen.
+const DESKTOP_CLASSES: [&str; 2] = ["Progman", "WorkerW"];
 
 #[derive(Debug)]
 pub struct Window {
     event_rx: UnboundedReceiver<WindowEvent>,
-    #[allow(dead_code)]
     event_tx: UnboundedSender<WindowEvent>,
 }
 
-// Define EVENT_TX globally using OnceCell
-static EVENT_TX: OnceCell<Mutex<Option<UnboundedSender<WindowEvent>>>> = OnceCell::new();
+// Senders of all live `Window` instances. The event hook is shared
+// between instances and only started once.
+static EVENT_TX: OnceCell<Mutex<Vec<UnboundedSender<WindowEvent>>>> = OnceCell::new();
+
+// Handle of the current foreground window, whose title and bounds changes
+// are tracked.
+static FOREGROUND_WINDOW: AtomicIsize = AtomicIsize::new(0);
 
 impl Window {
     pub fn new() -> crate::Result<Self> {
         let (event_tx, event_rx) = mpsc::unbounded_channel();
 
-        // Initialize the static EVENT_TX if not already initialized.
-        EVENT_TX.get_or_init(|| Mutex::new(None));
-        *EVENT_TX.get().unwrap().lock().unwrap() = Some(event_tx.clone());
+        let mut is_first_init = false;
 
-        Self::start_window_event_listener();
+        EVENT_TX
+            .get_or_init(|| {
+                is_first_init = true;
+                Mutex::new(Vec::new())
+            })
+            .lock()
+            .unwrap()
+            .push(event_tx.clone());
+
+        if is_first_init {
+            Self::start_window_event_listener();
+        }
 
         Ok(Window { event_rx, event_tx })
     }
     /// Returns the next event from the `Window`.
     pub async fn events(&mut self) -> Option<WindowEvent> {
-        while let Some(event) = self.event_rx.recv().await {
-            if let Some(event) = self.on_event(event) {
-                return Some(event);
-            }
-        }
-        None
+        self.event_rx.recv().await
     }
 
     /// Returns the next event from the `Window` (synchronously).
     pub fn events_blocking(&mut self) -> Option<WindowEvent> {
-        while let Some(event) = self.event_rx.blocking_recv() {
-            if let Some(event) = self.on_event(event) {
-                return Some(event);
-            }
-        }
-        None
-    }
-
-    fn on_event(&mut self, event: WindowEvent) -> Option<WindowEvent> {
-        // Example: Filter out events with empty titles
-        if event.title.is_empty() {
-            println!("Ignored event with empty title: {:?}", event);
-            return None;
-        }
-        Some(event)
+        self.event_rx.blocking_recv()
     }
 
     fn start_window_event_listener() {
         task::spawn_blocking(move || {
             if let Some(_hook) = WinEventHook::new() {
-                println!("Listening for window events...");
+                tracing::info!("Listening for window events.");
 
                 let mut msg = MSG::default();
                 loop {
@@ -119,81 +119,155 @@ impl Window {
 impl Drop for Window {
     fn drop(&mut self) {
         if let Some(mutex) = EVENT_TX.get() {
-            *mutex.lock().unwrap() = None;
+            mutex
+                .lock()
+                .unwrap()
+                .retain(|sender| !sender.same_channel(&self.event_tx));
         }
     }
 }
 
-// Struct to manage the lifetime of the event hook
+// Struct to manage the lifetime of the event hooks
 struct WinEventHook {
-    hook: windows::Win32::UI::Accessibility::HWINEVENTHOOK,
+    hooks: Vec<HWINEVENTHOOK>,
 }
 
 impl WinEventHook {
     fn new() -> Option<Self> {
-        let hook = unsafe {
-            SetWinEventHook(
-                EVENT_SYSTEM_FOREGROUND,
-                EVENT_SYSTEM_FOREGROUND,
-                GetModuleHandleW(None).unwrap(),
-                Some(event_callback),
-                0,
-                0,
-                0,
-            )
-        };
-
-        if hook.0.is_null() {
-            eprintln!("Failed to set foreground event hook");
-            None
-        } else {
-            Some(Self { hook })
+        // Foreground changes, as well as location and title changes (which
+        // are filtered to the foreground window in the callback).
+        let event_ranges = [
+            (EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_FOREGROUND),
+            (EVENT_OBJECT_LOCATIONCHANGE, EVENT_OBJECT_NAMECHANGE),
+        ];
+
+        let mut hooks = Vec::new();
+
+        for (event_min, event_max) in event_ranges {
+            let hook = unsafe {
+                SetWinEventHook(
+                    event_min,
+                    event_max,
+                    GetModuleHandleW(None).unwrap(),
+                    Some(event_callback),
+                    0,
+                    0,
+                    0,
+                )
+            };
+
+            if hook.0.is_null() {
+                tracing::error!("Failed to set window event hook.");
+                return None;
+            }
+
+            hooks.push(hook);
         }
+
+        Some(Self { hooks })
     }
 }
 
 impl Drop for WinEventHook {
     fn drop(&mut self) {
-        unsafe {
-            let _ = UnhookWinEvent(self.hook);
+        for hook in &self.hooks {
+            unsafe {
+                let _ = UnhookWinEvent(*hook);
+            }
         }
     }
 }
 
-// Callback function for foreground window changes
+// Callback function for foreground window changes, and title and bounds
+// changes of the foreground window
 unsafe extern "system" fn event_callback(
-    _: windows::Win32::UI::Accessibility::HWINEVENTHOOK,
-    _: u32,
+    _: HWINEVENTHOOK,
+    event: u32,
     hwnd: HWND,
-    _: i32,
-    _: i32,
+    id_object: i32,
+    id_child: i32,
     _: u32,
     _: u32,
 ) {
+    let kind = match event {
+        EVENT_SYSTEM_FOREGROUND => {
+            FOREGROUND_WINDOW.store(hwnd.0 as isize, Ordering::Relaxed);
+            WindowEventKind::Focus
+        }
+        // Ignore other windows and child objects (e.g. the caret).
+        _ if hwnd.0 as isize != FOREGROUND_WINDOW.load(Ordering::Relaxed)
+            || id_object != OBJID_WINDOW.0
+            || id_child != 0 =>
+        {
+            return;
+        }
+        EVENT_OBJECT_NAMECHANGE => WindowEventKind::TitleChange,
+        _ => WindowEventKind::BoundsChange,
+    };
+
     // Retrieve the window title
     let length = GetWindowTextLengthW(hwnd) + 1;
     let mut buffer = vec![0u16; length as usize];
     let copied_length = GetWindowTextW(hwnd, &mut buffer);
+    let window_title = String::from_utf16_lossy(&buffer[..copied_length.max(0) as usize]);
 
-    if copied_length > 0 {
-        let window_title = String::from_utf16_lossy(&buffer[..copied_length as usize]);
+    // Ignore Zebar's own windows
+    if window_title.contains("Zebar") {
+        return;
+    }
 
-        // Ignore events with the specific title
-        if window_title.contains("Zebar") {
-            println!("Ignored window with title: {:?}", window_title);
-            return;
-        }
+    // Retrieve the window class
+    let mut class_buffer = [0u16; 256];
+    let class_length = GetClassNameW(hwnd, &mut class_buffer);
+    let class = (class_length > 0)
+        .then(|| String::from_utf16_lossy(&class_buffer[..class_length as usize]));
 
-        // Access the static `EVENT_TX` to send the event
-        if let Some(mutex) = EVENT_TX.get() {
-            if let Some(sender) = mutex.lock().unwrap().as_ref() {
-                if let Err(err) = sender.send(WindowEvent {
-                    hwnd: hwnd.0 as isize,
-                    title: window_title,
-                }) {
-                    eprintln!("Failed to send event: {}", err);
-                }
-            }
-        }
+    // Retrieve the owning process ID and executable name
+    let mut pid = 0u32;
+    GetWindowThreadProcessId(hwnd, Some(&mut pid));
+    let pid = (pid != 0).then_some(pid);
+
+    let process_name = pid.and_then(process_path).and_then(|path| {
+        path.file_name().map(|name| name.to_string_lossy().into_owned())
+    });
+
+    // Retrieve the window bounds and the monitor it's on
+    let mut rect = RECT::default();
+    let bounds = GetWindowRect(hwnd, &mut rect).ok().map(|_| WindowBounds {
+        x: rect.left,
+        y: rect.top,
+        width: rect.right - rect.left,
+        height: rect.bottom - rect.top,
+    });
+
+    let monitor = monitor_info(hwnd);
+
+    let is_desktop = class
+        .as_deref()
+        .is_some_and(|class| DESKTOP_CLASSES.contains(&class));
+
+    let is_fullscreen = !is_desktop
+        && bounds
+            .zip(monitor.as_ref())
+            .is_some_and(|(bounds, (_, monitor_bounds))| bounds.covers(monitor_bounds));
+
+    let event = WindowEvent {
+        kind,
+        hwnd: hwnd.0 as isize,
+        title: window_title,
+        class,
+        pid,
+        process_name,
+        bounds,
+        monitor: monitor.map(|(name, _)| name),
+        is_fullscreen,
+    };
+
+    // Send the event to all live `Window` instances.
+    if let Some(mutex) = EVENT_TX.get() {
+        mutex
+            .lock()
+            .unwrap()
+            .retain(|sender| sender.send(event.clone()).is_ok());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,252 @@
+use std::{fmt, io::Cursor, path::PathBuf};
+
+pub use image::{ImageFormat, RgbaImage};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+#[cfg(target_os = "windows")]
+use crate::window_list_windows as backend;
+#[cfg(target_os = "linux")]
+use crate::window_list_x11 as backend;
+
+/// A top-level window, i.e. a window that would be shown on a taskbar.
+#[derive(Clone, Eq, PartialEq)]
+pub struct WindowInfo {
+  /// Window handle on Windows, or the X window ID on X11.
+  pub id: isize,
+
+  pub title: String,
+
+  /// File name of the executable that owns the window (e.g.
+  /// `firefox.exe`).
+  pub process_name: Option<String>,
+
+  /// Full path to the executable that owns the window.
+  pub process_path: Option<PathBuf>,
+
+  /// ID of the process that owns the window.
+  pub pid: Option<u32>,
+
+  /// Application icon of the window.
+  pub icon_image: Option<RgbaImage>,
+
+  /// Name of the monitor that the window is on (e.g. `\\.\DISPLAY1` on
+  /// Windows or `HDMI-1` on X11).
+  pub monitor: Option<String>,
+
+  pub is_minimized: bool,
+
+  pub is_maximized: bool,
+
+  /// Whether the window is requesting attention (e.g. a flashing taskbar
+  /// button).
+  pub is_urgent: bool,
+}
+
+// Debug implementation for `WindowInfo`. Icon image is a large buffer,
+// so we trim it in the debug output.
+impl fmt::Debug for WindowInfo {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("WindowInfo")
+      .field("id", &self.id)
+      .field("title", &self.title)
+      .field("process_name", &self.process_name)
+      .field("process_path", &self.process_path)
+      .field("pid", &self.pid)
+      .field("icon_image", &self.icon_image.as_ref().map(|_| "..."))
+      .field("monitor", &self.monitor)
+      .field("is_minimized", &self.is_minimized)
+      .field("is_maximized", &self.is_maximized)
+      .field("is_urgent", &self.is_urgent)
+      .finish()
+  }
+}
+
+impl WindowInfo {
+  /// Converts the icon image to a byte vector of the given image format.
+  pub fn to_image_format(&self, format: ImageFormat) -> crate::Result<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+
+    self
+      .icon_image
+      .as_ref()
+      .ok_or_else(|| anyhow::anyhow!("Window has no icon."))?
+      .write_to(&mut Cursor::new(&mut bytes), format)?;
+
+    Ok(bytes)
+  }
+}
+
+/// Events that can be emitted by `WindowList`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum WindowListEvent {
+  WindowAdd(WindowInfo),
+  WindowUpdate(WindowInfo),
+  WindowRemove(isize),
+}
+
+/// Actions that can be performed on a `WindowInfo`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WindowAction {
+  Focus,
+  Minimize,
+  Maximize,
+  Restore,
+  Close,
+}
+
+/// Tracks all top-level windows.
+///
+/// Windows are kept in the order they were first seen, which gives a
+/// stable ordering for taskbars.
+#[derive(Debug)]
+pub struct WindowList {
+  windows: Vec<WindowInfo>,
+  event_rx: UnboundedReceiver<WindowListEvent>,
+}
+
+impl WindowList {
+  /// Creates a new `WindowList` and starts watching for window changes.
+  ///
+  /// The initial set of windows is emitted as `WindowAdd` events.
+  pub fn new() -> crate::Result<Self> {
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+    backend::spawn_listener(event_tx)?;
+
+    Ok(WindowList {
+      windows: Vec::new(),
+      event_rx,
+    })
+  }
+
+  /// Returns all windows managed by the `WindowList`.
+  pub fn windows(&self) -> Vec<WindowInfo> {
+    self.windows.clone()
+  }
+
+  /// Returns the window with the given ID.
+  pub fn window_by_id(&self, id: isize) -> Option<&WindowInfo> {
+    self.windows.iter().find(|window| window.id == id)
+  }
+
+  /// Returns the next event from the `WindowList`.
+  pub async fn events(&mut self) -> Option<WindowListEvent> {
+    let event = self.event_rx.recv().await?;
+    self.on_event(&event);
+    Some(event)
+  }
+
+  /// Returns the next event from the `WindowList` (synchronously).
+  pub fn events_blocking(&mut self) -> Option<WindowListEvent> {
+    let event = self.event_rx.blocking_recv()?;
+    self.on_event(&event);
+    Some(event)
+  }
+
+  /// Returns the next event from the `WindowList` if one is immediately
+  /// available. Useful for coalescing bursts of events.
+  pub fn try_events(&mut self) -> Option<WindowListEvent> {
+    let event = self.event_rx.try_recv().ok()?;
+    self.on_event(&event);
+    Some(event)
+  }
+
+  /// Applies an event to the tracked windows.
+  fn on_event(&mut self, event: &WindowListEvent) {
+    match event {
+      WindowListEvent::WindowAdd(window) => self.windows.push(window.clone()),
+      WindowListEvent::WindowUpdate(window) => {
+        if let Some(found_window) = self.windows.iter_mut().find(|found| found.id == window.id) {
+          *found_window = window.clone();
+        }
+      }
+      WindowListEvent::WindowRemove(id) => self.windows.retain(|window| window.id != *id),
+    }
+  }
+
+  /// Sends an action to the window with the given ID.
+  pub fn send_action(&self, id: isize, action: &WindowAction) -> crate::Result<()> {
+    if self.window_by_id(id).is_none() {
+      anyhow::bail!("Window not found.");
+    }
+
+    backend::send_action(id, action)
+  }
+}
+
+/// Diffs successive enumerations of top-level windows into
+/// `WindowListEvent`s.
+#[derive(Debug, Default)]
+pub(crate) struct WindowDiff {
+  windows: Vec<WindowInfo>,
+}
+
+impl WindowDiff {
+  /// Replaces the previous enumeration and returns the events needed to
+  /// get from the previous enumeration to the new one.
+  pub(crate) fn update(&mut self, windows: Vec<WindowInfo>) -> Vec<WindowListEvent> {
+    let removed = self
+      .windows
+      .iter()
+      .filter(|prev| !windows.iter().any(|window| window.id == prev.id))
+      .map(|prev| WindowListEvent::WindowRemove(prev.id));
+
+    let added_or_updated = windows.iter().filter_map(|window| {
+      match self.windows.iter().find(|prev| prev.id == window.id) {
+        None => Some(WindowListEvent::WindowAdd(window.clone())),
+        Some(prev) if prev != window => Some(WindowListEvent::WindowUpdate(window.clone())),
+        Some(_) => None,
+      }
+    });
+
+    let events = removed.chain(added_or_updated).collect();
+    self.windows = windows;
+    events
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn window(id: isize, title: &str) -> WindowInfo {
+    WindowInfo {
+      id,
+      title: title.into(),
+      process_name: None,
+      process_path: None,
+      pid: None,
+      icon_image: None,
+      monitor: None,
+      is_minimized: false,
+      is_maximized: false,
+      is_urgent: false,
+    }
+  }
+
+  #[test]
+  fn test_window_diff() {
+    let mut diff = WindowDiff::default();
+
+    assert_eq!(
+      diff.update(vec![window(1, "a"), window(2, "b")]),
+      vec![
+        WindowListEvent::WindowAdd(window(1, "a")),
+        WindowListEvent::WindowAdd(window(2, "b")),
+      ]
+    );
+
+    // Unchanged windows don't produce events.
+    assert_eq!(
+      diff.update(vec![window(2, "b2"), window(3, "c")]),
+      vec![
+        WindowListEvent::WindowRemove(1),
+        WindowListEvent::WindowUpdate(window(2, "b2")),
+        WindowListEvent::WindowAdd(window(3, "c")),
+      ]
+    );
+
+    assert!(diff
+      .update(vec![window(2, "b2"), window(3, "c")])
+      .is_empty());
+  }
+}
@@ -0,0 +1,433 @@
+use std::{
+  cell::{Cell, RefCell},
+  collections::{HashMap, HashSet},
+  ffi::c_void,
+  path::PathBuf,
+  thread,
+};
+
+use anyhow::bail;
+use image::RgbaImage;
+use systray_util::Util;
+use tokio::sync::mpsc::UnboundedSender;
+use windows::Win32::{
+  Foundation::{CloseHandle, BOOL, HMODULE, HWND, LPARAM, LRESULT, WPARAM},
+  Graphics::{
+    Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED},
+    Gdi::{GetMonitorInfoW, MonitorFromWindow, MONITORINFOEXW, MONITOR_DEFAULTTONEAREST},
+  },
+  System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+  },
+  UI::{
+    Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
+    WindowsAndMessaging::{
+      DefWindowProcW, DeregisterShellHookWindow, EnumWindows, GetClassLongPtrW, GetWindow,
+      GetWindowLongPtrW, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsIconic,
+      IsWindow, IsWindowVisible, IsZoomed, KillTimer, PostMessageW, PostQuitMessage,
+      RegisterShellHookWindow, RegisterWindowMessageW, SendMessageTimeoutW, SetForegroundWindow,
+      SetTimer, ShowWindowAsync, EVENT_OBJECT_CREATE, EVENT_OBJECT_UNCLOAKED,
+      EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_MINIMIZEEND, GCLP_HICON, GCLP_HICONSM, GWL_EXSTYLE,
+      GW_OWNER, ICON_BIG, ICON_SMALL2, OBJID_WINDOW, SMTO_ABORTIFHUNG, SW_MAXIMIZE, SW_MINIMIZE,
+      SW_RESTORE, WINEVENT_OUTOFCONTEXT, WINEVENT_SKIPOWNPROCESS, WM_CLOSE, WM_GETICON, WM_TIMER,
+      WS_EX_APPWINDOW, WS_EX_TOOLWINDOW,
+    },
+  },
+};
+use windows_core::{w, PWSTR};
+
+use crate::{WindowAction, WindowBounds, WindowDiff, WindowInfo, WindowListEvent};
+
+/// Timer used to debounce refreshes of the window list.
+const REFRESH_TIMER_ID: usize = 1;
+
+/// Delay in milliseconds before refreshing after a window event.
+const REFRESH_DELAY_MS: u32 = 50;
+
+/// Shell hook codes (`HSHELL_*`) that affect urgency.
+const HSHELL_WINDOWACTIVATED: usize = 4;
+const HSHELL_RUDEAPPACTIVATED: usize = 0x8004;
+const HSHELL_FLASH: usize = 0x8006;
+
+/// State of the listener thread. Only accessed from within the
+/// listener's message loop.
+struct ListenerState {
+  event_tx: UnboundedSender<WindowListEvent>,
+  diff: WindowDiff,
+
+  /// Icon handle and converted image by window handle. Icons are only
+  /// re-converted when the handle changes.
+  icons: HashMap<isize, (isize, Option<RgbaImage>)>,
+}
+
+thread_local! {
+  static STATE: RefCell<Option<ListenerState>> = const { RefCell::new(None) };
+
+  /// Handle of the message window that receives refresh timers and shell
+  /// hook messages.
+  static MESSAGE_WINDOW: Cell<isize> = const { Cell::new(0) };
+
+  /// Message ID registered for shell hook messages.
+  static SHELL_HOOK_MESSAGE: Cell<u32> = const { Cell::new(0) };
+
+  /// Windows that are flashing for attention.
+  static URGENT_WINDOWS: RefCell<HashSet<isize>> = RefCell::new(HashSet::new());
+}
+
+/// Starts the listener thread, which refreshes the window list on
+/// window events and shell hook messages.
+pub(crate) fn spawn_listener(event_tx: UnboundedSender<WindowListEvent>) -> crate::Result<()> {
+  thread::spawn(move || {
+    if let Err(err) = run_listener(event_tx) {
+      tracing::warn!("Window list listener stopped: {}", err);
+    }
+  });
+
+  Ok(())
+}
+
+fn run_listener(event_tx: UnboundedSender<WindowListEvent>) -> crate::Result<()> {
+  let message_window = Util::create_message_window("ZebarWindowList", Some(window_proc))?;
+  let hwnd = HWND(message_window as _);
+
+  MESSAGE_WINDOW.set(message_window);
+  SHELL_HOOK_MESSAGE.set(unsafe { RegisterWindowMessageW(w!("SHELLHOOK")) });
+  unsafe { RegisterShellHookWindow(hwnd) }.ok()?;
+
+  STATE.set(Some(ListenerState {
+    event_tx,
+    diff: WindowDiff::default(),
+    icons: HashMap::new(),
+  }));
+
+  // Hook window creation, visibility, title, location and cloaking
+  // changes, as well as foreground and minimize changes.
+  let hooks = [
+    (EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_MINIMIZEEND),
+    (EVENT_OBJECT_CREATE, EVENT_OBJECT_UNCLOAKED),
+  ]
+  .map(|(event_min, event_max)| unsafe {
+    SetWinEventHook(
+      event_min,
+      event_max,
+      HMODULE::default(),
+      Some(win_event_proc),
+      0,
+      0,
+      WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+    )
+  });
+
+  // Emit the windows that exist at startup.
+  refresh();
+  Util::run_message_loop();
+
+  for hook in hooks {
+    let _ = unsafe { UnhookWinEvent(hook) };
+  }
+
+  let _ = unsafe { DeregisterShellHookWindow(hwnd) };
+
+  Ok(())
+}
+
+/// Sends an action to the given window.
+pub(crate) fn send_action(id: isize, action: &WindowAction) -> crate::Result<()> {
+  let hwnd = HWND(id as _);
+
+  if !unsafe { IsWindow(hwnd) }.as_bool() {
+    bail!("Window no longer exists.");
+  }
+
+  match action {
+    WindowAction::Focus => {
+      if unsafe { IsIconic(hwnd) }.as_bool() {
+        unsafe { ShowWindowAsync(hwnd, SW_RESTORE) }.ok()?;
+      }
+
+      if !unsafe { SetForegroundWindow(hwnd) }.as_bool() {
+        bail!("Failed to focus window.");
+      }
+    }
+    WindowAction::Minimize => unsafe { ShowWindowAsync(hwnd, SW_MINIMIZE) }.ok()?,
+    WindowAction::Maximize => unsafe { ShowWindowAsync(hwnd, SW_MAXIMIZE) }.ok()?,
+    WindowAction::Restore => unsafe { ShowWindowAsync(hwnd, SW_RESTORE) }.ok()?,
+    WindowAction::Close => unsafe { PostMessageW(hwnd, WM_CLOSE, WPARAM(0), LPARAM(0)) }?,
+  };
+
+  Ok(())
+}
+
+/// Schedules a refresh of the window list. Bursts of events reset the
+/// timer, so that only a single refresh happens.
+fn schedule_refresh() {
+  let hwnd = HWND(MESSAGE_WINDOW.get() as _);
+  unsafe { SetTimer(hwnd, REFRESH_TIMER_ID, REFRESH_DELAY_MS, None) };
+}
+
+/// Re-enumerates all top-level windows and emits the changes.
+fn refresh() {
+  // The state is taken out for the duration of the refresh, since
+  // querying other windows can re-enter the message loop.
+  let Some(mut state) = STATE.take() else {
+    return;
+  };
+
+  let mut handles: Vec<HWND> = Vec::new();
+
+  let _ = unsafe {
+    EnumWindows(
+      Some(enum_windows_proc),
+      LPARAM(&mut handles as *mut _ as isize),
+    )
+  };
+
+  let windows = handles
+    .into_iter()
+    .filter(|hwnd| is_taskbar_window(*hwnd))
+    .map(|hwnd| window_info(hwnd, &mut state.icons))
+    .collect::<Vec<_>>();
+
+  state
+    .icons
+    .retain(|id, _| windows.iter().any(|window| window.id == *id));
+
+  URGENT_WINDOWS
+    .with_borrow_mut(|urgent| urgent.retain(|id| windows.iter().any(|window| window.id == *id)));
+
+  for event in state.diff.update(windows) {
+    // Stop listening once the receiving `WindowList` has been dropped.
+    if state.event_tx.send(event).is_err() {
+      unsafe { PostQuitMessage(0) };
+      return;
+    }
+  }
+
+  STATE.set(Some(state));
+}
+
+/// Whether the window would be shown on the taskbar.
+///
+/// Follows the same rules as the Windows taskbar: visible, uncloaked
+/// windows that are either unowned or explicitly marked as app windows.
+fn is_taskbar_window(hwnd: HWND) -> bool {
+  if !unsafe { IsWindowVisible(hwnd) }.as_bool() {
+    return false;
+  }
+
+  let ex_style = unsafe { GetWindowLongPtrW(hwnd, GWL_EXSTYLE) } as u32;
+  let is_app_window = ex_style & WS_EX_APPWINDOW.0 != 0;
+
+  if ex_style & WS_EX_TOOLWINDOW.0 != 0 && !is_app_window {
+    return false;
+  }
+
+  let has_owner = unsafe { GetWindow(hwnd, GW_OWNER) }.is_ok_and(|owner| !owner.is_invalid());
+
+  if has_owner && !is_app_window {
+    return false;
+  }
+
+  // Windows on other virtual desktops and suspended UWP apps are
+  // cloaked.
+  let mut cloaked = 0u32;
+  let _ = unsafe {
+    DwmGetWindowAttribute(
+      hwnd,
+      DWMWA_CLOAKED,
+      &mut cloaked as *mut _ as *mut c_void,
+      std::mem::size_of::<u32>() as u32,
+    )
+  };
+
+  let mut pid = 0u32;
+  unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+
+  // Skip Zebar's own windows.
+  cloaked == 0 && pid != std::process::id() && unsafe { GetWindowTextLengthW(hwnd) } > 0
+}
+
+/// Reads the info of a top-level window.
+fn window_info(hwnd: HWND, icons: &mut HashMap<isize, (isize, Option<RgbaImage>)>) -> WindowInfo {
+  let id = hwnd.0 as isize;
+
+  let length = unsafe { GetWindowTextLengthW(hwnd) } + 1;
+  let mut buffer = vec![0u16; length as usize];
+  let copied_length = unsafe { GetWindowTextW(hwnd, &mut buffer) };
+  let title = String::from_utf16_lossy(&buffer[..copied_length as usize]);
+
+  let mut pid = 0u32;
+  unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+  let pid = (pid != 0).then_some(pid);
+
+  let process_path = pid.and_then(process_path);
+
+  let process_name = process_path
+    .as_ref()
+    .and_then(|path| path.file_name())
+    .map(|name| name.to_string_lossy().into_owned());
+
+  // Avoid re-reading the icon image if the handle hasn't changed.
+  let icon_image = match icon_handle(hwnd) {
+    Some(icon_handle) => match icons.get(&id) {
+      Some((cached_handle, icon_image)) if *cached_handle == icon_handle => icon_image.clone(),
+      _ => {
+        let icon_image = Util::icon_to_image(icon_handle).ok();
+        icons.insert(id, (icon_handle, icon_image.clone()));
+        icon_image
+      }
+    },
+    None => None,
+  };
+
+  WindowInfo {
+    id,
+    title,
+    process_name,
+    process_path,
+    pid,
+    icon_image,
+    monitor: monitor_info(hwnd).map(|(name, _)| name),
+    is_minimized: unsafe { IsIconic(hwnd) }.as_bool(),
+    is_maximized: unsafe { IsZoomed(hwnd) }.as_bool(),
+    is_urgent: URGENT_WINDOWS.with_borrow(|urgent| urgent.contains(&id)),
+  }
+}
+
+/// Gets the full path to the executable of the given process.
+pub(crate) fn process_path(pid: u32) -> Option<PathBuf> {
+  let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+
+  let mut buffer = [0u16; 1024];
+  let mut size = buffer.len() as u32;
+
+  let res = unsafe {
+    QueryFullProcessImageNameW(
+      handle,
+      PROCESS_NAME_WIN32,
+      PWSTR(buffer.as_mut_ptr()),
+      &mut size,
+    )
+  };
+
+  let _ = unsafe { CloseHandle(handle) };
+  res.ok()?;
+
+  Some(PathBuf::from(String::from_utf16_lossy(
+    &buffer[..size as usize],
+  )))
+}
+
+/// Gets the icon handle of a window, falling back to the icon of its
+/// window class.
+fn icon_handle(hwnd: HWND) -> Option<isize> {
+  for icon_type in [ICON_BIG, ICON_SMALL2] {
+    let mut icon_handle = 0usize;
+
+    // Hung windows would otherwise block the listener.
+    unsafe {
+      SendMessageTimeoutW(
+        hwnd,
+        WM_GETICON,
+        WPARAM(icon_type as usize),
+        LPARAM(0),
+        SMTO_ABORTIFHUNG,
+        100,
+        Some(&mut icon_handle),
+      )
+    };
+
+    if icon_handle != 0 {
+      return Some(icon_handle as isize);
+    }
+  }
+
+  [GCLP_HICON, GCLP_HICONSM]
+    .into_iter()
+    .map(|index| unsafe { GetClassLongPtrW(hwnd, index) })
+    .find(|icon_handle| *icon_handle != 0)
+    .map(|icon_handle| icon_handle as isize)
+}
+
+/// Gets the device name (e.g. `\\.\DISPLAY1`) and bounds of the monitor
+/// that the window is mostly on.
+pub(crate) fn monitor_info(hwnd: HWND) -> Option<(String, WindowBounds)> {
+  let monitor = unsafe { MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST) };
+
+  let mut info = MONITORINFOEXW::default();
+  info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+
+  if !unsafe { GetMonitorInfoW(monitor, &mut info.monitorInfo) }.as_bool() {
+    return None;
+  }
+
+  let length = info
+    .szDevice
+    .iter()
+    .position(|char| *char == 0)
+    .unwrap_or(info.szDevice.len());
+
+  let rect = info.monitorInfo.rcMonitor;
+
+  let bounds = WindowBounds {
+    x: rect.left,
+    y: rect.top,
+    width: rect.right - rect.left,
+    height: rect.bottom - rect.top,
+  };
+
+  Some((String::from_utf16_lossy(&info.szDevice[..length]), bounds))
+}
+
+unsafe extern "system" fn enum_windows_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
+  let handles = &mut *(lparam.0 as *mut Vec<HWND>);
+  handles.push(hwnd);
+  true.into()
+}
+
+unsafe extern "system" fn win_event_proc(
+  _: HWINEVENTHOOK,
+  _: u32,
+  _: HWND,
+  id_object: i32,
+  id_child: i32,
+  _: u32,
+  _: u32,
+) {
+  // Ignore events for child objects (e.g. caret or cursor movement).
+  if id_object == OBJID_WINDOW.0 && id_child == 0 {
+    schedule_refresh();
+  }
+}
+
+unsafe extern "system" fn window_proc(
+  hwnd: HWND,
+  msg: u32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+) -> LRESULT {
+  if msg == WM_TIMER && wparam.0 == REFRESH_TIMER_ID {
+    let _ = KillTimer(hwnd, REFRESH_TIMER_ID);
+    refresh();
+    return LRESULT(0);
+  }
+
+  if msg == SHELL_HOOK_MESSAGE.get() {
+    let window = lparam.0;
+
+    URGENT_WINDOWS.with_borrow_mut(|urgent| match wparam.0 {
+      HSHELL_FLASH => {
+        urgent.insert(window);
+      }
+      HSHELL_WINDOWACTIVATED | HSHELL_RUDEAPPACTIVATED => {
+        urgent.remove(&window);
+      }
+      _ => {}
+    });
+
+    schedule_refresh();
+    return LRESULT(0);
+  }
+
+  DefWindowProcW(hwnd, msg, wparam, lparam)
+}
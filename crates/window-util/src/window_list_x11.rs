@@ -0,0 +1,373 @@
+use std::{collections::HashMap, thread};
+
+use image::RgbaImage;
+use tokio::sync::mpsc::UnboundedSender;
+use x11rb::{
+  connection::Connection,
+  protocol::{
+    xproto::{AtomEnum, ChangeWindowAttributesAux, ConnectionExt, EventMask, Window as XWindow},
+    Event,
+  },
+  rust_connection::RustConnection,
+  CURRENT_TIME,
+};
+
+use crate::{
+  x11::{
+    monitors, process_path, send_client_message, window_bounds, window_pid, window_states,
+    window_title, Atoms, SOURCE_PAGER,
+  },
+  WindowAction, WindowBounds, WindowDiff, WindowInfo, WindowListEvent,
+};
+
+/// `UrgencyHint` flag in `WM_HINTS`.
+const URGENCY_HINT: u32 = 1 << 8;
+
+/// `IconicState` value for `WM_CHANGE_STATE` requests.
+const ICONIC_STATE: u32 = 3;
+
+/// Actions for `_NET_WM_STATE` requests.
+const NET_WM_STATE_REMOVE: u32 = 0;
+const NET_WM_STATE_ADD: u32 = 1;
+
+/// Connects to the X server and starts watching `_NET_CLIENT_LIST` and
+/// the properties of each client window.
+pub(crate) fn spawn_listener(event_tx: UnboundedSender<WindowListEvent>) -> crate::Result<()> {
+  let (conn, screen_num) = x11rb::connect(None)?;
+  let root = conn.setup().roots[screen_num].root;
+  let atoms = Atoms::new(&conn)?.reply()?;
+
+  // Property changes on the root window include `_NET_CLIENT_LIST`.
+  conn.change_window_attributes(
+    root,
+    &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+  )?;
+  conn.flush()?;
+
+  let listener = X11WindowListListener {
+    conn,
+    root,
+    atoms,
+    clients: Vec::new(),
+    icons: HashMap::new(),
+    diff: WindowDiff::default(),
+    event_tx,
+  };
+
+  thread::spawn(move || {
+    if let Err(err) = listener.run() {
+      tracing::warn!("X11 window list listener stopped: {}", err);
+    }
+  });
+
+  Ok(())
+}
+
+/// Sends an action to the given window via EWMH client messages.
+pub(crate) fn send_action(id: isize, action: &WindowAction) -> crate::Result<()> {
+  let (conn, screen_num) = x11rb::connect(None)?;
+  let root = conn.setup().roots[screen_num].root;
+  let atoms = Atoms::new(&conn)?.reply()?;
+  let window = id as XWindow;
+
+  let activate = || {
+    send_client_message(
+      &conn,
+      root,
+      window,
+      atoms._NET_ACTIVE_WINDOW,
+      [SOURCE_PAGER, CURRENT_TIME, 0, 0, 0],
+    )
+  };
+
+  let set_maximized = |action| {
+    send_client_message(
+      &conn,
+      root,
+      window,
+      atoms._NET_WM_STATE,
+      [
+        action,
+        atoms._NET_WM_STATE_MAXIMIZED_VERT,
+        atoms._NET_WM_STATE_MAXIMIZED_HORZ,
+        SOURCE_PAGER,
+        0,
+      ],
+    )
+  };
+
+  match action {
+    WindowAction::Focus => activate(),
+    WindowAction::Minimize => send_client_message(
+      &conn,
+      root,
+      window,
+      atoms.WM_CHANGE_STATE,
+      [ICONIC_STATE, 0, 0, 0, 0],
+    ),
+    WindowAction::Maximize => set_maximized(NET_WM_STATE_ADD),
+    WindowAction::Restore => {
+      // Activating a minimized window maps it in its previous state.
+      // Otherwise, restoring means leaving the maximized state.
+      if window_states(&conn, &atoms, window)?.contains(&atoms._NET_WM_STATE_HIDDEN) {
+        activate()
+      } else {
+        set_maximized(NET_WM_STATE_REMOVE)
+      }
+    }
+    WindowAction::Close => send_client_message(
+      &conn,
+      root,
+      window,
+      atoms._NET_CLOSE_WINDOW,
+      [CURRENT_TIME, SOURCE_PAGER, 0, 0, 0],
+    ),
+  }
+}
+
+/// Watches the root window for changes to the client list, and each
+/// client window for changes to its title, state, hints and icon.
+struct X11WindowListListener {
+  conn: RustConnection,
+  root: XWindow,
+  atoms: Atoms,
+  clients: Vec<XWindow>,
+
+  /// Icons of client windows. Only re-read when `_NET_WM_ICON` changes.
+  icons: HashMap<XWindow, Option<RgbaImage>>,
+
+  diff: WindowDiff,
+  event_tx: UnboundedSender<WindowListEvent>,
+}
+
+impl X11WindowListListener {
+  fn run(mut self) -> crate::Result<()> {
+    // Emit the windows that exist at startup.
+    if !self.refresh()? {
+      return Ok(());
+    }
+
+    loop {
+      let event = self.conn.wait_for_event()?;
+      let mut should_refresh = self.handle_event(event);
+
+      // Coalesce bursts of events (e.g. while dragging a window) into a
+      // single refresh.
+      while let Some(event) = self.conn.poll_for_event()? {
+        should_refresh |= self.handle_event(event);
+      }
+
+      // Stop listening once the receiving `WindowList` has been dropped.
+      if should_refresh && !self.refresh()? {
+        return Ok(());
+      }
+    }
+  }
+
+  /// Returns whether the event requires the window list to be refreshed.
+  fn handle_event(&mut self, event: Event) -> bool {
+    match event {
+      Event::PropertyNotify(event) if event.window == self.root => {
+        event.atom == self.atoms._NET_CLIENT_LIST
+      }
+      Event::PropertyNotify(event) => {
+        if event.atom == self.atoms._NET_WM_ICON {
+          self.icons.remove(&event.window);
+        }
+
+        [
+          self.atoms._NET_WM_NAME,
+          self.atoms._NET_WM_STATE,
+          self.atoms._NET_WM_ICON,
+          u32::from(AtomEnum::WM_NAME),
+          u32::from(AtomEnum::WM_HINTS),
+        ]
+        .contains(&event.atom)
+      }
+      // Moving a window can change the monitor it's on.
+      Event::ConfigureNotify(event) => self.clients.contains(&event.window),
+      _ => false,
+    }
+  }
+
+  /// Re-reads all client windows and emits the changes.
+  ///
+  /// Returns `false` if the receiver has been dropped.
+  fn refresh(&mut self) -> crate::Result<bool> {
+    let clients = self
+      .conn
+      .get_property(
+        false,
+        self.root,
+        self.atoms._NET_CLIENT_LIST,
+        AtomEnum::WINDOW,
+        0,
+        u32::MAX,
+      )?
+      .reply()?
+      .value32()
+      .map(|value| value.collect::<Vec<_>>())
+      .unwrap_or_default();
+
+    // Subscribe to changes on newly added clients. Clients may already
+    // be destroyed, so errors are ignored here.
+    for client in clients
+      .iter()
+      .filter(|client| !self.clients.contains(client))
+    {
+      let _ = self.conn.change_window_attributes(
+        *client,
+        &ChangeWindowAttributesAux::new()
+          .event_mask(EventMask::PROPERTY_CHANGE | EventMask::STRUCTURE_NOTIFY),
+      );
+    }
+
+    self.conn.flush()?;
+    self.icons.retain(|window, _| clients.contains(window));
+    self.clients = clients;
+
+    let monitors = monitors(&self.conn, self.root).unwrap_or_default();
+
+    let windows = self
+      .clients
+      .clone()
+      .into_iter()
+      .filter_map(|client| self.window_info(client, &monitors).ok().flatten())
+      .collect();
+
+    for event in self.diff.update(windows) {
+      if self.event_tx.send(event).is_err() {
+        return Ok(false);
+      }
+    }
+
+    Ok(true)
+  }
+
+  /// Reads the info of a client window.
+  ///
+  /// Returns `None` for windows that shouldn't be shown on a taskbar.
+  fn window_info(
+    &mut self,
+    window: XWindow,
+    monitors: &[(String, WindowBounds)],
+  ) -> crate::Result<Option<WindowInfo>> {
+    let states = window_states(&self.conn, &self.atoms, window)?;
+    let pid = window_pid(&self.conn, &self.atoms, window)?;
+
+    // Skip windows that opt out of taskbars, as well as Zebar's own
+    // windows.
+    if states.contains(&self.atoms._NET_WM_STATE_SKIP_TASKBAR) || pid == Some(std::process::id()) {
+      return Ok(None);
+    }
+
+    let process_path = pid.and_then(process_path);
+
+    let process_name = process_path
+      .as_ref()
+      .and_then(|path| path.file_name())
+      .map(|name| name.to_string_lossy().into_owned());
+
+    let icon_image = match self.icons.get(&window) {
+      Some(icon_image) => icon_image.clone(),
+      None => {
+        let icon_image = self.icon(window)?;
+        self.icons.insert(window, icon_image.clone());
+        icon_image
+      }
+    };
+
+    Ok(Some(WindowInfo {
+      id: window as isize,
+      title: window_title(&self.conn, &self.atoms, window)?,
+      process_name,
+      process_path,
+      pid,
+      icon_image,
+      monitor: self.monitor(window, monitors),
+      is_minimized: states.contains(&self.atoms._NET_WM_STATE_HIDDEN),
+      is_maximized: states.contains(&self.atoms._NET_WM_STATE_MAXIMIZED_VERT)
+        && states.contains(&self.atoms._NET_WM_STATE_MAXIMIZED_HORZ),
+      is_urgent: states.contains(&self.atoms._NET_WM_STATE_DEMANDS_ATTENTION)
+        || self.has_urgency_hint(window)?,
+    }))
+  }
+
+  /// Gets the largest icon from `_NET_WM_ICON`.
+  fn icon(&self, window: XWindow) -> crate::Result<Option<RgbaImage>> {
+    let reply = self
+      .conn
+      .get_property(
+        false,
+        window,
+        self.atoms._NET_WM_ICON,
+        AtomEnum::CARDINAL,
+        0,
+        u32::MAX,
+      )?
+      .reply()?;
+
+    let data = reply
+      .value32()
+      .map(|value| value.collect::<Vec<_>>())
+      .unwrap_or_default();
+
+    Ok(parse_net_wm_icon(&data))
+  }
+
+  /// Whether the `UrgencyHint` flag is set in `WM_HINTS`.
+  fn has_urgency_hint(&self, window: XWindow) -> crate::Result<bool> {
+    let reply = self
+      .conn
+      .get_property(false, window, AtomEnum::WM_HINTS, AtomEnum::WM_HINTS, 0, 1)?
+      .reply()?;
+
+    let flags = reply.value32().and_then(|mut value| value.next());
+    Ok(flags.is_some_and(|flags| flags & URGENCY_HINT != 0))
+  }
+
+  /// Gets the name of the monitor containing the center of the window.
+  fn monitor(&self, window: XWindow, monitors: &[(String, WindowBounds)]) -> Option<String> {
+    let bounds = window_bounds(&self.conn, self.root, window).ok()?;
+
+    monitors
+      .iter()
+      .find(|(_, monitor_bounds)| bounds.is_centered_in(monitor_bounds))
+      .map(|(name, _)| name.clone())
+  }
+}
+
+/// Picks the largest image out of `_NET_WM_ICON` data, which is an array
+/// of `width, height, pixels...` entries with pixels in ARGB order.
+fn parse_net_wm_icon(data: &[u32]) -> Option<RgbaImage> {
+  let mut largest: Option<(u32, u32, &[u32])> = None;
+  let mut rest = data;
+
+  while let [width, height, tail @ ..] = rest {
+    let pixel_count = (*width as usize).checked_mul(*height as usize)?;
+
+    if pixel_count == 0 || pixel_count > tail.len() {
+      break;
+    }
+
+    let (pixels, next) = tail.split_at(pixel_count);
+
+    if largest.is_none_or(|(largest_width, ..)| *width > largest_width) {
+      largest = Some((*width, *height, pixels));
+    }
+
+    rest = next;
+  }
+
+  let (width, height, pixels) = largest?;
+
+  let bytes = pixels
+    .iter()
+    .flat_map(|pixel| {
+      let [alpha, red, green, blue] = pixel.to_be_bytes();
+      [red, green, blue, alpha]
+    })
+    .collect();
+
+  RgbaImage::from_raw(width, height, bytes)
+}
@@ -0,0 +1,540 @@
+use std::{path::PathBuf, thread};
+
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use x11rb::{
+  connection::Connection,
+  errors::ReplyError,
+  protocol::{
+    randr::ConnectionExt as _,
+    xproto::{
+      Atom, AtomEnum, ChangeWindowAttributesAux, ClientMessageEvent, ConnectionExt, EventMask,
+      Window as XWindow,
+    },
+    Event,
+  },
+  rust_connection::RustConnection,
+  CURRENT_TIME, NONE,
+};
+
+use crate::{WindowBounds, WindowEvent, WindowEventKind};
+
+x11rb::atom_manager! {
+    pub(crate) Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_CLIENT_LIST,
+        _NET_CLOSE_WINDOW,
+        _NET_WM_ICON,
+        _NET_WM_NAME,
+        _NET_WM_PID,
+        _NET_WM_STATE,
+        _NET_WM_STATE_DEMANDS_ATTENTION,
+        _NET_WM_STATE_FULLSCREEN,
+        _NET_WM_STATE_HIDDEN,
+        _NET_WM_STATE_MAXIMIZED_HORZ,
+        _NET_WM_STATE_MAXIMIZED_VERT,
+        _NET_WM_STATE_SKIP_TASKBAR,
+        UTF8_STRING,
+        WM_CHANGE_STATE,
+    }
+}
+
+/// Source indication for EWMH client messages. A value of 2 marks the
+/// request as coming from a pager, which window managers honor without
+/// focus-stealing prevention.
+pub(crate) const SOURCE_PAGER: u32 = 2;
+
+#[derive(Debug)]
+pub struct Window {
+  event_rx: UnboundedReceiver<WindowEvent>,
+}
+
+impl Window {
+  /// Connects to the X server and starts watching the active window.
+  pub fn new() -> crate::Result<Self> {
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+
+    let (conn, screen_num) = x11rb::connect(None)?;
+    let root = conn.setup().roots[screen_num].root;
+    let atoms = Atoms::new(&conn)?.reply()?;
+
+    // Property changes on the root window include `_NET_ACTIVE_WINDOW`.
+    conn.change_window_attributes(
+      root,
+      &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+    )?;
+    conn.flush()?;
+
+    let listener = X11Listener {
+      conn,
+      root,
+      atoms,
+      active_window: NONE,
+      event_tx,
+    };
+
+    thread::spawn(move || {
+      if let Err(err) = listener.run() {
+        tracing::warn!("X11 window listener stopped: {}", err);
+      }
+    });
+
+    Ok(Window { event_rx })
+  }
+
+  /// Returns the next event from the `Window`.
+  pub async fn events(&mut self) -> Option<WindowEvent> {
+    self.event_rx.recv().await
+  }
+
+  /// Returns the next event from the `Window` (synchronously).
+  pub fn events_blocking(&mut self) -> Option<WindowEvent> {
+    self.event_rx.blocking_recv()
+  }
+
+  /// Asks the window manager to activate the window with the given X
+  /// window ID via a `_NET_ACTIVE_WINDOW` client message.
+  pub fn set_foreground_window(hwnd: isize) -> anyhow::Result<(), String> {
+    let activate = || -> crate::Result<()> {
+      let (conn, screen_num) = x11rb::connect(None)?;
+      let root = conn.setup().roots[screen_num].root;
+      let atoms = Atoms::new(&conn)?.reply()?;
+
+      send_client_message(
+        &conn,
+        root,
+        hwnd as XWindow,
+        atoms._NET_ACTIVE_WINDOW,
+        [SOURCE_PAGER, CURRENT_TIME, 0, 0, 0],
+      )
+    };
+
+    activate().map_err(|err| format!("Failed to set foreground window: {}", err))
+  }
+}
+
+/// Watches the root window for active window changes, and the active
+/// window for title, bounds and state changes.
+struct X11Listener {
+  conn: RustConnection,
+  root: XWindow,
+  atoms: Atoms,
+  active_window: XWindow,
+  event_tx: UnboundedSender<WindowEvent>,
+}
+
+impl X11Listener {
+  fn run(mut self) -> crate::Result<()> {
+    // Emit the window that is active at startup.
+    if skip_window_errors(self.update_active_window())? == Some(true)
+      && skip_window_errors(self.emit(WindowEventKind::Focus))? == Some(false)
+    {
+      return Ok(());
+    }
+
+    loop {
+      let kind = match self.conn.wait_for_event()? {
+        Event::PropertyNotify(event) if event.window == self.root => (event.atom
+          == self.atoms._NET_ACTIVE_WINDOW
+          && skip_window_errors(self.update_active_window())? == Some(true))
+        .then_some(WindowEventKind::Focus),
+        Event::PropertyNotify(event) if event.window == self.active_window => {
+          if event.atom == self.atoms._NET_WM_NAME || event.atom == u32::from(AtomEnum::WM_NAME) {
+            Some(WindowEventKind::TitleChange)
+          } else if event.atom == self.atoms._NET_WM_STATE {
+            Some(WindowEventKind::BoundsChange)
+          } else {
+            None
+          }
+        }
+        Event::ConfigureNotify(event) if event.window == self.active_window => {
+          Some(WindowEventKind::BoundsChange)
+        }
+        _ => None,
+      };
+
+      // Stop listening once the receiving `Window` has been dropped.
+      if let Some(kind) = kind {
+        if skip_window_errors(self.emit(kind))? == Some(false) {
+          return Ok(());
+        }
+      }
+    }
+  }
+
+  /// Reads `_NET_ACTIVE_WINDOW` from the root window and moves the
+  /// property and structure subscriptions over to the new active window.
+  ///
+  /// Returns whether the active window changed.
+  fn update_active_window(&mut self) -> crate::Result<bool> {
+    let reply = self
+      .conn
+      .get_property(
+        false,
+        self.root,
+        self.atoms._NET_ACTIVE_WINDOW,
+        AtomEnum::WINDOW,
+        0,
+        1,
+      )?
+      .reply()?;
+
+    let window = reply
+      .value32()
+      .and_then(|mut value| value.next())
+      .unwrap_or(NONE);
+
+    if window == self.active_window {
+      return Ok(false);
+    }
+
+    // The previous window may already be destroyed, so errors are
+    // ignored here.
+    if self.active_window != NONE {
+      let _ = self.conn.change_window_attributes(
+        self.active_window,
+        &ChangeWindowAttributesAux::new().event_mask(EventMask::NO_EVENT),
+      );
+    }
+
+    if window != NONE {
+      self.conn.change_window_attributes(
+        window,
+        &ChangeWindowAttributesAux::new()
+          .event_mask(EventMask::PROPERTY_CHANGE | EventMask::STRUCTURE_NOTIFY),
+      )?;
+    }
+
+    self.conn.flush()?;
+    self.active_window = window;
+
+    Ok(window != NONE)
+  }
+
+  /// Sends an event for the current active window.
+  ///
+  /// Returns `false` if the receiver has been dropped.
+  fn emit(&self, kind: WindowEventKind) -> crate::Result<bool> {
+    let window = self.active_window;
+    let title = window_title(&self.conn, &self.atoms, window)?;
+
+    // Ignore events from Zebar's own windows.
+    if title.contains("Zebar") {
+      return Ok(true);
+    }
+
+    let pid = window_pid(&self.conn, &self.atoms, window)?;
+    let bounds = window_bounds(&self.conn, self.root, window).ok();
+
+    let monitor = bounds.and_then(|bounds| {
+      monitors(&self.conn, self.root)
+        .ok()?
+        .into_iter()
+        .find(|(_, monitor_bounds)| bounds.is_centered_in(monitor_bounds))
+    });
+
+    let is_fullscreen = window_states(&self.conn, &self.atoms, window)?
+      .contains(&self.atoms._NET_WM_STATE_FULLSCREEN)
+      || bounds
+        .zip(monitor.as_ref())
+        .is_some_and(|(bounds, (_, monitor_bounds))| bounds.covers(monitor_bounds));
+
+    let event = WindowEvent {
+      kind,
+      hwnd: window as isize,
+      title,
+      class: window_class(&self.conn, window)?,
+      pid,
+      process_name: pid.and_then(process_path).and_then(|path| {
+        path
+          .file_name()
+          .map(|name| name.to_string_lossy().into_owned())
+      }),
+      bounds,
+      monitor: monitor.map(|(name, _)| name),
+      is_fullscreen,
+    };
+
+    Ok(self.event_tx.send(event).is_ok())
+  }
+}
+
+/// Treats X11 error replies (e.g. `BadWindow` when the window was
+/// destroyed before it was queried) as skipping the event, whereas
+/// connection errors are returned.
+fn skip_window_errors<T>(res: crate::Result<T>) -> crate::Result<Option<T>> {
+  match res {
+    Ok(value) => Ok(Some(value)),
+    Err(err)
+      if matches!(
+        err.downcast_ref::<ReplyError>(),
+        Some(ReplyError::X11Error(_))
+      ) =>
+    {
+      tracing::debug!("Skipping X11 window event: {}", err);
+      Ok(None)
+    }
+    Err(err) => Err(err),
+  }
+}
+
+/// Sends an EWMH client message for the given window to the root window,
+/// where the window manager picks it up.
+pub(crate) fn send_client_message(
+  conn: &impl Connection,
+  root: XWindow,
+  window: XWindow,
+  message_type: Atom,
+  data: [u32; 5],
+) -> crate::Result<()> {
+  let event = ClientMessageEvent::new(32, window, message_type, data);
+
+  conn.send_event(
+    false,
+    root,
+    EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+    event,
+  )?;
+  conn.flush()?;
+
+  Ok(())
+}
+
+/// Gets the window title, preferring `_NET_WM_NAME` over `WM_NAME`.
+pub(crate) fn window_title(
+  conn: &impl Connection,
+  atoms: &Atoms,
+  window: XWindow,
+) -> crate::Result<String> {
+  let net_wm_name = conn
+    .get_property(
+      false,
+      window,
+      atoms._NET_WM_NAME,
+      atoms.UTF8_STRING,
+      0,
+      u32::MAX,
+    )?
+    .reply()?;
+
+  if !net_wm_name.value.is_empty() {
+    return Ok(String::from_utf8_lossy(&net_wm_name.value).into_owned());
+  }
+
+  let wm_name = conn
+    .get_property(false, window, AtomEnum::WM_NAME, AtomEnum::ANY, 0, u32::MAX)?
+    .reply()?;
+
+  Ok(String::from_utf8_lossy(&wm_name.value).into_owned())
+}
+
+/// Gets the class part of `WM_CLASS`, which is stored as two
+/// null-terminated strings (instance and class).
+pub(crate) fn window_class(
+  conn: &impl Connection,
+  window: XWindow,
+) -> crate::Result<Option<String>> {
+  let reply = conn
+    .get_property(
+      false,
+      window,
+      AtomEnum::WM_CLASS,
+      AtomEnum::STRING,
+      0,
+      u32::MAX,
+    )?
+    .reply()?;
+
+  Ok(
+    reply
+      .value
+      .split(|byte| *byte == 0)
+      .rfind(|part| !part.is_empty())
+      .map(|class| String::from_utf8_lossy(class).into_owned()),
+  )
+}
+
+/// Gets the process ID from `_NET_WM_PID`.
+pub(crate) fn window_pid(
+  conn: &impl Connection,
+  atoms: &Atoms,
+  window: XWindow,
+) -> crate::Result<Option<u32>> {
+  let reply = conn
+    .get_property(false, window, atoms._NET_WM_PID, AtomEnum::CARDINAL, 0, 1)?
+    .reply()?;
+
+  Ok(reply.value32().and_then(|mut value| value.next()))
+}
+
+/// Gets the atoms in `_NET_WM_STATE` of a window.
+pub(crate) fn window_states(
+  conn: &impl Connection,
+  atoms: &Atoms,
+  window: XWindow,
+) -> crate::Result<Vec<u32>> {
+  let reply = conn
+    .get_property(
+      false,
+      window,
+      atoms._NET_WM_STATE,
+      AtomEnum::ATOM,
+      0,
+      u32::MAX,
+    )?
+    .reply()?;
+
+  Ok(
+    reply
+      .value32()
+      .map(|value| value.collect())
+      .unwrap_or_default(),
+  )
+}
+
+/// Gets the position and size of a window relative to the root window.
+pub(crate) fn window_bounds(
+  conn: &impl Connection,
+  root: XWindow,
+  window: XWindow,
+) -> crate::Result<WindowBounds> {
+  let geometry = conn.get_geometry(window)?.reply()?;
+  let position = conn.translate_coordinates(window, root, 0, 0)?.reply()?;
+
+  Ok(WindowBounds {
+    x: i32::from(position.dst_x),
+    y: i32::from(position.dst_y),
+    width: i32::from(geometry.width),
+    height: i32::from(geometry.height),
+  })
+}
+
+/// Gets the names and bounds of the RandR monitors of the screen.
+pub(crate) fn monitors(
+  conn: &impl Connection,
+  root: XWindow,
+) -> crate::Result<Vec<(String, WindowBounds)>> {
+  let reply = conn.randr_get_monitors(root, true)?.reply()?;
+
+  reply
+    .monitors
+    .into_iter()
+    .map(|monitor| {
+      let name = conn.get_atom_name(monitor.name)?.reply()?.name;
+
+      let bounds = WindowBounds {
+        x: i32::from(monitor.x),
+        y: i32::from(monitor.y),
+        width: i32::from(monitor.width),
+        height: i32::from(monitor.height),
+      };
+
+      Ok((String::from_utf8_lossy(&name).into_owned(), bounds))
+    })
+    .collect()
+}
+
+/// Gets the path to the executable of a process via `/proc`.
+pub(crate) fn process_path(pid: u32) -> Option<PathBuf> {
+  std::fs::read_link(format!("/proc/{}/exe", pid)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use x11rb::{
+    protocol::xproto::{CreateWindowAux, PropMode, WindowClass},
+    wrapper::ConnectionExt as _,
+    COPY_DEPTH_FROM_PARENT,
+  };
+
+  use super::*;
+
+  #[tokio::test]
+  #[ignore = "requires an X server, e.g. `xvfb-run cargo test -p window-util -- --ignored`"]
+  async fn test_active_window_change() {
+    let mut window = Window::new().unwrap();
+
+    let (conn, screen_num) = x11rb::connect(None).unwrap();
+    let screen = &conn.setup().roots[screen_num];
+    let atoms = Atoms::new(&conn).unwrap().reply().unwrap();
+    let client = conn.generate_id().unwrap();
+
+    conn
+      .create_window(
+        COPY_DEPTH_FROM_PARENT,
+        client,
+        screen.root,
+        0,
+        0,
+        100,
+        100,
+        0,
+        WindowClass::INPUT_OUTPUT,
+        0,
+        &CreateWindowAux::new(),
+      )
+      .unwrap();
+
+    conn
+      .change_property8(
+        PropMode::REPLACE,
+        client,
+        atoms._NET_WM_NAME,
+        atoms.UTF8_STRING,
+        b"Editor",
+      )
+      .unwrap();
+    conn
+      .change_property8(
+        PropMode::REPLACE,
+        client,
+        AtomEnum::WM_CLASS,
+        AtomEnum::STRING,
+        b"editor\0Editor\0",
+      )
+      .unwrap();
+    conn
+      .change_property32(
+        PropMode::REPLACE,
+        client,
+        atoms._NET_WM_PID,
+        AtomEnum::CARDINAL,
+        &[4242],
+      )
+      .unwrap();
+
+    // Stand in for the window manager by setting the active window.
+    conn
+      .change_property32(
+        PropMode::REPLACE,
+        screen.root,
+        atoms._NET_ACTIVE_WINDOW,
+        AtomEnum::WINDOW,
+        &[client],
+      )
+      .unwrap();
+    conn.flush().unwrap();
+
+    let event = window.events().await.unwrap();
+    assert_eq!(event.kind, WindowEventKind::Focus);
+    assert_eq!(event.hwnd, client as isize);
+    assert_eq!(event.title, "Editor");
+    assert_eq!(event.class.as_deref(), Some("Editor"));
+    assert_eq!(event.pid, Some(4242));
+
+    // Title changes of the active window are emitted as well.
+    conn
+      .change_property8(
+        PropMode::REPLACE,
+        client,
+        atoms._NET_WM_NAME,
+        atoms.UTF8_STRING,
+        b"Editor - file.rs",
+      )
+      .unwrap();
+    conn.flush().unwrap();
+
+    let event = window.events().await.unwrap();
+    assert_eq!(event.kind, WindowEventKind::TitleChange);
+    assert_eq!(event.title, "Editor - file.rs");
+  }
+}
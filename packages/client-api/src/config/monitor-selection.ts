@@ -1,3 +1,16 @@
-export type MonitorSelection = {
-  type: 'all' | 'primary' | 'secondary';
-};
+export type MonitorSelection =
+  | {
+      type:
+        | 'all'
+        | 'primary'
+        | 'secondary'
+        | 'largest'
+        | 'portrait'
+        | 'landscape';
+    }
+  | { type: 'index' | 'min_width'; match: number }
+  | { type: 'name' | 'name_regex'; match: string }
+  | {
+      type: 'all_except' | 'any_of' | 'all_of';
+      match: MonitorSelection[];
+    };
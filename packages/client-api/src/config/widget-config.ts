@@ -3,6 +3,7 @@ import type { WidgetPreset } from './widget-preset';
 import type { WidgetPrivileges } from './widget-privileges';
 
 export type WidgetConfig = {
+  version: number;
   htmlPath: string;
   zOrder: 'normal' | 'top_most' | 'bottom_most';
   shownInTaskbar: boolean;
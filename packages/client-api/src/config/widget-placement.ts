@@ -16,4 +16,5 @@ export type WidgetPlacement = {
   height: string;
   monitorSelection: MonitorSelection;
   dockToEdge: DockConfig;
+  hideOnFullscreen: boolean;
 };
@@ -12,6 +12,16 @@ const logger = createLogger('desktop-commands');
 export const desktopCommands = {
   startWidget,
   startPreset,
+  showWidget,
+  showPreset,
+  hideWidget,
+  hidePreset,
+  toggleWidget,
+  togglePreset,
+  resetWidgetWindow,
+  publish,
+  subscribe,
+  unsubscribe,
   listenProvider,
   unlistenProvider,
   callProviderFunction,
@@ -28,8 +38,11 @@ export const desktopCommands = {
 
 export type ProviderFunction =
   | AudioFunction
+  | GlazeWmFunction
+  | KomorebiFunction
   | MediaFunction
-  | SystrayFunction;
+  | SystrayFunction
+  | TaskbarFunction;
 
 export interface AudioFunction {
   type: 'audio';
@@ -42,6 +55,27 @@ export interface AudioFunction {
   };
 }
 
+export interface GlazeWmFunction {
+  type: 'glazewm';
+  function: {
+    name: 'run_command';
+    args: {
+      command: string;
+      subjectContainerId?: string;
+    };
+  };
+}
+
+export interface KomorebiFunction {
+  type: 'komorebi';
+  function: {
+    name: 'run_command';
+    args: {
+      command: { type: string; content?: unknown };
+    };
+  };
+}
+
 export interface MediaFunction {
   type: 'media';
   function: {
@@ -68,6 +102,16 @@ export interface SystrayFunction {
   };
 }
 
+export interface TaskbarFunction {
+  type: 'taskbar';
+  function: {
+    name: 'focus' | 'minimize' | 'maximize' | 'restore' | 'close';
+    args: {
+      windowId: number;
+    };
+  };
+}
+
 function startWidget(
   configPath: string,
   placement: WidgetPlacement,
@@ -82,6 +126,59 @@ function startPreset(
   return invoke<void>('start_preset', { configPath, presetName });
 }
 
+function showWidget(widgetId: string): Promise<void> {
+  return invoke<void>('show_widget', { widgetId });
+}
+
+function showPreset(
+  configPath: string,
+  presetName: string,
+): Promise<void> {
+  return invoke<void>('show_preset', { configPath, presetName });
+}
+
+function hideWidget(widgetId: string): Promise<void> {
+  return invoke<void>('hide_widget', { widgetId });
+}
+
+function hidePreset(
+  configPath: string,
+  presetName: string,
+): Promise<void> {
+  return invoke<void>('hide_preset', { configPath, presetName });
+}
+
+function toggleWidget(widgetId: string): Promise<void> {
+  return invoke<void>('toggle_widget', { widgetId });
+}
+
+function togglePreset(
+  configPath: string,
+  presetName: string,
+): Promise<void> {
+  return invoke<void>('toggle_preset', { configPath, presetName });
+}
+
+function resetWidgetWindow(widgetId: string): Promise<void> {
+  return invoke<void>('reset_widget_window', { widgetId });
+}
+
+function publish(
+  topic: string,
+  payload: unknown,
+  retain?: boolean,
+): Promise<void> {
+  return invoke<void>('publish', { topic, payload, retain });
+}
+
+function subscribe<T = unknown>(topic: string): Promise<T | null> {
+  return invoke<T | null>('subscribe', { topic });
+}
+
+function unsubscribe(topic: string): Promise<void> {
+  return invoke<void>('unsubscribe', { topic });
+}
+
 function listenProvider(args: {
   configHash: string;
   config: ProviderConfig;
@@ -1,5 +1,6 @@
 export * from './desktop-commands';
 export * from './dialogs';
+export * from './message-bus';
 export * from './monitors';
 export * from './provider-emit';
 export * from './shell';
@@ -0,0 +1,77 @@
+import { beforeEach, describe, expect, it, vi } from 'vitest';
+
+import { desktopCommands } from './desktop-commands';
+import { subscribe } from './message-bus';
+
+type Listener = (event: { payload: unknown }) => void;
+
+/** Listeners of each window, keyed by window label. */
+const windowListeners = new Map<string, Set<Listener>>();
+
+/** Sends an event to a single window, like `emit_to` in the backend. */
+function emitTo(label: string, payload: unknown) {
+  for (const listener of windowListeners.get(label) ?? []) {
+    listener({ payload });
+  }
+}
+
+vi.mock('@tauri-apps/api/webviewWindow', () => ({
+  getCurrentWebviewWindow: () => ({
+    listen: async (_event: string, listener: Listener) => {
+      const listeners = windowListeners.get('widget-a') ?? new Set();
+      listeners.add(listener);
+      windowListeners.set('widget-a', listeners);
+      return () => listeners.delete(listener);
+    },
+  }),
+}));
+
+vi.mock('./desktop-commands', () => ({
+  desktopCommands: {
+    subscribe: vi.fn(async () => null),
+    unsubscribe: vi.fn(async () => {}),
+  },
+}));
+
+describe('subscribe', () => {
+  beforeEach(() => {
+    windowListeners.clear();
+    vi.clearAllMocks();
+  });
+
+  it('delivers each message once to every subscriber', async () => {
+    const first = vi.fn();
+    const second = vi.fn();
+
+    const unsubscribeFirst = await subscribe('theme', first);
+    await subscribe('theme', second);
+
+    emitTo('widget-a', { topic: 'theme', payload: 'dark' });
+    emitTo('widget-a', { topic: 'other', payload: 'ignored' });
+    emitTo('widget-b', { topic: 'theme', payload: 'light' });
+
+    expect(first.mock.calls).toEqual([['dark']]);
+    expect(second.mock.calls).toEqual([['dark']]);
+
+    // The widget stays subscribed while another subscriber is left.
+    await unsubscribeFirst();
+    expect(desktopCommands.unsubscribe).not.toHaveBeenCalled();
+
+    emitTo('widget-a', { topic: 'theme', payload: 'light' });
+
+    expect(first.mock.calls).toEqual([['dark']]);
+    expect(second.mock.calls).toEqual([['dark'], ['light']]);
+  });
+
+  it('unsubscribes the widget once all subscribers are removed', async () => {
+    const unsubscribeFirst = await subscribe('theme', vi.fn());
+    const unsubscribeSecond = await subscribe('theme', vi.fn());
+
+    await unsubscribeFirst();
+    await unsubscribeFirst();
+    await unsubscribeSecond();
+
+    expect(desktopCommands.unsubscribe).toHaveBeenCalledTimes(1);
+    expect(desktopCommands.unsubscribe).toHaveBeenCalledWith('theme');
+  });
+});
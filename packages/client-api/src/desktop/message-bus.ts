@@ -0,0 +1,86 @@
+import type { Event } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+
+import { desktopCommands } from './desktop-commands';
+
+interface BusMessage<T = unknown> {
+  topic: string;
+  payload: T;
+}
+
+/**
+ * Number of subscriptions of the current widget per topic. The widget is
+ * only unsubscribed from a topic once all of them are removed.
+ */
+const subscriptionCounts = new Map<string, number>();
+
+export interface PublishOptions {
+  /**
+   * Keep the payload as the topic's last value, which is sent to widgets
+   * when they subscribe. Publishing `null` with `retain` clears it.
+   */
+  retain?: boolean;
+}
+
+/**
+ * Publishes a message to all widgets subscribed to the topic.
+ */
+export async function publish<T = unknown>(
+  topic: string,
+  payload: T,
+  options?: PublishOptions,
+): Promise<void> {
+  return desktopCommands.publish(topic, payload, options?.retain);
+}
+
+/**
+ * Subscribes the current widget to messages on a topic.
+ *
+ * The callback is invoked immediately with the topic's retained value
+ * (if any).
+ *
+ * @returns A function to unsubscribe.
+ */
+export async function subscribe<T = unknown>(
+  topic: string,
+  callback: (payload: T) => void,
+): Promise<() => Promise<void>> {
+  // Only listen to messages sent to this widget. A global listener would
+  // also receive messages sent to other widgets.
+  const unlisten = await getCurrentWebviewWindow().listen(
+    'bus-message',
+    (event: Event<BusMessage<T>>) => {
+      if (event.payload.topic === topic) {
+        callback(event.payload.payload);
+      }
+    },
+  );
+
+  subscriptionCounts.set(topic, (subscriptionCounts.get(topic) ?? 0) + 1);
+
+  const retained = await desktopCommands.subscribe<T>(topic);
+
+  if (retained !== null) {
+    callback(retained);
+  }
+
+  let isSubscribed = true;
+
+  return async () => {
+    if (!isSubscribed) {
+      return;
+    }
+
+    isSubscribed = false;
+    unlisten();
+
+    const count = (subscriptionCounts.get(topic) ?? 1) - 1;
+
+    if (count > 0) {
+      subscriptionCounts.set(topic, count);
+    } else {
+      subscriptionCounts.delete(topic);
+      await desktopCommands.unsubscribe(topic);
+    }
+  };
+}
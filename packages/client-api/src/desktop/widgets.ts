@@ -7,6 +7,9 @@ import { currentWindow, type WidgetWindow } from './windows';
 export interface Widget {
   /**
    * Unique identifier for the widget instance.
+   *
+   * Stays the same across relaunches for a given config path, preset
+   * and monitor.
    */
   id: string;
 
@@ -60,10 +63,7 @@ export async function startWidget(
   configPath: string,
   placement: WidgetPlacement,
 ) {
-  // Ensure the config path ends with '.zebar.json'.
-  const filePath = configPath.endsWith('.zebar.json')
-    ? configPath
-    : `${configPath}.zebar.json`;
+  const filePath = withConfigExtension(configPath);
 
   const absolutePath = await join(
     getWidgetState().configPath,
@@ -83,10 +83,7 @@ export async function startWidgetPreset(
   configPath: string,
   presetName: string,
 ) {
-  // Ensure the config path ends with '.zebar.json'.
-  const filePath = configPath.endsWith('.zebar.json')
-    ? configPath
-    : `${configPath}.zebar.json`;
+  const filePath = withConfigExtension(configPath);
 
   const absolutePath = await join(
     getWidgetState().configPath,
@@ -96,3 +93,86 @@ export async function startWidgetPreset(
 
   return desktopCommands.startPreset(absolutePath, presetName);
 }
+
+/**
+ * Shows the widgets of a preset, opening the preset if it isn't open.
+ *
+ * Config path is relative within the Zebar config directory.
+ */
+export async function showWidgetPreset(
+  configPath: string,
+  presetName: string,
+) {
+  return desktopCommands.showPreset(
+    await toAbsoluteConfigPath(configPath),
+    presetName,
+  );
+}
+
+/**
+ * Hides the widgets of a preset without closing them, so that they keep
+ * their state.
+ *
+ * Config path is relative within the Zebar config directory.
+ */
+export async function hideWidgetPreset(
+  configPath: string,
+  presetName: string,
+) {
+  return desktopCommands.hidePreset(
+    await toAbsoluteConfigPath(configPath),
+    presetName,
+  );
+}
+
+/**
+ * Hides the widgets of a preset if any are shown, and shows them
+ * otherwise.
+ *
+ * Config path is relative within the Zebar config directory.
+ */
+export async function toggleWidgetPreset(
+  configPath: string,
+  presetName: string,
+) {
+  return desktopCommands.togglePreset(
+    await toAbsoluteConfigPath(configPath),
+    presetName,
+  );
+}
+
+/**
+ * Hides the current widget without closing it.
+ */
+export async function hideCurrentWidget() {
+  return desktopCommands.hideWidget(getWidgetState().id);
+}
+
+/**
+ * Extensions of widget configs in each of the supported formats.
+ */
+const CONFIG_EXTENSION_REGEX = /\.zebar\.(json|json5|yaml|toml)$/;
+
+/**
+ * Ensures the config path ends with a widget config extension. Defaults
+ * to '.zebar.json'.
+ */
+function withConfigExtension(configPath: string) {
+  return CONFIG_EXTENSION_REGEX.test(configPath)
+    ? configPath
+    : `${configPath}.zebar.json`;
+}
+
+async function toAbsoluteConfigPath(configPath: string) {
+  const filePath = withConfigExtension(configPath);
+
+  return join(getWidgetState().configPath, '../', filePath);
+}
+
+/**
+ * Moves the current widget back to its configured placement, discarding
+ * where it was moved or resized to.
+ */
+export async function resetWidgetWindow() {
+  return desktopCommands.resetWidgetWindow(getWidgetState().id);
+}
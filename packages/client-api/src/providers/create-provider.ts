@@ -22,6 +22,18 @@ import type {
   GpuProvider,
 } from './gpu/gpu-provider-types';
 
+import { createGlazeWmProvider } from './glazewm/create-glazewm-provider';
+import type {
+  GlazeWmProviderConfig,
+  GlazeWmProvider,
+} from './glazewm/glazewm-provider-types';
+
+import { createKomorebiProvider } from './komorebi/create-komorebi-provider';
+import type {
+  KomorebiProviderConfig,
+  KomorebiProvider,
+} from './komorebi/komorebi-provider-types';
+
 import { createDateProvider } from './date/create-date-provider';
 import type {
   DateProviderConfig,
@@ -52,6 +64,12 @@ import type {
   SystrayProvider,
 } from './systray/systray-provider-types';
 
+import { createTaskbarProvider } from './taskbar/create-taskbar-provider';
+import type {
+  TaskbarProviderConfig,
+  TaskbarProvider,
+} from './taskbar/taskbar-provider-types';
+
 import { createWindowProvider } from './window/create-window-provider';
 import type {
   WindowProviderConfig,
@@ -64,10 +82,13 @@ export interface ProviderConfigMap {
   cpu: CpuProviderConfig;
   gpu: GpuProviderConfig;
   date: DateProviderConfig;
+  glazewm: GlazeWmProviderConfig;
+  komorebi: KomorebiProviderConfig;
   media: MediaProviderConfig;
   memory: MemoryProviderConfig;
   network: NetworkProviderConfig;
   systray: SystrayProviderConfig;
+  taskbar: TaskbarProviderConfig;
   window: WindowProviderConfig;
 }
 
@@ -77,10 +98,13 @@ export interface ProviderMap {
   cpu: CpuProvider;
   gpu: GpuProvider;
   date: DateProvider;
+  glazewm: GlazeWmProvider;
+  komorebi: KomorebiProvider;
   media: MediaProvider;
   memory: MemoryProvider;
   network: NetworkProvider;
   systray: SystrayProvider;
+  taskbar: TaskbarProvider;
   window: WindowProvider;
 }
 
@@ -115,6 +139,10 @@ export function createProvider<T extends ProviderConfig>(
       return createGpuProvider(config) as any;
     case 'date':
       return createDateProvider(config) as any;
+    case 'glazewm':
+      return createGlazeWmProvider(config) as any;
+    case 'komorebi':
+      return createKomorebiProvider(config) as any;
     case 'media':
       return createMediaProvider(config) as any;
     case 'memory':
@@ -123,6 +151,8 @@ export function createProvider<T extends ProviderConfig>(
       return createNetworkProvider(config) as any;
     case 'systray':
       return createSystrayProvider(config) as any;
+    case 'taskbar':
+      return createTaskbarProvider(config) as any;
     case 'window':
       return createWindowProvider(config) as any;
     default:
@@ -0,0 +1,44 @@
+import { z } from 'zod';
+
+import { createBaseProvider } from '../create-base-provider';
+import { desktopCommands, onProviderEmit } from '~/desktop';
+import type {
+  GlazeWmOutput,
+  GlazeWmProvider,
+  GlazeWmProviderConfig,
+} from './glazewm-provider-types';
+
+const glazeWmProviderConfigSchema = z.object({
+  type: z.literal('glazewm'),
+  ipcPort: z.coerce.number().default(6123),
+});
+
+export function createGlazeWmProvider(
+  config: GlazeWmProviderConfig,
+): GlazeWmProvider {
+  const mergedConfig = glazeWmProviderConfigSchema.parse(config);
+
+  return createBaseProvider(mergedConfig, async queue => {
+    return onProviderEmit<GlazeWmOutput>(
+      mergedConfig,
+      ({ configHash, result }) => {
+        if ('error' in result) {
+          queue.error(result.error);
+        } else {
+          queue.output({
+            ...result.output,
+            runCommand: (command: string, subjectContainerId?: string) => {
+              return desktopCommands.callProviderFunction(configHash, {
+                type: 'glazewm',
+                function: {
+                  name: 'run_command',
+                  args: { command, subjectContainerId },
+                },
+              });
+            },
+          });
+        }
+      },
+    );
+  });
+}
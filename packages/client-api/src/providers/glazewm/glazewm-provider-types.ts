@@ -0,0 +1,33 @@
+import type { Provider } from '../create-base-provider';
+
+export interface GlazeWmProviderConfig {
+  type: 'glazewm';
+
+  /**
+   * Port of the GlazeWM IPC server. Defaults to `6123`.
+   */
+  ipcPort?: number;
+}
+
+export type GlazeWmProvider = Provider<
+  GlazeWmProviderConfig,
+  GlazeWmOutput
+>;
+
+export interface GlazeWmOutput {
+  allMonitors: any[];
+  allWorkspaces: any[];
+  focusedMonitor: any | null;
+  focusedWorkspace: any | null;
+  focusedContainer: any | null;
+  bindingModes: { name: string; displayName?: string | null }[];
+
+  /**
+   * Runs a WM command (e.g. `focus --workspace 1`).
+   *
+   * @param command - WM command to run.
+   * @param subjectContainerId - ID of container to use as subject. If not
+   * provided, this defaults to the currently focused container.
+   */
+  runCommand(command: string, subjectContainerId?: string): Promise<void>;
+}
@@ -3,10 +3,13 @@ export * from './battery/battery-provider-types';
 export * from './cpu/cpu-provider-types';
 export * from './gpu/gpu-provider-types';
 export * from './date/date-provider-types';
+export * from './glazewm/glazewm-provider-types';
+export * from './komorebi/komorebi-provider-types';
 export * from './media/media-provider-types';
 export * from './memory/memory-provider-types';
 export * from './network/network-provider-types';
 export * from './systray/systray-provider-types';
+export * from './taskbar/taskbar-provider-types';
 export * from './window/window-provider-types';
 export * from './create-provider';
 export * from './create-provider-group';
@@ -0,0 +1,44 @@
+import { z } from 'zod';
+
+import { createBaseProvider } from '../create-base-provider';
+import { desktopCommands, onProviderEmit } from '~/desktop';
+import type {
+  KomorebiOutput,
+  KomorebiProvider,
+  KomorebiProviderConfig,
+} from './komorebi-provider-types';
+
+const komorebiProviderConfigSchema = z.object({
+  type: z.literal('komorebi'),
+  socketDir: z.string().optional(),
+});
+
+export function createKomorebiProvider(
+  config: KomorebiProviderConfig,
+): KomorebiProvider {
+  const mergedConfig = komorebiProviderConfigSchema.parse(config);
+
+  return createBaseProvider(mergedConfig, async queue => {
+    return onProviderEmit<KomorebiOutput>(
+      mergedConfig,
+      ({ configHash, result }) => {
+        if ('error' in result) {
+          queue.error(result.error);
+        } else {
+          queue.output({
+            ...result.output,
+            runCommand: (command: { type: string; content?: unknown }) => {
+              return desktopCommands.callProviderFunction(configHash, {
+                type: 'komorebi',
+                function: {
+                  name: 'run_command',
+                  args: { command },
+                },
+              });
+            },
+          });
+        }
+      },
+    );
+  });
+}
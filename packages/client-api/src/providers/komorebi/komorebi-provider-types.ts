@@ -0,0 +1,32 @@
+import type { Provider } from '../create-base-provider';
+
+export interface KomorebiProviderConfig {
+  type: 'komorebi';
+
+  /**
+   * Directory containing komorebi's sockets. Defaults to komorebi's data
+   * directory (i.e. `%LOCALAPPDATA%/komorebi`).
+   */
+  socketDir?: string;
+}
+
+export type KomorebiProvider = Provider<
+  KomorebiProviderConfig,
+  KomorebiOutput
+>;
+
+export interface KomorebiOutput {
+  allMonitors: any[];
+  focusedMonitorIndex: number;
+  focusedMonitor: any | null;
+  focusedWorkspace: any | null;
+  lastEvent: { type: string; content?: unknown } | null;
+
+  /**
+   * Sends a socket message to komorebi (e.g.
+   * `{ type: 'FocusWorkspaceNumber', content: 1 }`).
+   *
+   * @param command - Socket message to send.
+   */
+  runCommand(command: { type: string; content?: unknown }): Promise<void>;
+}
@@ -0,0 +1,81 @@
+import { z } from 'zod';
+
+import { createBaseProvider } from '../create-base-provider';
+import {
+  desktopCommands,
+  onProviderEmit,
+  type TaskbarFunction,
+} from '~/desktop';
+import type {
+  TaskbarOutput,
+  TaskbarProvider,
+  TaskbarProviderConfig,
+} from './taskbar-provider-types';
+
+const taskbarProviderConfigSchema = z.object({
+  type: z.literal('taskbar'),
+});
+
+export function createTaskbarProvider(
+  config: TaskbarProviderConfig,
+): TaskbarProvider {
+  const mergedConfig = taskbarProviderConfigSchema.parse(config);
+
+  return createBaseProvider(mergedConfig, async queue => {
+    return onProviderEmit<TaskbarOutput>(
+      mergedConfig,
+      ({ configHash, result }) => {
+        if ('error' in result) {
+          queue.error(result.error);
+        } else {
+          const callWindowFunction = (
+            name: TaskbarFunction['function']['name'],
+            windowId: number,
+          ) => {
+            return desktopCommands.callProviderFunction(configHash, {
+              type: 'taskbar',
+              function: {
+                name,
+                args: { windowId },
+              },
+            });
+          };
+
+          queue.output({
+            ...result.output,
+            windows: result.output.windows.map(window => {
+              if (!window.iconBytes) {
+                return { ...window, iconBlob: null, iconUrl: null };
+              }
+
+              const iconBlob = new Blob([new Uint8Array(window.iconBytes)], {
+                type: 'image/png',
+              });
+
+              return {
+                ...window,
+                iconBlob,
+                iconUrl: URL.createObjectURL(iconBlob),
+              };
+            }),
+            focus: (windowId: number) => {
+              return callWindowFunction('focus', windowId);
+            },
+            minimize: (windowId: number) => {
+              return callWindowFunction('minimize', windowId);
+            },
+            maximize: (windowId: number) => {
+              return callWindowFunction('maximize', windowId);
+            },
+            restore: (windowId: number) => {
+              return callWindowFunction('restore', windowId);
+            },
+            close: (windowId: number) => {
+              return callWindowFunction('close', windowId);
+            },
+          });
+        }
+      },
+    );
+  });
+}
@@ -0,0 +1,59 @@
+import type { Provider } from '../create-base-provider';
+
+export interface TaskbarProviderConfig {
+  type: 'taskbar';
+}
+
+export type TaskbarProvider = Provider<
+  TaskbarProviderConfig,
+  TaskbarOutput
+>;
+
+export interface TaskbarOutput {
+  /**
+   * All top-level windows, in the order they were opened.
+   */
+  windows: TaskbarWindow[];
+
+  /**
+   * Changes since the previous output.
+   */
+  changes: TaskbarChange[];
+
+  focus: (windowId: number) => Promise<void>;
+  minimize: (windowId: number) => Promise<void>;
+  maximize: (windowId: number) => Promise<void>;
+  restore: (windowId: number) => Promise<void>;
+  close: (windowId: number) => Promise<void>;
+}
+
+export interface TaskbarWindow {
+  /**
+   * Window handle on Windows, or the X window ID on Linux.
+   */
+  id: number;
+  title: string;
+  processName: string | null;
+  processPath: string | null;
+  pid: number | null;
+  iconBytes: number[] | null;
+  iconBlob: Blob | null;
+  iconUrl: string | null;
+
+  /**
+   * Name of the monitor that the window is on.
+   */
+  monitor: string | null;
+  isMinimized: boolean;
+  isMaximized: boolean;
+
+  /**
+   * Whether the window is requesting attention (e.g. flashing).
+   */
+  isUrgent: boolean;
+}
+
+export interface TaskbarChange {
+  type: 'add' | 'update' | 'remove';
+  windowId: number;
+}
@@ -8,5 +8,46 @@ export type WindowProvider = Provider<WindowProviderConfig, WindowOutput>;
 
 export interface WindowOutput {
   title: string;
+
+  /**
+   * Window handle on Windows, or the X window ID on Linux.
+   */
   hwnd: number;
+
+  /**
+   * Window class name (`WM_CLASS` on Linux).
+   */
+  class: string | null;
+
+  /**
+   * ID of the process that owns the window.
+   */
+  pid: number | null;
+
+  /**
+   * File name of the executable that owns the window (e.g. `firefox.exe`).
+   */
+  processName: string | null;
+
+  /**
+   * Position and size of the window in screen coordinates.
+   */
+  bounds: WindowBounds | null;
+
+  /**
+   * Name of the monitor that the window is on.
+   */
+  monitor: string | null;
+
+  /**
+   * Whether the window covers its entire monitor.
+   */
+  isFullscreen: boolean;
+}
+
+export interface WindowBounds {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
 }
@@ -18,7 +18,7 @@ use uuid::Uuid;
 use crate::common::PathExt;
 
 /// Port for the localhost asset server.
-const ASSET_SERVER_PORT: u16 = 6124;
+pub const ASSET_SERVER_PORT: u16 = 6124;
 
 /// Map of tokens to their corresponding path.
 static ASSET_SERVER_TOKENS: LazyLock<Mutex<HashMap<String, PathBuf>>> =
@@ -1,8 +1,13 @@
 use std::{path::PathBuf, process};
 
+use anyhow::Context;
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
 
-use crate::{common::LengthValue, config::AnchorPoint};
+use crate::{
+  common::LengthValue,
+  config::{AnchorPoint, MonitorSelection},
+};
 
 const VERSION: &'static str = env!("VERSION_NUMBER");
 
@@ -23,7 +28,8 @@ impl Cli {
 pub enum CliCommand {
   /// Opens a widget by its config path and chosen placement.
   ///
-  /// Config path is relative within the Zebar config directory, e.g.
+  /// Config path is relative to the working directory or the Zebar
+  /// config directory, e.g.
   /// `zebar start-widget --path starter/vanilla`.
   ///
   /// Starts Zebar if it is not already running.
@@ -31,7 +37,8 @@ pub enum CliCommand {
 
   /// Opens a widget by its config path and a preset name.
   ///
-  /// Config path is relative within the Zebar config directory, e.g.
+  /// Config path is relative to the working directory or the Zebar
+  /// config directory, e.g.
   /// `zebar start-widget-preset --path starter/vanilla --preset default`.
   ///
   /// Starts Zebar if it is not already running.
@@ -42,9 +49,101 @@ pub enum CliCommand {
   /// Starts Zebar if it is not already running.
   Startup(StartupArgs),
 
-  /// Retrieves and outputs a specific part of the state.
+  /// Shows the widgets of a preset, opening the preset if it isn't
+  /// already open, e.g.
+  /// `zebar show --path starter/vanilla --preset default`.
+  ///
+  /// Starts Zebar if it is not already running.
+  Show(WidgetPresetArgs),
+
+  /// Hides the widgets of a preset without closing them, so that they
+  /// keep their state, e.g.
+  /// `zebar hide --path starter/vanilla --preset default`.
+  Hide(WidgetPresetArgs),
+
+  /// Hides the widgets of a preset if any are shown, and shows them
+  /// otherwise, e.g. `zebar toggle --path starter/vanilla --preset default`.
+  ///
+  /// Starts Zebar if it is not already running.
+  Toggle(WidgetPresetArgs),
+
+  /// Moves a widget back to its configured placement, discarding where
+  /// it was moved or resized to, e.g.
+  /// `zebar reset-widget --id widget-1a2b3c4d5e6f`.
+  ///
+  /// Starts Zebar if it is not already running.
+  ResetWidget(ResetWidgetArgs),
+
+  /// Publishes a message to widgets subscribed to the topic, e.g.
+  /// `zebar publish calendar/date '{"date":"2024-01-01"}'`.
+  ///
+  /// Requires an already running instance of Zebar.
+  Publish(PublishArgs),
+
+  /// Outputs the open widgets as JSON.
+  ///
+  /// Requires an already running instance of Zebar.
+  ListWidgets,
+
+  /// Closes a widget by its ID, e.g.
+  /// `zebar stop-widget --id widget-1a2b3c4d5e6f`.
+  ///
+  /// Requires an already running instance of Zebar.
+  StopWidget(StopWidgetArgs),
+
+  /// Closes the widgets of a preset, e.g.
+  /// `zebar stop-preset --path starter/vanilla --preset default`.
   ///
   /// Requires an already running instance of Zebar.
+  StopPreset(WidgetPresetArgs),
+
+  /// Empties the cache and reloads all widget configs from disk.
+  ///
+  /// Requires an already running instance of Zebar.
+  Reload,
+
+  /// Lists or switches between the profiles in the settings, e.g.
+  /// `zebar profile switch work`.
+  ///
+  /// Switching starts Zebar if it is not already running.
+  #[clap(subcommand)]
+  Profile(ProfileArgs),
+
+  /// Validates the settings and widget configs within a config
+  /// directory, and outputs any errors as JSON.
+  ///
+  /// Exits with a non-zero code if any config is invalid. Doesn't
+  /// require or start Zebar.
+  Validate(CheckArgs),
+
+  /// Validates configs like `zebar validate`, and additionally checks
+  /// that the environment is able to run Zebar (e.g. asset server port,
+  /// data directories and webview runtime).
+  ///
+  /// Exits with a non-zero code if any check fails. Doesn't require or
+  /// start Zebar.
+  Doctor(CheckArgs),
+
+  /// Installs, lists, removes or exports widget packs (folders of
+  /// widgets with a `zebar-pack.json` manifest), e.g.
+  /// `zebar pack install my-bar.zip`.
+  ///
+  /// Doesn't require or start Zebar. A running instance picks up the
+  /// changes automatically.
+  #[clap(subcommand)]
+  Pack(PackArgs),
+
+  /// Upgrades settings and widget configs from older versions of Zebar
+  /// to the current format. Previous versions are kept as backups.
+  ///
+  /// Zebar also reads older configs as is, but only this command writes
+  /// the upgraded files. Doesn't require or start Zebar.
+  Migrate(MigrateArgs),
+
+  /// Retrieves and outputs a specific part of the state as JSON.
+  ///
+  /// Requires an already running instance of Zebar, except for
+  /// `zebar query monitors`.
   #[clap(subcommand)]
   Query(QueryArgs),
 
@@ -56,10 +155,28 @@ pub enum CliCommand {
   Empty,
 }
 
+impl CliCommand {
+  /// Whether the command can only be run against an already running
+  /// instance of Zebar, rather than starting one.
+  pub fn requires_running_instance(&self) -> bool {
+    matches!(
+      self,
+      Self::Hide(_)
+        | Self::Publish(_)
+        | Self::ListWidgets
+        | Self::StopWidget(_)
+        | Self::StopPreset(_)
+        | Self::Reload
+        | Self::Profile(ProfileArgs::List)
+        | Self::Query(QueryArgs::Widgets | QueryArgs::Configs | QueryArgs::Providers)
+    )
+  }
+}
+
 #[derive(Args, Clone, Debug, PartialEq)]
 pub struct StartWidgetArgs {
-  /// Relative file path to widget config within the Zebar config
-  /// directory.
+  /// File path to widget config. Relative paths are resolved against
+  /// the working directory, or the Zebar config directory if not found.
   #[clap(long = "path", value_hint = clap::ValueHint::FilePath)]
   pub config_path: PathBuf,
 
@@ -84,23 +201,91 @@ pub struct StartWidgetArgs {
   pub height: LengthValue,
 
   /// Monitor(s) to place the widget on.
+  #[clap(long, required_unless_present = "monitor_selection")]
+  pub monitor_type: Option<MonitorType>,
+
+  /// Value to match for the `index`, `name`, `name_regex` and
+  /// `min_width` monitor types.
   #[clap(long)]
-  pub monitor_type: MonitorType,
+  pub monitor_match: Option<String>,
+
+  /// Monitor selection as JSON, in the same format as in widget configs.
+  ///
+  /// Allows for combined selections, e.g. `{"type": "all_except",
+  /// "match": [{"type": "primary"}]}`.
+  #[clap(long, conflicts_with_all = ["monitor_type", "monitor_match"], value_parser = parse_monitor_selection)]
+  pub monitor_selection: Option<MonitorSelection>,
+}
+
+impl StartWidgetArgs {
+  /// Gets the monitor selection from either `--monitor-selection` or
+  /// `--monitor-type` and `--monitor-match`.
+  pub fn monitor_selection(&self) -> anyhow::Result<MonitorSelection> {
+    if let Some(monitor_selection) = &self.monitor_selection {
+      return Ok(monitor_selection.clone());
+    }
+
+    let monitor_match = || {
+      self
+        .monitor_match
+        .clone()
+        .context("Monitor type requires a `--monitor-match` value.")
+    };
+
+    let monitor_selection = match self.monitor_type {
+      None | Some(MonitorType::All) => MonitorSelection::All,
+      Some(MonitorType::Primary) => MonitorSelection::Primary,
+      Some(MonitorType::Secondary) => MonitorSelection::Secondary,
+      Some(MonitorType::Index) => MonitorSelection::Index(monitor_match()?.parse()?),
+      Some(MonitorType::Name) => MonitorSelection::Name(monitor_match()?),
+      Some(MonitorType::NameRegex) => MonitorSelection::NameRegex(monitor_match()?),
+      Some(MonitorType::Largest) => MonitorSelection::Largest,
+      Some(MonitorType::Portrait) => MonitorSelection::Portrait,
+      Some(MonitorType::Landscape) => MonitorSelection::Landscape,
+      Some(MonitorType::MinWidth) => MonitorSelection::MinWidth(monitor_match()?.parse()?),
+    };
+
+    Ok(monitor_selection)
+  }
 }
 
-/// TODO: Add support for `Index` and `Name` types.
+/// Monitor selections that can be made without JSON. Combined selections
+/// require `--monitor-selection`.
 #[derive(Clone, Debug, PartialEq, ValueEnum)]
 #[clap(rename_all = "snake_case")]
 pub enum MonitorType {
   All,
   Primary,
   Secondary,
+  Index,
+  Name,
+  NameRegex,
+  Largest,
+  Portrait,
+  Landscape,
+  MinWidth,
+}
+
+fn parse_monitor_selection(value: &str) -> Result<MonitorSelection, String> {
+  serde_json::from_str(value).map_err(|err| err.to_string())
 }
 
 #[derive(Args, Clone, Debug, PartialEq)]
 pub struct StartWidgetPresetArgs {
-  /// Relative file path to widget config within the Zebar config
-  /// directory.
+  /// File path to widget config. Relative paths are resolved against
+  /// the working directory, or the Zebar config directory if not found.
+  #[clap(long = "path", value_hint = clap::ValueHint::FilePath)]
+  pub config_path: PathBuf,
+
+  /// Name of the preset within the target widget config.
+  #[clap(long = "preset")]
+  pub preset_name: String,
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct WidgetPresetArgs {
+  /// File path to widget config. Relative paths are resolved against
+  /// the working directory, or the Zebar config directory if not found.
   #[clap(long = "path", value_hint = clap::ValueHint::FilePath)]
   pub config_path: PathBuf,
 
@@ -118,10 +303,184 @@ pub struct StartupArgs {
   pub config_dir: Option<PathBuf>,
 }
 
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct CheckArgs {
+  /// Absolute or relative path to the Zebar config directory.
+  ///
+  /// The default path is `%userprofile%/.glzr/zebar/`
+  #[clap(long, value_hint = clap::ValueHint::FilePath)]
+  pub config_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct MigrateArgs {
+  /// Outputs a diff of the changes instead of writing them.
+  #[clap(long)]
+  pub dry_run: bool,
+
+  /// Absolute or relative path to the Zebar config directory.
+  ///
+  /// The default path is `%userprofile%/.glzr/zebar/`
+  #[clap(long, value_hint = clap::ValueHint::FilePath)]
+  pub config_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct ResetWidgetArgs {
+  /// ID of the widget to reset.
+  #[clap(long = "id", required_unless_present = "all")]
+  pub widget_id: Option<String>,
+
+  /// Resets all widgets.
+  #[clap(long, conflicts_with = "widget_id")]
+  pub all: bool,
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct StopWidgetArgs {
+  /// ID of the widget to close.
+  #[clap(long = "id")]
+  pub widget_id: String,
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct PublishArgs {
+  /// Topic to publish the message on.
+  pub topic: String,
+
+  /// JSON payload of the message.
+  #[clap(value_parser = parse_json, default_value = "null")]
+  pub payload: serde_json::Value,
+
+  /// Keeps the payload as the topic's last value, which is sent to
+  /// widgets when they subscribe.
+  #[clap(long)]
+  pub retain: bool,
+}
+
+fn parse_json(value: &str) -> Result<serde_json::Value, String> {
+  serde_json::from_str(value).map_err(|err| err.to_string())
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub enum ProfileArgs {
+  /// Outputs the profiles and which one is active.
+  ///
+  /// Requires an already running instance of Zebar.
+  List,
+
+  /// Switches to a profile. Only the widgets that differ from the
+  /// current profile are opened and closed.
+  Switch(ProfileSwitchArgs),
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct ProfileSwitchArgs {
+  /// Name of the profile to switch to.
+  pub name: String,
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub enum PackArgs {
+  /// Installs a pack from a `.zip` archive or a directory, or updates
+  /// it if already installed.
+  ///
+  /// Shell commands requested by the pack's widgets are shown for
+  /// approval before installing.
+  Install(PackInstallArgs),
+
+  /// Outputs the packs within the config directory as JSON.
+  List(CheckArgs),
+
+  /// Removes an installed pack.
+  Remove(PackRemoveArgs),
+
+  /// Exports a pack to a `.zip` archive for sharing.
+  Export(PackExportArgs),
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct PackInstallArgs {
+  /// Path to a `.zip` archive or a directory containing a
+  /// `zebar-pack.json`.
+  #[clap(value_hint = clap::ValueHint::FilePath)]
+  pub source: PathBuf,
+
+  /// Overwrite the installed pack even if it has local changes.
+  #[clap(long)]
+  pub force: bool,
+
+  /// Approve any shell commands requested by the pack without
+  /// prompting.
+  #[clap(long, short)]
+  pub yes: bool,
+
+  /// Absolute or relative path to the Zebar config directory.
+  ///
+  /// The default path is `%userprofile%/.glzr/zebar/`
+  #[clap(long, value_hint = clap::ValueHint::FilePath)]
+  pub config_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct PackRemoveArgs {
+  /// Name of the pack, as in its `zebar-pack.json`.
+  pub name: String,
+
+  /// Remove the pack even if it has local changes.
+  #[clap(long)]
+  pub force: bool,
+
+  /// Absolute or relative path to the Zebar config directory.
+  ///
+  /// The default path is `%userprofile%/.glzr/zebar/`
+  #[clap(long, value_hint = clap::ValueHint::FilePath)]
+  pub config_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct PackExportArgs {
+  /// Path to the pack's directory.
+  #[clap(value_hint = clap::ValueHint::DirPath)]
+  pub path: PathBuf,
+
+  /// Path of the archive to create. Defaults to `<name>-<version>.zip`
+  /// in the current directory.
+  #[clap(long, short, value_hint = clap::ValueHint::FilePath)]
+  pub output: Option<PathBuf>,
+}
+
 #[derive(Clone, Debug, Parser, PartialEq)]
 pub enum QueryArgs {
   /// Outputs available monitors.
   Monitors,
+
+  /// Outputs the full state of open widgets.
+  Widgets,
+
+  /// Outputs all widget configs within the config directory.
+  Configs,
+
+  /// Outputs active providers and their latest emissions.
+  Providers,
+}
+
+/// Prints a report as JSON to stdout and exits the process. Exits with a
+/// non-zero code if the report isn't successful.
+pub fn print_report_and_exit<T: Serialize>(report: anyhow::Result<T>, is_success: fn(&T) -> bool) {
+  match report {
+    Ok(report) => {
+      let exit_code = if is_success(&report) { 0 } else { 1 };
+
+      match serde_json::to_string_pretty(&report) {
+        Ok(output) => println!("{}", output),
+        Err(err) => print_and_exit(Err(err.into())),
+      }
+
+      process::exit(exit_code);
+    }
+    Err(err) => print_and_exit(Err(err)),
+  }
 }
 
 /// Prints to stdout/stderror and exits the process.
@@ -2,7 +2,7 @@ use std::{collections::HashMap, path::PathBuf, sync::Arc};
 use serde_json::Value;
 
 use tauri::{AppHandle, State, Window};
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "linux"))]
 use window_util::Window as UtilWindow;
 
 #[cfg(target_os = "macos")]
@@ -11,9 +11,14 @@ use crate::common::macos::WindowExtMacOs;
 use crate::common::windows::WindowExtWindows;
 use crate::{
   config::{Config, WidgetConfig, WidgetPlacement},
+  config_diagnostics::ConfigDiagnostic,
+  message_bus::MessageBus,
+  pack_manager::{InstalledPack, PackManager, PackPreview, StagedPacks},
   providers::{ProviderConfig, ProviderFunction, ProviderFunctionResponse, ProviderManager},
   shell_state::{ShellCommandArgs, ShellState},
+  sys_tray::SETTINGS_WINDOW_LABEL,
   widget_factory::{WidgetFactory, WidgetOpenOptions, WidgetState},
+  widget_pack::WidgetPack,
 };
 
 #[tauri::command]
@@ -23,6 +28,107 @@ pub async fn widget_configs(
   Ok(config.widget_configs().await)
 }
 
+#[tauri::command]
+pub async fn widget_packs(
+  config: State<'_, Arc<Config>>,
+) -> Result<Vec<WidgetPack>, String> {
+  Ok(config.widget_packs().await)
+}
+
+#[tauri::command]
+pub async fn list_packs(config: State<'_, Arc<Config>>) -> Result<Vec<InstalledPack>, String> {
+  PackManager::new(&config.config_dir)
+    .list()
+    .map_err(|err| err.to_string())
+}
+
+/// Fails unless the command was invoked by the settings window, so that
+/// widgets can't install packs without the user's approval.
+fn ensure_settings_window(window: &Window) -> Result<(), String> {
+  match window.label() == SETTINGS_WINDOW_LABEL {
+    true => Ok(()),
+    false => Err("Packs can only be managed from the settings window.".to_string()),
+  }
+}
+
+/// Returns what installing the pack would do (e.g. which shell commands
+/// it requests), so that the user can approve it before `install_pack`.
+#[tauri::command]
+pub async fn preview_pack(
+  source: String,
+  window: Window,
+  config: State<'_, Arc<Config>>,
+  staged_packs: State<'_, StagedPacks>,
+) -> Result<PackPreview, String> {
+  ensure_settings_window(&window)?;
+
+  let staged = PackManager::new(&config.config_dir)
+    .stage(&PathBuf::from(source))
+    .map_err(|err| err.to_string())?;
+
+  let preview = staged.preview.clone();
+  staged_packs.insert(staged).await;
+
+  Ok(preview)
+}
+
+/// Installs a pack that was approved by the user in the settings window,
+/// given the install token from its `preview_pack` result.
+#[tauri::command]
+pub async fn install_pack(
+  install_token: String,
+  force: bool,
+  window: Window,
+  config: State<'_, Arc<Config>>,
+  staged_packs: State<'_, StagedPacks>,
+) -> Result<WidgetPack, String> {
+  ensure_settings_window(&window)?;
+
+  let staged = staged_packs
+    .take(&install_token)
+    .await
+    .map_err(|err| err.to_string())?;
+
+  PackManager::new(&config.config_dir)
+    .install(&staged, force)
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_pack(
+  name: String,
+  force: bool,
+  window: Window,
+  config: State<'_, Arc<Config>>,
+) -> Result<WidgetPack, String> {
+  ensure_settings_window(&window)?;
+
+  PackManager::new(&config.config_dir)
+    .remove(&name, force)
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn export_pack(
+  path: String,
+  output: Option<String>,
+  window: Window,
+  config: State<'_, Arc<Config>>,
+) -> Result<PathBuf, String> {
+  ensure_settings_window(&window)?;
+
+  PackManager::new(&config.config_dir)
+    .export_installed(&PathBuf::from(path), output.map(PathBuf::from))
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn config_diagnostics(
+  config: State<'_, Arc<Config>>,
+) -> Result<Vec<ConfigDiagnostic>, String> {
+  Ok(config.diagnostics.all())
+}
+
 #[tauri::command]
 pub async fn widget_states(
   widget_factory: State<'_, Arc<WidgetFactory>>,
@@ -72,6 +178,86 @@ pub async fn stop_preset(
     .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+pub async fn show_widget(
+  widget_id: String,
+  widget_factory: State<'_, Arc<WidgetFactory>>,
+) -> anyhow::Result<(), String> {
+  widget_factory
+    .show_by_id(&widget_id)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn show_preset(
+  config_path: String,
+  preset_name: String,
+  widget_factory: State<'_, Arc<WidgetFactory>>,
+) -> anyhow::Result<(), String> {
+  widget_factory
+    .show_by_preset(&PathBuf::from(config_path), &preset_name)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn hide_widget(
+  widget_id: String,
+  widget_factory: State<'_, Arc<WidgetFactory>>,
+) -> anyhow::Result<(), String> {
+  widget_factory
+    .hide_by_id(&widget_id)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn hide_preset(
+  config_path: String,
+  preset_name: String,
+  widget_factory: State<'_, Arc<WidgetFactory>>,
+) -> anyhow::Result<(), String> {
+  widget_factory
+    .hide_by_preset(&PathBuf::from(config_path), &preset_name)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn toggle_widget(
+  widget_id: String,
+  widget_factory: State<'_, Arc<WidgetFactory>>,
+) -> anyhow::Result<(), String> {
+  widget_factory
+    .toggle_by_id(&widget_id)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn toggle_preset(
+  config_path: String,
+  preset_name: String,
+  widget_factory: State<'_, Arc<WidgetFactory>>,
+) -> anyhow::Result<(), String> {
+  widget_factory
+    .toggle_by_preset(&PathBuf::from(config_path), &preset_name)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn reset_widget_window(
+  widget_id: String,
+  widget_factory: State<'_, Arc<WidgetFactory>>,
+) -> anyhow::Result<(), String> {
+  widget_factory
+    .reset_window_states(&[widget_id])
+    .await
+    .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn update_widget_config(
   config_path: String,
@@ -84,6 +270,50 @@ pub async fn update_widget_config(
     .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+pub fn can_undo_config_change(config: State<'_, Arc<Config>>) -> bool {
+  config.can_undo()
+}
+
+#[tauri::command]
+pub async fn undo_config_change(
+  config: State<'_, Arc<Config>>,
+) -> Result<Option<PathBuf>, String> {
+  config.undo().await.map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn publish(
+  topic: String,
+  payload: Value,
+  retain: Option<bool>,
+  message_bus: State<'_, Arc<MessageBus>>,
+) -> anyhow::Result<(), String> {
+  message_bus
+    .publish(&topic, payload, retain.unwrap_or(false))
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn subscribe(
+  topic: String,
+  window: Window,
+  message_bus: State<'_, Arc<MessageBus>>,
+) -> anyhow::Result<Option<Value>, String> {
+  Ok(message_bus.subscribe(window.label(), &topic).await)
+}
+
+#[tauri::command]
+pub async fn unsubscribe(
+  topic: String,
+  window: Window,
+  message_bus: State<'_, Arc<MessageBus>>,
+) -> anyhow::Result<(), String> {
+  message_bus.unsubscribe(window.label(), &topic).await;
+  Ok(())
+}
+
 #[tauri::command]
 pub async fn listen_provider(
   config_hash: String,
@@ -198,15 +428,26 @@ pub async fn shell_kill(
 
 #[tauri::command]
 pub fn set_foreground_window(hwnd: isize) -> Result<String, String> {
-  match UtilWindow::set_foreground_window(hwnd) {
-    Ok(_) => Ok(format!(
-      "Successfully set window {} to the foreground.",
+  #[cfg(any(target_os = "windows", target_os = "linux"))]
+  {
+    match UtilWindow::set_foreground_window(hwnd) {
+      Ok(_) => Ok(format!(
+        "Successfully set window {} to the foreground.",
+        hwnd
+      )),
+      Err(err) => Err(format!(
+        "Failed to set window {} to the foreground: {}",
+        hwnd, err
+      )),
+    }
+  }
+
+  #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+  {
+    Err(format!(
+      "Setting the foreground window is not supported on this platform (window {}).",
       hwnd
-    )),
-    Err(err) => Err(format!(
-      "Failed to set window {} to the foreground: {}",
-      hwnd, err
-    )),
+    ))
   }
 }
 
@@ -217,9 +458,12 @@ pub fn show_menu(
   sub_items: Vec<HashMap<String, Value>>,
   button_x: i32,
   monitor_y: i32,
+  window: Window,
   config: State<'_, Arc<Config>>,
+  shell_state: State<'_, ShellState>,
 ) -> Result<String, String> {
   let app_handle: &AppHandle = config.app_handle();
+  shell_state.set_menu_owner(window.label());
 
   #[cfg(target_os = "windows")]
   {
@@ -1,4 +1,8 @@
-use std::{fs, path::PathBuf};
+use std::{
+  fs,
+  io::Write,
+  path::{Path, PathBuf},
+};
 
 use anyhow::Context;
 use serde::de::DeserializeOwned;
@@ -16,15 +20,6 @@ pub fn read_and_parse_json<T: DeserializeOwned>(path: &PathBuf) -> anyhow::Resul
   Ok(parsed)
 }
 
-/// Returns whether the path has the given extension.
-pub fn has_extension(path: &PathBuf, extension: &str) -> bool {
-  path
-    .file_name()
-    .and_then(|name| name.to_str())
-    .map(|name| name.ends_with(extension))
-    .unwrap_or(false)
-}
-
 /// Recursively copies a directory and all its contents to a new file
 /// location.
 ///
@@ -50,3 +45,28 @@ pub fn copy_dir_all(
 
   Ok(())
 }
+
+/// Writes content to a file by writing to a temporary file next to it and
+/// renaming it over the original. A crash mid-write therefore leaves
+/// either the old or the new content, and never a truncated file.
+pub fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> anyhow::Result<()> {
+  let file_name = path
+    .file_name()
+    .with_context(|| format!("Invalid file path: {}", path.display()))?;
+
+  let temp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+  let res = (|| -> anyhow::Result<()> {
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(content.as_ref())?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+  })();
+
+  if res.is_err() {
+    let _ = fs::remove_file(&temp_path);
+  }
+
+  res.with_context(|| format!("Failed to write file: {}", path.display()))
+}
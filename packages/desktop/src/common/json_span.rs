@@ -0,0 +1,244 @@
+use std::ops::Range;
+
+/// Splits a JSON pointer (e.g. `/presets/0/anchor`) into its unescaped
+/// segments.
+pub fn pointer_segments(pointer: &str) -> Vec<String> {
+  pointer
+    .split('/')
+    .skip(1)
+    .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+    .collect()
+}
+
+/// Finds the byte range of the value at the given path within JSON or
+/// JSON5 text.
+///
+/// Supports comments, unquoted keys, single-quoted strings and trailing
+/// commas, so that values can be located in hand-written configs.
+pub fn value_span(json: &str, path: &[String]) -> Option<Range<usize>> {
+  let bytes = json.as_bytes();
+  let mut pos = skip_whitespace(bytes, 0);
+
+  for segment in path {
+    pos = match bytes.get(pos)? {
+      b'{' => find_object_value(json, pos, segment)?,
+      b'[' => find_array_item(bytes, pos, segment.parse().ok()?)?,
+      _ => return None,
+    };
+  }
+
+  Some(pos..skip_value(bytes, pos)?)
+}
+
+/// Converts a byte offset into a 1-based line and column.
+pub fn line_column(text: &str, offset: usize) -> (usize, usize) {
+  let before = &text[..offset];
+  let line = before.matches('\n').count() + 1;
+  let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+
+  (line, column)
+}
+
+/// Returns the position of the first member of the object starting at
+/// `pos`, or of its closing brace if it's empty.
+pub fn object_contents_start(json: &str, pos: usize) -> usize {
+  skip_whitespace(json.as_bytes(), pos + 1)
+}
+
+/// Returns the position of the value with the given key in the object
+/// starting at `pos`.
+fn find_object_value(json: &str, mut pos: usize, key: &str) -> Option<usize> {
+  let bytes = json.as_bytes();
+  pos = skip_whitespace(bytes, pos + 1);
+
+  loop {
+    let (found_key, key_end) = match bytes.get(pos)? {
+      b'}' => return None,
+      quote @ (b'"' | b'\'') => {
+        let key_end = skip_string(bytes, pos)?;
+        let raw_key = &json[pos + 1..key_end - 1];
+
+        let found_key = match quote {
+          b'"' => serde_json::from_str::<String>(&json[pos..key_end]).ok()?,
+          _ => raw_key.replace("\\'", "'"),
+        };
+
+        (found_key, key_end)
+      }
+      // Unquoted key (JSON5).
+      _ => {
+        let key_end = pos
+          + bytes[pos..]
+            .iter()
+            .position(|byte| *byte == b':' || byte.is_ascii_whitespace())?;
+
+        (json[pos..key_end].to_string(), key_end)
+      }
+    };
+
+    pos = skip_whitespace(bytes, key_end);
+
+    if *bytes.get(pos)? != b':' {
+      return None;
+    }
+
+    let value_pos = skip_whitespace(bytes, pos + 1);
+
+    if found_key == key {
+      return Some(value_pos);
+    }
+
+    pos = skip_whitespace(bytes, skip_value(bytes, value_pos)?);
+
+    match bytes.get(pos)? {
+      b',' => pos = skip_whitespace(bytes, pos + 1),
+      _ => return None,
+    }
+  }
+}
+
+/// Returns the position of the item at the given index in the array
+/// starting at `pos`.
+fn find_array_item(bytes: &[u8], mut pos: usize, index: usize) -> Option<usize> {
+  pos = skip_whitespace(bytes, pos + 1);
+
+  for _ in 0..index {
+    pos = skip_whitespace(bytes, skip_value(bytes, pos)?);
+
+    match bytes.get(pos)? {
+      b',' => pos = skip_whitespace(bytes, pos + 1),
+      _ => return None,
+    }
+  }
+
+  (*bytes.get(pos)? != b']').then_some(pos)
+}
+
+/// Returns the position after any whitespace and comments at `pos`.
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+  loop {
+    match (bytes.get(pos), bytes.get(pos + 1)) {
+      (Some(byte), _) if byte.is_ascii_whitespace() => pos += 1,
+      (Some(b'/'), Some(b'/')) => {
+        while bytes.get(pos).is_some_and(|byte| *byte != b'\n') {
+          pos += 1;
+        }
+      }
+      (Some(b'/'), Some(b'*')) => {
+        pos += 2;
+
+        while bytes.get(pos).is_some() && !bytes[pos..].starts_with(b"*/") {
+          pos += 1;
+        }
+
+        pos += 2;
+      }
+      _ => return pos.min(bytes.len()),
+    }
+  }
+}
+
+/// Returns the position after the string starting at `pos`.
+fn skip_string(bytes: &[u8], mut pos: usize) -> Option<usize> {
+  let quote = bytes[pos];
+  pos += 1;
+
+  loop {
+    match bytes.get(pos)? {
+      b'\\' => pos += 2,
+      byte if *byte == quote => return Some(pos + 1),
+      _ => pos += 1,
+    }
+  }
+}
+
+/// Returns the position after the value starting at `pos`.
+fn skip_value(bytes: &[u8], mut pos: usize) -> Option<usize> {
+  match bytes.get(pos)? {
+    b'"' | b'\'' => skip_string(bytes, pos),
+    b'{' | b'[' => {
+      let mut depth = 0;
+
+      loop {
+        match bytes.get(pos)? {
+          b'"' | b'\'' => {
+            pos = skip_string(bytes, pos)?;
+            continue;
+          }
+          b'/' if matches!(bytes.get(pos + 1), Some(b'/' | b'*')) => {
+            pos = skip_whitespace(bytes, pos);
+            continue;
+          }
+          b'{' | b'[' => depth += 1,
+          b'}' | b']' => {
+            depth -= 1;
+
+            if depth == 0 {
+              return Some(pos + 1);
+            }
+          }
+          _ => {}
+        }
+
+        pos += 1;
+      }
+    }
+    _ => {
+      while bytes.get(pos).is_some_and(|byte| {
+        !matches!(byte, b',' | b'}' | b']' | b'/') && !byte.is_ascii_whitespace()
+      }) {
+        pos += 1;
+      }
+
+      Some(pos)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const JSON: &str = r#"{
+  "htmlPath": "index.html",
+  "presets": [
+    { "name": "a/b", "anchor": "top_left" },
+    {
+      "name": "default",
+      "anchor": "nope"
+    }
+  ]
+}"#;
+
+  const JSON5: &str = r#"{
+  // Entry point.
+  htmlPath: 'index.html', /* trailing */
+  presets: [
+    { name: 'a}b', anchor: 'top_left', },
+  ],
+}"#;
+
+  fn locate(json: &str, pointer: &str) -> Option<(usize, usize)> {
+    value_span(json, &pointer_segments(pointer)).map(|span| line_column(json, span.start))
+  }
+
+  #[test]
+  fn locates_json_values() {
+    assert_eq!(locate(JSON, ""), Some((1, 1)));
+    assert_eq!(locate(JSON, "/htmlPath"), Some((2, 15)));
+    assert_eq!(locate(JSON, "/presets/0/anchor"), Some((4, 32)));
+    assert_eq!(locate(JSON, "/presets/1/anchor"), Some((7, 17)));
+    assert_eq!(locate(JSON, "/presets/2"), None);
+    assert_eq!(locate(JSON, "/missing"), None);
+  }
+
+  #[test]
+  fn locates_json5_values() {
+    assert_eq!(locate(JSON5, "/htmlPath"), Some((3, 13)));
+    assert_eq!(locate(JSON5, "/presets/0/anchor"), Some((5, 28)));
+    assert_eq!(locate(JSON5, "/presets/1"), None);
+
+    let span = value_span(JSON5, &pointer_segments("/presets/0")).unwrap();
+    assert_eq!(&JSON5[span], "{ name: 'a}b', anchor: 'top_left', }");
+  }
+}
@@ -1,73 +1,315 @@
-use std::str::FromStr;
+use std::{iter::Peekable, str::FromStr};
 
 use anyhow::{bail, Context};
-use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+/// A length that is resolved to pixels relative to a monitor or window.
+///
+/// Plain values (e.g. `10px`) have a single term, whereas `calc()`
+/// expressions (e.g. `calc(100% - 40px)`) are reduced to a sum of terms
+/// with different units.
 #[derive(Debug, Clone, PartialEq)]
 pub struct LengthValue {
+  pub terms: Vec<LengthTerm>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LengthTerm {
   pub amount: f32,
   pub unit: LengthUnit,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LengthUnit {
+  /// Percentage of the total length (`%`).
   Percentage,
+  /// Pixels (`px`). Scaled by the monitor's scale factor when resolved
+  /// via `to_px_scaled`.
   Pixel,
+  /// Logical pixels (`dp`). An alias of `px`, which is resolved the same
+  /// way, since pixels are already scaled by the monitor's scale factor.
+  LogicalPixel,
+  /// Percentage of the viewport's (i.e. monitor's) width (`vw`).
+  ViewportWidth,
+  /// Percentage of the viewport's (i.e. monitor's) height (`vh`).
+  ViewportHeight,
+}
+
+impl LengthUnit {
+  fn from_suffix(suffix: &str) -> Option<Self> {
+    match suffix {
+      "px" | "" => Some(Self::Pixel),
+      "%" => Some(Self::Percentage),
+      "dp" => Some(Self::LogicalPixel),
+      "vw" => Some(Self::ViewportWidth),
+      "vh" => Some(Self::ViewportHeight),
+      _ => None,
+    }
+  }
+
+  fn suffix(&self) -> &'static str {
+    match self {
+      Self::Percentage => "%",
+      Self::Pixel => "px",
+      Self::LogicalPixel => "dp",
+      Self::ViewportWidth => "vw",
+      Self::ViewportHeight => "vh",
+    }
+  }
 }
 
 impl LengthValue {
-  pub fn to_px(&self, total_px: i32) -> i32 {
-    match self.unit {
-      LengthUnit::Percentage => (self.amount / 100. * total_px as f32) as i32,
-      LengthUnit::Pixel => self.amount as i32,
+  pub fn new(amount: f32, unit: LengthUnit) -> Self {
+    Self {
+      terms: vec![LengthTerm { amount, unit }],
     }
   }
 
+  /// Resolves the length without scaling pixel values (`px` and `dp`).
+  /// Viewport units are relative to `total_px`.
+  pub fn to_px(&self, total_px: i32) -> i32 {
+    self.to_px_in_viewport(total_px, total_px, total_px, 1.)
+  }
+
+  /// Resolves the length, scaling pixel values by the given scale
+  /// factor. Viewport units are relative to `total_px`.
   pub fn to_px_scaled(&self, total_px: i32, scale_factor: f32) -> i32 {
-    match self.unit {
-      LengthUnit::Percentage => self.to_px(total_px),
-      LengthUnit::Pixel => (scale_factor * self.amount) as i32,
-    }
+    self.to_px_in_viewport(total_px, total_px, total_px, scale_factor)
+  }
+
+  /// Resolves the length, scaling pixel values by the given scale
+  /// factor. Viewport units are relative to the given viewport size.
+  pub fn to_px_in_viewport(
+    &self,
+    total_px: i32,
+    viewport_width: i32,
+    viewport_height: i32,
+    scale_factor: f32,
+  ) -> i32 {
+    let px = self
+      .terms
+      .iter()
+      .map(|term| match term.unit {
+        LengthUnit::Percentage => term.amount / 100. * total_px as f32,
+        LengthUnit::Pixel | LengthUnit::LogicalPixel => scale_factor * term.amount,
+        LengthUnit::ViewportWidth => term.amount / 100. * viewport_width as f32,
+        LengthUnit::ViewportHeight => term.amount / 100. * viewport_height as f32,
+      })
+      .sum::<f32>();
+
+    px as i32
   }
 }
 
 impl FromStr for LengthValue {
   type Err = anyhow::Error;
 
-  /// Parses a string containing a number followed by a unit (`px`, `%`).
-  /// Allows for negative numbers.
+  /// Parses a string containing a number followed by a unit (`px`, `%`,
+  /// `dp`, `vw`, `vh`), or a `calc()` expression combining several of
+  /// them. Allows for negative and fractional numbers.
   ///
   /// Example:
   /// ```
-  /// LengthValue::from_str("100px") // { amount: 100.0, unit: LengthUnit::Pixel }
+  /// LengthValue::from_str("100px") // { terms: [{ amount: 100.0, unit: LengthUnit::Pixel }] }
+  /// LengthValue::from_str("calc(100% - 40px)")
   /// ```
   fn from_str(unparsed: &str) -> anyhow::Result<Self> {
-    let units_regex = Regex::new(r"([+-]?\d+)(%|px)?")?;
-
     let err_msg = format!(
-      "Not a valid length value '{}'. Must be of format '10px' or '10%'.",
+      "Not a valid length value '{}'. Must be of format '10px', '10%', '10dp', '10vw', '10vh' or 'calc(100% - 10px)'.",
       unparsed
     );
 
-    let captures = units_regex
-      .captures(unparsed)
-      .context(err_msg.to_string())?;
+    let tokens = tokenize(unparsed).context(err_msg.clone())?;
+    let mut tokens = tokens.into_iter().peekable();
+
+    let operand = parse_sum(&mut tokens).context(err_msg.clone())?;
+
+    if tokens.next().is_some() {
+      bail!(err_msg);
+    }
+
+    Ok(operand.into_length())
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Number(f32, LengthUnit),
+  UnitlessNumber(f32),
+  Operator(char),
+  OpenParen,
+  CloseParen,
+}
+
+fn tokenize(unparsed: &str) -> anyhow::Result<Vec<Token>> {
+  let mut tokens = Vec::new();
+  let mut chars = unparsed.trim().chars().peekable();
+
+  while let Some(&char) = chars.peek() {
+    match char {
+      ' ' | '\t' => {
+        chars.next();
+      }
+      '+' | '-' | '*' | '/' => {
+        chars.next();
+        tokens.push(Token::Operator(char));
+      }
+      '(' => {
+        chars.next();
+        tokens.push(Token::OpenParen);
+      }
+      ')' => {
+        chars.next();
+        tokens.push(Token::CloseParen);
+      }
+      '0'..='9' | '.' => {
+        let number = take_while(&mut chars, |c| c.is_ascii_digit() || c == '.');
+        let suffix = take_while(&mut chars, |c| c.is_ascii_alphabetic() || c == '%');
+
+        let amount = f32::from_str(&number)?;
+
+        tokens.push(match suffix.as_str() {
+          "" => Token::UnitlessNumber(amount),
+          _ => Token::Number(
+            amount,
+            LengthUnit::from_suffix(&suffix).context(format!("Unknown unit '{}'.", suffix))?,
+          ),
+        });
+      }
+      _ if char.is_ascii_alphabetic() => {
+        let function = take_while(&mut chars, |c| c.is_ascii_alphabetic());
+
+        if function != "calc" || chars.next() != Some('(') {
+          bail!("Unknown function '{}'.", function);
+        }
+
+        tokens.push(Token::OpenParen);
+      }
+      _ => bail!("Unexpected character '{}'.", char),
+    }
+  }
+
+  Ok(tokens)
+}
+
+fn take_while(
+  chars: &mut Peekable<impl Iterator<Item = char>>,
+  predicate: impl Fn(char) -> bool,
+) -> String {
+  let mut taken = String::new();
+
+  while let Some(char) = chars.next_if(|&c| predicate(c)) {
+    taken.push(char);
+  }
+
+  taken
+}
+
+/// Intermediate value while parsing an expression. Numbers without a
+/// unit can be used to multiply or divide lengths.
+#[derive(Debug)]
+enum Operand {
+  Number(f32),
+  Length(Vec<LengthTerm>),
+}
+
+impl Operand {
+  /// Converts to a length, where unitless numbers are treated as pixels.
+  fn into_length(self) -> LengthValue {
+    match self {
+      Operand::Number(amount) => LengthValue::new(amount, LengthUnit::Pixel),
+      Operand::Length(terms) => LengthValue { terms },
+    }
+  }
+
+  fn add(self, other: Operand) -> Operand {
+    match (self, other) {
+      (Operand::Number(a), Operand::Number(b)) => Operand::Number(a + b),
+      (a, b) => {
+        let mut terms = a.into_length().terms;
+
+        for term in b.into_length().terms {
+          match terms.iter_mut().find(|t| t.unit == term.unit) {
+            Some(existing) => existing.amount += term.amount,
+            None => terms.push(term),
+          }
+        }
+
+        Operand::Length(terms)
+      }
+    }
+  }
+
+  fn scale(self, factor: f32) -> Operand {
+    match self {
+      Operand::Number(amount) => Operand::Number(amount * factor),
+      Operand::Length(terms) => Operand::Length(
+        terms
+          .into_iter()
+          .map(|term| LengthTerm {
+            amount: term.amount * factor,
+            unit: term.unit,
+          })
+          .collect(),
+      ),
+    }
+  }
+}
+
+type Tokens = Peekable<std::vec::IntoIter<Token>>;
+
+fn parse_sum(tokens: &mut Tokens) -> anyhow::Result<Operand> {
+  let mut result = parse_product(tokens)?;
+
+  while let Some(Token::Operator(operator @ ('+' | '-'))) = tokens.peek().cloned() {
+    tokens.next();
+    let rhs = parse_product(tokens)?;
+
+    result = match operator {
+      '+' => result.add(rhs),
+      _ => result.add(rhs.scale(-1.)),
+    };
+  }
+
+  Ok(result)
+}
+
+fn parse_product(tokens: &mut Tokens) -> anyhow::Result<Operand> {
+  let mut result = parse_factor(tokens)?;
+
+  while let Some(Token::Operator(operator @ ('*' | '/'))) = tokens.peek().cloned() {
+    tokens.next();
+    let rhs = parse_factor(tokens)?;
 
-    let unit_str = captures.get(2).map_or("", |m| m.as_str());
-    let unit = match unit_str {
-      "px" | "" => LengthUnit::Pixel,
-      "%" => LengthUnit::Percentage,
-      _ => bail!(err_msg),
+    result = match (operator, result, rhs) {
+      ('*', Operand::Number(factor), operand) | ('*', operand, Operand::Number(factor)) => {
+        operand.scale(factor)
+      }
+      ('/', _, Operand::Number(0.)) => bail!("Division by zero."),
+      ('/', operand, Operand::Number(divisor)) => operand.scale(1. / divisor),
+      _ => bail!("Lengths can only be multiplied or divided by a number."),
     };
+  }
 
-    let amount = captures
-      .get(1)
-      .and_then(|amount_str| f32::from_str(amount_str.into()).ok())
-      .context(err_msg.to_string())?;
+  Ok(result)
+}
+
+fn parse_factor(tokens: &mut Tokens) -> anyhow::Result<Operand> {
+  match tokens.next() {
+    Some(Token::Operator('+')) => parse_factor(tokens),
+    Some(Token::Operator('-')) => Ok(parse_factor(tokens)?.scale(-1.)),
+    Some(Token::UnitlessNumber(amount)) => Ok(Operand::Number(amount)),
+    Some(Token::Number(amount, unit)) => Ok(Operand::Length(vec![LengthTerm { amount, unit }])),
+    Some(Token::OpenParen) => {
+      let operand = parse_sum(tokens)?;
 
-    Ok(LengthValue { amount, unit })
+      match tokens.next() {
+        Some(Token::CloseParen) => Ok(operand),
+        _ => bail!("Missing closing parenthesis."),
+      }
+    }
+    _ => bail!("Expected a number."),
   }
 }
 
@@ -76,9 +318,19 @@ impl Serialize for LengthValue {
   where
     S: Serializer,
   {
-    let s = match self.unit {
-      LengthUnit::Percentage => format!("{}%", self.amount),
-      LengthUnit::Pixel => format!("{}px", self.amount),
+    let s = match self.terms.as_slice() {
+      [] => "0px".to_string(),
+      [term] => format!("{}{}", term.amount, term.unit.suffix()),
+      [first, rest @ ..] => {
+        let mut s = format!("calc({}{}", first.amount, first.unit.suffix());
+
+        for term in rest {
+          let operator = if term.amount < 0. { '-' } else { '+' };
+          s += &format!(" {} {}{}", operator, term.amount.abs(), term.unit.suffix());
+        }
+
+        s + ")"
+      }
     };
 
     serializer.serialize_str(&s)
@@ -97,9 +349,45 @@ impl<'de> Deserialize<'de> for LengthValue {
 
 impl Default for LengthValue {
   fn default() -> Self {
-    Self {
-      amount: 0.,
-      unit: LengthUnit::Pixel,
-    }
+    Self::new(0., LengthUnit::Pixel)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_length_value() {
+    let parse = |s: &str| LengthValue::from_str(s).unwrap();
+
+    assert_eq!(parse("100px"), LengthValue::new(100., LengthUnit::Pixel));
+    assert_eq!(
+      parse("-12.5%"),
+      LengthValue::new(-12.5, LengthUnit::Percentage)
+    );
+    assert_eq!(parse("40"), LengthValue::new(40., LengthUnit::Pixel));
+    assert_eq!(parse("2vh").to_px_in_viewport(0, 1920, 1080, 1.), 21);
+    assert_eq!(parse("10dp").to_px_scaled(0, 1.5), 15);
+    assert_eq!(parse("10dp").to_px(0), parse("10px").to_px(0));
+
+    let calc = parse("calc(100% - 2 * (10px + 5dp))");
+    assert_eq!(calc.to_px(1000), 970);
+    assert_eq!(calc.to_px_scaled(1000, 2.), 940);
+    assert_eq!(parse("100% - 40px").to_px(1000), 960);
+
+    assert!(LengthValue::from_str("10em").is_err());
+    assert!(LengthValue::from_str("calc(100% * 10px)").is_err());
+    assert!(LengthValue::from_str("calc(100% - 40px").is_err());
+  }
+
+  #[test]
+  fn test_serialize_length_value() {
+    let value = LengthValue::from_str("calc(100vw - 40px + 0.5%)").unwrap();
+
+    assert_eq!(
+      serde_json::to_string(&value).unwrap(),
+      "\"calc(100vw - 40px + 0.5%)\""
+    );
   }
 }
@@ -0,0 +1,323 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, Mutex},
+  thread,
+};
+
+use tokio::sync::mpsc;
+use tracing::warn;
+use wayland_client::{
+  protocol::{wl_output, wl_registry},
+  Connection as WaylandConnection, Dispatch, QueueHandle, WEnum,
+};
+use x11rb::{
+  connection::Connection,
+  protocol::{
+    randr::{self, ConnectionExt as _, NotifyMask},
+    xproto::{
+      Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt, EventMask, Window as XWindow,
+    },
+    Event,
+  },
+  rust_connection::RustConnection,
+};
+
+use crate::monitor_state::DisplayInfo;
+
+/// Outputs advertised by the Wayland compositor, keyed by their global
+/// name.
+static WAYLAND_OUTPUTS: LazyLock<Mutex<HashMap<u32, WaylandOutput>>> =
+  LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Default)]
+struct WaylandOutput {
+  /// Connector name (e.g. `DP-1`). Only sent by `wl_output` v4+.
+  name: Option<String>,
+  model: Option<String>,
+  refresh_rate: Option<f32>,
+}
+
+/// Whether GTK is running on Wayland rather than X11.
+fn is_wayland() -> bool {
+  std::env::var_os("WAYLAND_DISPLAY").is_some()
+    && std::env::var("GDK_BACKEND").map_or(true, |backend| backend != "x11")
+}
+
+/// Listens for monitor changes via `wl_output` events on Wayland, or via
+/// XRandR notifications and `_NET_WORKAREA` changes on X11.
+pub fn listen_display_changes(change_tx: mpsc::UnboundedSender<()>) -> anyhow::Result<()> {
+  match is_wayland() {
+    true => listen_wayland(change_tx),
+    false => listen_x11(change_tx),
+  }
+}
+
+fn listen_x11(change_tx: mpsc::UnboundedSender<()>) -> anyhow::Result<()> {
+  let (conn, screen_num) = x11rb::connect(None)?;
+  let root = conn.setup().roots[screen_num].root;
+  let workarea_atom = conn.intern_atom(false, b"_NET_WORKAREA")?.reply()?.atom;
+
+  conn.randr_select_input(
+    root,
+    NotifyMask::SCREEN_CHANGE | NotifyMask::CRTC_CHANGE | NotifyMask::OUTPUT_CHANGE,
+  )?;
+
+  conn.change_window_attributes(
+    root,
+    &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+  )?;
+
+  conn.flush()?;
+
+  thread::spawn(move || loop {
+    let is_change = match conn.wait_for_event() {
+      Ok(Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_)) => true,
+      Ok(Event::PropertyNotify(event)) => event.atom == workarea_atom,
+      Ok(_) => false,
+      Err(err) => {
+        warn!("X11 display listener stopped: {}", err);
+        break;
+      }
+    };
+
+    if is_change && change_tx.send(()).is_err() {
+      break;
+    }
+  });
+
+  Ok(())
+}
+
+fn listen_wayland(change_tx: mpsc::UnboundedSender<()>) -> anyhow::Result<()> {
+  let conn = WaylandConnection::connect_to_env()?;
+  let mut event_queue = conn.new_event_queue();
+  let _registry = conn.display().get_registry(&event_queue.handle(), ());
+
+  let mut listener = WaylandListener { change_tx };
+
+  // Receive the initial set of outputs before returning.
+  event_queue.roundtrip(&mut listener)?;
+
+  thread::spawn(move || loop {
+    if let Err(err) = event_queue.blocking_dispatch(&mut listener) {
+      warn!("Wayland display listener stopped: {}", err);
+      break;
+    }
+
+    if listener.change_tx.is_closed() {
+      break;
+    }
+  });
+
+  Ok(())
+}
+
+struct WaylandListener {
+  change_tx: mpsc::UnboundedSender<()>,
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for WaylandListener {
+  fn event(
+    state: &mut Self,
+    registry: &wl_registry::WlRegistry,
+    event: wl_registry::Event,
+    _: &(),
+    _: &WaylandConnection,
+    queue_handle: &QueueHandle<Self>,
+  ) {
+    match event {
+      wl_registry::Event::Global {
+        name,
+        interface,
+        version,
+      } if interface == "wl_output" => {
+        registry.bind::<wl_output::WlOutput, _, _>(name, version.min(4), queue_handle, name);
+      }
+      wl_registry::Event::GlobalRemove { name } => {
+        if WAYLAND_OUTPUTS.lock().unwrap().remove(&name).is_some() {
+          let _ = state.change_tx.send(());
+        }
+      }
+      _ => {}
+    }
+  }
+}
+
+impl Dispatch<wl_output::WlOutput, u32> for WaylandListener {
+  fn event(
+    state: &mut Self,
+    _: &wl_output::WlOutput,
+    event: wl_output::Event,
+    global_name: &u32,
+    _: &WaylandConnection,
+    _: &QueueHandle<Self>,
+  ) {
+    let mut outputs = WAYLAND_OUTPUTS.lock().unwrap();
+    let output = outputs.entry(*global_name).or_default();
+
+    match event {
+      wl_output::Event::Geometry { model, .. } => output.model = Some(model),
+      wl_output::Event::Name { name } => output.name = Some(name),
+      wl_output::Event::Mode {
+        flags: WEnum::Value(flags),
+        refresh,
+        ..
+      } if flags.contains(wl_output::Mode::Current) => {
+        output.refresh_rate = Some(refresh as f32 / 1000.0);
+      }
+      // Sent after all properties of an output have been updated.
+      wl_output::Event::Done => {
+        let _ = state.change_tx.send(());
+      }
+      _ => {}
+    }
+  }
+}
+
+/// Gets platform-specific details for each of the given monitors, by
+/// their name.
+///
+/// Monitor names are RandR monitor names on X11, whereas GTK names
+/// monitors by their model on Wayland.
+pub fn display_infos(names: &[&str]) -> Vec<DisplayInfo> {
+  if is_wayland() {
+    let outputs = WAYLAND_OUTPUTS.lock().unwrap();
+
+    return names
+      .iter()
+      .map(|name| {
+        outputs
+          .values()
+          .find(|output| {
+            output.name.as_deref() == Some(*name) || output.model.as_deref() == Some(*name)
+          })
+          .map(|output| DisplayInfo {
+            id: output.name.clone(),
+            refresh_rate: output.refresh_rate,
+          })
+          .unwrap_or_default()
+      })
+      .collect();
+  }
+
+  match X11Displays::new() {
+    Ok(displays) => names
+      .iter()
+      .map(|name| {
+        displays.display_info(name).unwrap_or_else(|err| {
+          warn!("Failed to get X11 display info for '{}': {}", name, err);
+          DisplayInfo::default()
+        })
+      })
+      .collect(),
+    Err(err) => {
+      warn!("Failed to get X11 display info: {}", err);
+      names.iter().map(|_| DisplayInfo::default()).collect()
+    }
+  }
+}
+
+/// Connection to the X server for looking up the details of multiple
+/// monitors.
+struct X11Displays {
+  conn: RustConnection,
+  root: XWindow,
+  edid_atom: Atom,
+  resources: randr::GetScreenResourcesCurrentReply,
+}
+
+impl X11Displays {
+  fn new() -> anyhow::Result<Self> {
+    let (conn, screen_num) = x11rb::connect(None)?;
+    let root = conn.setup().roots[screen_num].root;
+    let edid_atom = conn.intern_atom(false, b"EDID")?.reply()?.atom;
+    let resources = conn.randr_get_screen_resources_current(root)?.reply()?;
+
+    Ok(Self {
+      conn,
+      root,
+      edid_atom,
+      resources,
+    })
+  }
+
+  fn display_info(&self, name: &str) -> anyhow::Result<DisplayInfo> {
+    let Some(output) = self.monitor_output(name)? else {
+      return Ok(DisplayInfo::default());
+    };
+
+    let output_info = self
+      .conn
+      .randr_get_output_info(output, self.resources.config_timestamp)?
+      .reply()?;
+
+    // Manufacturer, product code and serial number from the EDID, which
+    // identify the physical monitor regardless of the port it's plugged
+    // into.
+    let edid = self
+      .conn
+      .randr_get_output_property(output, self.edid_atom, AtomEnum::ANY, 0, 128, false, false)?
+      .reply()?
+      .data;
+
+    let id = edid.get(8..16).map(|bytes| {
+      let id = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+
+      // Monitors of the same model without a serial number can only be
+      // told apart by their connector (e.g. `DP-1`).
+      match bytes[4..].iter().all(|byte| *byte == 0) {
+        true => format!("{}-{}", id, String::from_utf8_lossy(&output_info.name)),
+        false => id,
+      }
+    });
+
+    Ok(DisplayInfo {
+      id,
+      refresh_rate: self.refresh_rate(&output_info)?,
+    })
+  }
+
+  /// Gets the first output of the RandR monitor with the given name.
+  fn monitor_output(&self, name: &str) -> anyhow::Result<Option<randr::Output>> {
+    for monitor in self
+      .conn
+      .randr_get_monitors(self.root, true)?
+      .reply()?
+      .monitors
+    {
+      let monitor_name = self.conn.get_atom_name(monitor.name)?.reply()?.name;
+
+      if monitor_name == name.as_bytes() {
+        return Ok(monitor.outputs.first().copied());
+      }
+    }
+
+    Ok(None)
+  }
+
+  /// Calculates the refresh rate of the mode that the output's CRTC is
+  /// currently using.
+  fn refresh_rate(&self, output_info: &randr::GetOutputInfoReply) -> anyhow::Result<Option<f32>> {
+    if output_info.crtc == x11rb::NONE {
+      return Ok(None);
+    }
+
+    let crtc_info = self
+      .conn
+      .randr_get_crtc_info(output_info.crtc, self.resources.config_timestamp)?
+      .reply()?;
+
+    let refresh_rate = self
+      .resources
+      .modes
+      .iter()
+      .find(|mode| mode.id == crtc_info.mode)
+      .filter(|mode| mode.htotal != 0 && mode.vtotal != 0)
+      .map(|mode| mode.dot_clock as f32 / (mode.htotal as f32 * mode.vtotal as f32));
+
+    Ok(refresh_rate)
+  }
+}
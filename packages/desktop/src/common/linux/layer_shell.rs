@@ -0,0 +1,119 @@
+use anyhow::bail;
+use gtk::{gdk, prelude::*};
+use gtk_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+use tauri::{PhysicalPosition, PhysicalSize};
+use tracing::info;
+
+use crate::config::DockEdge;
+
+/// Turns a GTK window into a wlr-layer-shell surface anchored to the
+/// given monitor edge, with an exclusive zone reserving its space.
+///
+/// Layer surfaces are positioned by the compositor, so the window spans
+/// the full length of the edge and is offset via a margin instead of an
+/// absolute position.
+pub fn set_exclusive_zone(
+  gtk_window: &gtk::ApplicationWindow,
+  size: PhysicalSize<i32>,
+  position: PhysicalPosition<i32>,
+  edge: DockEdge,
+) -> anyhow::Result<()> {
+  if !gtk_layer_shell::is_supported() {
+    bail!("Compositor does not support wlr-layer-shell.");
+  }
+
+  let Some((monitor, scale_factor)) = gdk_monitor_at(gtk_window, position) else {
+    bail!("No monitor found at {:?}.", position);
+  };
+
+  let geometry = monitor.geometry();
+
+  // Layer shell works in logical pixels relative to the monitor.
+  let to_logical = |value: i32| (value as f64 / scale_factor).round() as i32;
+  let logical_x = to_logical(position.x) - geometry.x();
+  let logical_y = to_logical(position.y) - geometry.y();
+  let logical_width = to_logical(size.width);
+  let logical_height = to_logical(size.height);
+
+  let (anchors, margin, exclusive_zone) = match edge {
+    DockEdge::Top => (
+      [Edge::Top, Edge::Left, Edge::Right],
+      logical_y,
+      logical_height,
+    ),
+    DockEdge::Bottom => (
+      [Edge::Bottom, Edge::Left, Edge::Right],
+      geometry.height() - logical_y - logical_height,
+      logical_height,
+    ),
+    DockEdge::Left => (
+      [Edge::Left, Edge::Top, Edge::Bottom],
+      logical_x,
+      logical_width,
+    ),
+    DockEdge::Right => (
+      [Edge::Right, Edge::Top, Edge::Bottom],
+      geometry.width() - logical_x - logical_width,
+      logical_width,
+    ),
+  };
+
+  // Layer shell can only be initialized on an unrealized window. Windows
+  // that are re-docked (e.g. after a monitor change) are already layer
+  // surfaces.
+  let was_visible = gtk_window.is_visible();
+
+  if !gtk_window.is_layer_window() {
+    gtk_window.hide();
+    gtk_window.unrealize();
+    gtk_window.init_layer_shell();
+  }
+
+  gtk_window.set_namespace("zebar");
+  gtk_window.set_layer(Layer::Top);
+  gtk_window.set_keyboard_mode(KeyboardMode::OnDemand);
+  gtk_window.set_monitor(&monitor);
+
+  for anchor in anchors {
+    gtk_window.set_anchor(anchor, true);
+  }
+
+  // The compositor adds the margin to the exclusive zone.
+  gtk_window.set_layer_shell_margin(anchors[0], margin.max(0));
+  gtk_window.set_exclusive_zone(exclusive_zone.max(0));
+  gtk_window.set_size_request(logical_width, logical_height);
+
+  if was_visible && !gtk_window.is_visible() {
+    gtk_window.show();
+  }
+
+  info!(
+    "Set layer shell exclusive zone of {} on edge {:?}.",
+    exclusive_zone, edge
+  );
+
+  Ok(())
+}
+
+/// Gets the GDK monitor containing the given physical position, along
+/// with its scale factor.
+fn gdk_monitor_at(
+  gtk_window: &gtk::ApplicationWindow,
+  position: PhysicalPosition<i32>,
+) -> Option<(gdk::Monitor, f64)> {
+  let display = gtk_window.display();
+
+  (0..display.n_monitors())
+    .filter_map(|index| display.monitor(index))
+    .find_map(|monitor| {
+      let geometry = monitor.geometry();
+      let scale_factor = monitor.scale_factor() as f64;
+      let x = (position.x as f64 / scale_factor) as i32;
+      let y = (position.y as f64 / scale_factor) as i32;
+
+      let is_match = (geometry.x()..geometry.x() + geometry.width()).contains(&x)
+        && (geometry.y()..geometry.y() + geometry.height()).contains(&y);
+
+      is_match.then_some((monitor, scale_factor))
+    })
+}
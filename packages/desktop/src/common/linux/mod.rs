@@ -0,0 +1,8 @@
+mod display;
+mod layer_shell;
+mod strut;
+mod window_ext_linux;
+mod x11;
+
+pub use display::*;
+pub use window_ext_linux::*;
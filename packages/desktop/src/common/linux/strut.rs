@@ -0,0 +1,100 @@
+use tauri::{PhysicalPosition, PhysicalSize};
+use tracing::info;
+use x11rb::{
+  connection::Connection,
+  protocol::xproto::{AtomEnum, ConnectionExt, PropMode, Window as XWindow},
+  wrapper::ConnectionExt as _,
+};
+
+use crate::config::DockEdge;
+
+x11rb::atom_manager! {
+  Atoms: AtomsCookie {
+    _NET_WM_STRUT,
+    _NET_WM_STRUT_PARTIAL,
+    _NET_WM_WINDOW_TYPE,
+    _NET_WM_WINDOW_TYPE_DOCK,
+  }
+}
+
+/// Reserves space at the given screen edge for an X11 window via
+/// `_NET_WM_STRUT_PARTIAL`, and marks the window as a dock so that window
+/// managers keep it out of tiling and focus cycling.
+///
+/// Struts are relative to the edges of the root window rather than the
+/// monitor, and are released automatically when the window is destroyed.
+pub fn set_strut(
+  xid: XWindow,
+  size: PhysicalSize<i32>,
+  position: PhysicalPosition<i32>,
+  edge: DockEdge,
+) -> anyhow::Result<()> {
+  let (conn, screen_num) = x11rb::connect(None)?;
+  let screen = &conn.setup().roots[screen_num];
+  let root_width = screen.width_in_pixels as i32;
+  let root_height = screen.height_in_pixels as i32;
+  let atoms = Atoms::new(&conn)?.reply()?;
+
+  let start_x = position.x.max(0) as u32;
+  let end_x = (position.x + size.width - 1).max(0) as u32;
+  let start_y = position.y.max(0) as u32;
+  let end_y = (position.y + size.height - 1).max(0) as u32;
+
+  // Order is left, right, top, bottom, followed by the start and end
+  // coordinates of each of the four struts.
+  let mut strut = [0u32; 12];
+
+  match edge {
+    DockEdge::Left => {
+      strut[0] = (position.x + size.width).max(0) as u32;
+      strut[4] = start_y;
+      strut[5] = end_y;
+    }
+    DockEdge::Right => {
+      strut[1] = (root_width - position.x).max(0) as u32;
+      strut[6] = start_y;
+      strut[7] = end_y;
+    }
+    DockEdge::Top => {
+      strut[2] = (position.y + size.height).max(0) as u32;
+      strut[8] = start_x;
+      strut[9] = end_x;
+    }
+    DockEdge::Bottom => {
+      strut[3] = (root_height - position.y).max(0) as u32;
+      strut[10] = start_x;
+      strut[11] = end_x;
+    }
+  }
+
+  conn.change_property32(
+    PropMode::REPLACE,
+    xid,
+    atoms._NET_WM_WINDOW_TYPE,
+    AtomEnum::ATOM,
+    &[atoms._NET_WM_WINDOW_TYPE_DOCK],
+  )?;
+
+  conn.change_property32(
+    PropMode::REPLACE,
+    xid,
+    atoms._NET_WM_STRUT_PARTIAL,
+    AtomEnum::CARDINAL,
+    &strut,
+  )?;
+
+  // Older window managers only support the legacy `_NET_WM_STRUT`.
+  conn.change_property32(
+    PropMode::REPLACE,
+    xid,
+    atoms._NET_WM_STRUT,
+    AtomEnum::CARDINAL,
+    &strut[..4],
+  )?;
+
+  conn.flush()?;
+
+  info!("Set X11 strut for window {} to {:?}.", xid, strut);
+
+  Ok(())
+}
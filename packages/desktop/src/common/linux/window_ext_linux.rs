@@ -0,0 +1,121 @@
+use std::sync::mpsc;
+
+use anyhow::Context;
+use gtk::prelude::*;
+use gtk_layer_shell::{Layer, LayerShell};
+use tauri::{PhysicalPosition, PhysicalSize, Runtime, Window};
+
+use super::{layer_shell, strut, x11};
+use crate::config::DockEdge;
+
+pub trait WindowExtLinux {
+  /// Reserves screen space for the window on X11 via struts, or on
+  /// Wayland via a wlr-layer-shell exclusive zone.
+  fn allocate_app_bar(
+    &self,
+    size: PhysicalSize<i32>,
+    position: PhysicalPosition<i32>,
+    edge: DockEdge,
+  ) -> anyhow::Result<(PhysicalSize<i32>, PhysicalPosition<i32>)>;
+
+  /// Keeps the window below all other windows, and prevents it from
+  /// being hidden by "show desktop".
+  fn pin_to_desktop(&self) -> anyhow::Result<()>;
+
+  /// Raises the window above other windows without focusing it.
+  fn raise_top_most(&self) -> anyhow::Result<()>;
+}
+
+impl<R: Runtime> WindowExtLinux for Window<R> {
+  fn allocate_app_bar(
+    &self,
+    size: PhysicalSize<i32>,
+    position: PhysicalPosition<i32>,
+    edge: DockEdge,
+  ) -> anyhow::Result<(PhysicalSize<i32>, PhysicalPosition<i32>)> {
+    with_gtk_window(self, move |gtk_window| {
+      // Unlike Windows appbars, neither struts nor layer surfaces are
+      // adjusted to avoid each other, so the requested space is
+      // allocated as-is.
+      match x11::window_id(gtk_window) {
+        Some(xid) => remap(gtk_window, || strut::set_strut(xid, size, position, edge))?,
+        None => layer_shell::set_exclusive_zone(gtk_window, size, position, edge)?,
+      }
+
+      Ok((size, position))
+    })
+  }
+
+  fn pin_to_desktop(&self) -> anyhow::Result<()> {
+    with_gtk_window(self, |gtk_window| {
+      gtk_window.set_keep_below(true);
+
+      match x11::window_id(gtk_window) {
+        Some(xid) => remap(gtk_window, || x11::set_desktop_type(xid)),
+        None => {
+          // Only layer surfaces (i.e. docked widgets) can be kept below
+          // other windows on Wayland.
+          if gtk_window.is_layer_window() {
+            gtk_window.set_layer(Layer::Bottom);
+          }
+
+          Ok(())
+        }
+      }
+    })
+  }
+
+  fn raise_top_most(&self) -> anyhow::Result<()> {
+    with_gtk_window(self, |gtk_window| {
+      if let Some(gdk_window) = gtk_window.window() {
+        gdk_window.raise();
+      }
+
+      Ok(())
+    })
+  }
+}
+
+/// Runs the callback with the underlying GTK window on the main thread,
+/// since GTK windows can't be accessed from other threads.
+fn with_gtk_window<R: Runtime, T: Send + 'static>(
+  window: &Window<R>,
+  callback: impl FnOnce(&gtk::ApplicationWindow) -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+  if gtk::is_initialized_main_thread() {
+    return callback(&window.gtk_window()?);
+  }
+
+  let window_clone = window.clone();
+  let (result_tx, result_rx) = mpsc::channel();
+
+  window.run_on_main_thread(move || {
+    let result = window_clone
+      .gtk_window()
+      .map_err(anyhow::Error::from)
+      .and_then(|gtk_window| callback(&gtk_window));
+
+    let _ = result_tx.send(result);
+  })?;
+
+  result_rx
+    .recv()
+    .context("Main thread did not run GTK window callback.")?
+}
+
+/// Unmaps the window while running the callback. Window managers only
+/// read some properties (e.g. the window type) when a window is mapped.
+fn remap(
+  gtk_window: &gtk::ApplicationWindow,
+  callback: impl FnOnce() -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+  let was_visible = gtk_window.is_visible();
+  gtk_window.hide();
+  let result = callback();
+
+  if was_visible {
+    gtk_window.show();
+  }
+
+  result
+}
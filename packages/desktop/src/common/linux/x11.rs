@@ -0,0 +1,66 @@
+use gtk::prelude::*;
+use tracing::info;
+use x11rb::{
+  connection::Connection,
+  protocol::xproto::{AtomEnum, ConnectionExt, PropMode, Window as XWindow},
+  wrapper::ConnectionExt as _,
+};
+
+x11rb::atom_manager! {
+  Atoms: AtomsCookie {
+    _NET_WM_WINDOW_TYPE,
+    _NET_WM_WINDOW_TYPE_DESKTOP,
+    _NET_WM_WINDOW_TYPE_DOCK,
+  }
+}
+
+/// Gets the X window ID of a GTK window, or `None` if it isn't running
+/// under X11 (i.e. on Wayland).
+pub fn window_id(gtk_window: &gtk::ApplicationWindow) -> Option<XWindow> {
+  gtk_window
+    .window()
+    .and_then(|gdk_window| gdk_window.downcast::<gdkx11::X11Window>().ok())
+    .map(|x11_window| x11_window.xid() as XWindow)
+}
+
+/// Marks the window as part of the desktop, which keeps it below other
+/// windows and visible while "show desktop" is active.
+///
+/// Docked windows keep their dock type, since window managers ignore
+/// the struts of desktop windows.
+pub fn set_desktop_type(xid: XWindow) -> anyhow::Result<()> {
+  let (conn, _) = x11rb::connect(None)?;
+  let atoms = Atoms::new(&conn)?.reply()?;
+
+  let window_types = conn
+    .get_property(
+      false,
+      xid,
+      atoms._NET_WM_WINDOW_TYPE,
+      AtomEnum::ATOM,
+      0,
+      u32::MAX,
+    )?
+    .reply()?
+    .value32()
+    .map(|value| value.collect::<Vec<_>>())
+    .unwrap_or_default();
+
+  if window_types.contains(&atoms._NET_WM_WINDOW_TYPE_DOCK) {
+    return Ok(());
+  }
+
+  conn.change_property32(
+    PropMode::REPLACE,
+    xid,
+    atoms._NET_WM_WINDOW_TYPE,
+    AtomEnum::ATOM,
+    &[atoms._NET_WM_WINDOW_TYPE_DESKTOP],
+  )?;
+
+  conn.flush()?;
+
+  info!("Set X11 window type of {} to desktop.", xid);
+
+  Ok(())
+}
@@ -1,7 +1,10 @@
 mod format_bytes;
 mod fs_util;
 mod interval;
+mod json_span;
 mod length_value;
+#[cfg(target_os = "linux")]
+pub mod linux;
 #[cfg(target_os = "macos")]
 pub mod macos;
 mod path_ext;
@@ -11,5 +14,6 @@ pub mod windows;
 pub use format_bytes::*;
 pub use fs_util::*;
 pub use interval::*;
+pub use json_span::*;
 pub use length_value::*;
 pub use path_ext::*;
@@ -0,0 +1,125 @@
+use std::{cell::RefCell, sync::mpsc as std_mpsc, thread};
+
+use anyhow::Context;
+use systray_util::Util;
+use tokio::sync::mpsc;
+use windows::{
+  core::PCWSTR,
+  Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    Graphics::Gdi::{
+      EnumDisplayDevicesW, EnumDisplaySettingsW, DEVMODEW, DISPLAY_DEVICEW, ENUM_CURRENT_SETTINGS,
+    },
+    UI::WindowsAndMessaging::{
+      DefWindowProcW, EDD_GET_DEVICE_INTERFACE_NAME, SPI_SETWORKAREA, WM_DISPLAYCHANGE,
+      WM_SETTINGCHANGE,
+    },
+  },
+};
+
+use crate::monitor_state::DisplayInfo;
+
+thread_local! {
+  /// Sender for display changes on the listener thread.
+  static CHANGE_TX: RefCell<Option<mpsc::UnboundedSender<()>>> = RefCell::new(None);
+}
+
+/// Listens for monitor connections, resolution and scaling changes
+/// (`WM_DISPLAYCHANGE`), as well as work area changes (e.g. when an
+/// appbar is added).
+///
+/// Broadcast messages are only received by top-level windows, so this
+/// creates a hidden window on a dedicated thread.
+pub fn listen_display_changes(change_tx: mpsc::UnboundedSender<()>) -> anyhow::Result<()> {
+  let (init_tx, init_rx) = std_mpsc::channel();
+
+  thread::spawn(move || {
+    CHANGE_TX.with_borrow_mut(|tx| *tx = Some(change_tx));
+
+    let window_res = Util::create_message_window("ZebarDisplayListener", Some(window_proc));
+    let is_created = window_res.is_ok();
+    let _ = init_tx.send(window_res);
+
+    if is_created {
+      Util::run_message_loop();
+    }
+  });
+
+  init_rx
+    .recv()
+    .context("Display listener thread exited unexpectedly.")??;
+
+  Ok(())
+}
+
+unsafe extern "system" fn window_proc(
+  hwnd: HWND,
+  msg: u32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+) -> LRESULT {
+  let is_change =
+    msg == WM_DISPLAYCHANGE || (msg == WM_SETTINGCHANGE && wparam.0 as u32 == SPI_SETWORKAREA.0);
+
+  if is_change {
+    CHANGE_TX.with_borrow(|tx| {
+      if let Some(tx) = tx {
+        let _ = tx.send(());
+      }
+    });
+  }
+
+  DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+/// Gets platform-specific details for each of the given monitors, by
+/// their device name.
+pub fn display_infos(device_names: &[&str]) -> Vec<DisplayInfo> {
+  device_names.iter().map(|name| display_info(name)).collect()
+}
+
+/// Gets platform-specific details for a monitor by its device name (e.g.
+/// `\\.\DISPLAY1`).
+fn display_info(device_name: &str) -> DisplayInfo {
+  let device_name = Util::to_wide(device_name);
+
+  // The device interface name of the monitor stays the same across
+  // reconnections, unlike the device name.
+  let mut device = DISPLAY_DEVICEW {
+    cb: std::mem::size_of::<DISPLAY_DEVICEW>() as u32,
+    ..Default::default()
+  };
+
+  let id = unsafe {
+    EnumDisplayDevicesW(
+      PCWSTR(device_name.as_ptr()),
+      0,
+      &mut device,
+      EDD_GET_DEVICE_INTERFACE_NAME,
+    )
+  }
+  .as_bool()
+  .then(|| String::from_utf16_lossy(&device.DeviceID))
+  .map(|id| id.trim_end_matches('\0').to_string())
+  .filter(|id| !id.is_empty());
+
+  let mut dev_mode = DEVMODEW {
+    dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+    ..Default::default()
+  };
+
+  // A frequency of 0 or 1 represents the hardware's default rate.
+  let refresh_rate = unsafe {
+    EnumDisplaySettingsW(
+      PCWSTR(device_name.as_ptr()),
+      ENUM_CURRENT_SETTINGS,
+      &mut dev_mode,
+    )
+  }
+  .as_bool()
+  .then_some(dev_mode.dmDisplayFrequency)
+  .filter(|frequency| *frequency > 1)
+  .map(|frequency| frequency as f32);
+
+  DisplayInfo { id, refresh_rate }
+}
@@ -1,7 +1,10 @@
 mod app_bar;
 mod com;
+mod display;
 mod window_ext_windows;
+mod z_order;
 
 pub use app_bar::*;
 pub use com::*;
+pub use display::*;
 pub use window_ext_windows::*;
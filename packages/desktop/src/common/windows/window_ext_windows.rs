@@ -1,3 +1,5 @@
+use std::sync::mpsc;
+
 use anyhow::Context;
 use tauri::{PhysicalPosition, PhysicalSize, Runtime, Window};
 use windows::Win32::{
@@ -5,7 +7,7 @@ use windows::Win32::{
   UI::WindowsAndMessaging::{SetWindowLongPtrW, GWL_EXSTYLE, WS_EX_APPWINDOW, WS_EX_TOOLWINDOW},
 };
 
-use super::app_bar;
+use super::{app_bar, z_order};
 use crate::config::DockEdge;
 
 pub trait WindowExtWindows {
@@ -19,6 +21,10 @@ pub trait WindowExtWindows {
   ) -> anyhow::Result<(PhysicalSize<i32>, PhysicalPosition<i32>)>;
 
   fn deallocate_app_bar(&self) -> anyhow::Result<()>;
+
+  fn pin_to_desktop(&self) -> anyhow::Result<()>;
+
+  fn raise_top_most(&self) -> anyhow::Result<()>;
 }
 
 impl<R: Runtime> WindowExtWindows for Window<R> {
@@ -52,4 +58,23 @@ impl<R: Runtime> WindowExtWindows for Window<R> {
     let handle = self.hwnd().context("Failed to get window handle.")?;
     app_bar::remove_app_bar(handle.0 as _)
   }
+
+  fn pin_to_desktop(&self) -> anyhow::Result<()> {
+    let handle = self.hwnd().context("Failed to get window handle.")?.0 as isize;
+    let (result_tx, result_rx) = mpsc::channel();
+
+    // Subclassing only works from the thread that owns the window.
+    self.run_on_main_thread(move || {
+      let _ = result_tx.send(z_order::pin_to_desktop(handle));
+    })?;
+
+    result_rx
+      .recv()
+      .context("Main thread did not pin window to desktop.")?
+  }
+
+  fn raise_top_most(&self) -> anyhow::Result<()> {
+    let handle = self.hwnd().context("Failed to get window handle.")?;
+    z_order::raise_top_most(handle.0 as _)
+  }
 }
@@ -0,0 +1,86 @@
+use anyhow::bail;
+use windows::{
+  core::w,
+  Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    UI::{
+      Shell::{DefSubclassProc, SetWindowSubclass},
+      WindowsAndMessaging::{
+        FindWindowW, SetWindowLongPtrW, SetWindowPos, GWLP_HWNDPARENT, HWND_BOTTOM, HWND_TOPMOST,
+        SET_WINDOW_POS_FLAGS, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, WINDOWPOS,
+        WM_WINDOWPOSCHANGING,
+      },
+    },
+  },
+};
+
+/// Identifier of the subclass that keeps windows at the bottom.
+const BOTTOM_MOST_SUBCLASS_ID: usize = 1;
+
+/// Keeps the window below all other windows, including when it is
+/// activated, and prevents it from being hidden by "show desktop".
+pub fn pin_to_desktop(handle: isize) -> anyhow::Result<()> {
+  let hwnd = HWND(handle as _);
+
+  // Windows owned by the desktop window are left alone by "show
+  // desktop".
+  let progman = unsafe { FindWindowW(w!("Progman"), None) }?;
+  unsafe { SetWindowLongPtrW(hwnd, GWLP_HWNDPARENT, progman.0 as isize) };
+
+  if !unsafe { SetWindowSubclass(hwnd, Some(bottom_most_proc), BOTTOM_MOST_SUBCLASS_ID, 0) }
+    .as_bool()
+  {
+    bail!("Failed to subclass window for bottom-most z-order.");
+  }
+
+  unsafe {
+    SetWindowPos(
+      hwnd,
+      HWND_BOTTOM,
+      0,
+      0,
+      0,
+      0,
+      SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+    )
+  }?;
+
+  Ok(())
+}
+
+/// Moves the window back to the top of the top-most windows, e.g. after
+/// another top-most window has been shown above it.
+pub fn raise_top_most(handle: isize) -> anyhow::Result<()> {
+  unsafe {
+    SetWindowPos(
+      HWND(handle as _),
+      HWND_TOPMOST,
+      0,
+      0,
+      0,
+      0,
+      SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+    )
+  }?;
+
+  Ok(())
+}
+
+/// Window procedure that forces every z-order change of the window to
+/// the bottom.
+unsafe extern "system" fn bottom_most_proc(
+  hwnd: HWND,
+  msg: u32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+  _subclass_id: usize,
+  _ref_data: usize,
+) -> LRESULT {
+  if msg == WM_WINDOWPOSCHANGING {
+    let window_pos = &mut *(lparam.0 as *mut WINDOWPOS);
+    window_pos.hwndInsertAfter = HWND_BOTTOM;
+    window_pos.flags = SET_WINDOW_POS_FLAGS(window_pos.flags.0 & !SWP_NOZORDER.0);
+  }
+
+  DefSubclassProc(hwnd, msg, wparam, lparam)
+}
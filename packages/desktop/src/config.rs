@@ -1,31 +1,117 @@
 use std::{
   collections::HashMap,
   fs::{self},
-  path::PathBuf,
+  path::{Path, PathBuf},
   sync::Arc,
 };
 
-use anyhow::Context;
+use anyhow::{bail, Context};
 use clap::ValueEnum;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Serialize};
 use tauri::{path::BaseDirectory, AppHandle, Manager};
 use tokio::sync::{broadcast, Mutex};
 use tracing::{error, info};
 
-use crate::common::{copy_dir_all, has_extension, read_and_parse_json, LengthValue, PathExt};
+use crate::{
+  common::{copy_dir_all, LengthValue, PathExt},
+  config_backups::ConfigBackups,
+  config_diagnostics::{ConfigDiagnostic, ConfigDiagnostics, ConfigSchema},
+  config_format::ConfigFormat,
+  config_inheritance,
+  config_migration::{self, CONFIG_VERSION},
+  monitor_state::Monitor,
+  widget_discovery::WidgetDiscovery,
+  widget_pack::{WidgetPack, PACK_MANIFEST_FILE},
+};
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SettingsConfig {
   /// JSON schema URL to validate the settings file.
   #[serde(rename = "$schema")]
   schema: Option<String>,
 
-  /// Widget configs to be launched on startup.
+  /// Version of the settings format. Older files are upgraded on read.
+  #[serde(default)]
+  pub version: u64,
+
+  /// Widget configs to be launched on startup, when no profile is active.
   pub startup_configs: Vec<StartupConfig>,
+
+  /// Named sets of widget configs to launch, which can be switched
+  /// between (e.g. for work and home setups).
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub profiles: Vec<Profile>,
+
+  /// Name of the profile whose widget configs are launched on startup.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub active_profile: Option<String>,
+
+  /// How widget configs are searched for within the config directory.
+  #[serde(default, skip_serializing_if = "WidgetDiscovery::is_default")]
+  pub widget_discovery: WidgetDiscovery,
+}
+
+impl SettingsConfig {
+  /// Returns the widget configs to launch on startup, which are those of
+  /// the active profile if one is set.
+  pub fn active_startup_configs(&self) -> &Vec<StartupConfig> {
+    match self.active_profile() {
+      Some(profile) => &profile.startup_configs,
+      None => &self.startup_configs,
+    }
+  }
+
+  /// Returns the widget configs to launch on startup within the given
+  /// profile, or outside of any profile if not found.
+  fn startup_configs_mut(&mut self, profile_name: Option<&str>) -> &mut Vec<StartupConfig> {
+    match self
+      .profiles
+      .iter_mut()
+      .find(|profile| Some(profile.name.as_str()) == profile_name)
+    {
+      Some(profile) => &mut profile.startup_configs,
+      None => &mut self.startup_configs,
+    }
+  }
+
+  fn active_profile(&self) -> Option<&Profile> {
+    self
+      .profiles
+      .iter()
+      .find(|profile| Some(&profile.name) == self.active_profile.as_ref())
+  }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+  /// Name of the profile (e.g. `work`).
+  pub name: String,
+
+  /// Widget configs to be launched when the profile is active.
+  pub startup_configs: Vec<StartupConfig>,
+
+  /// ID's or names of monitors for the profile to be selected
+  /// automatically when exactly these monitors are connected.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub monitors: Vec<String>,
+}
+
+impl Profile {
+  /// Whether the profile's monitor set matches the connected monitors.
+  pub fn matches_monitors(&self, monitors: &[Monitor]) -> bool {
+    !self.monitors.is_empty()
+      && self.monitors.len() == monitors.len()
+      && monitors.iter().all(|monitor| {
+        self.monitors.iter().any(|profile_monitor| {
+          *profile_monitor == monitor.id || Some(profile_monitor) == monitor.name.as_ref()
+        })
+      })
+  }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StartupConfig {
   /// Relative path to widget configs to launch on startup.
@@ -35,39 +121,18 @@ pub struct StartupConfig {
   pub preset: String,
 }
 
-// Deserializer that handles `StartupConfig` objects and string format from
-// v2.3.0 and earlier.
-impl<'de> Deserialize<'de> for StartupConfig {
-  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-  where
-    D: Deserializer<'de>,
-  {
-    #[derive(Deserialize)]
-    #[serde(untagged)]
-    enum StringOrObject {
-      String(String),
-      Object { path: PathBuf, preset: String },
-    }
-
-    let value = StringOrObject::deserialize(deserializer)?;
-
-    Ok(match value {
-      StringOrObject::String(s) => StartupConfig {
-        path: PathBuf::from(s),
-        preset: "default".to_string(),
-      },
-      StringOrObject::Object { path, preset } => StartupConfig { path, preset },
-    })
-  }
-}
-
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WidgetConfig {
   /// JSON schema URL to validate the widget config file.
   #[serde(rename = "$schema")]
   schema: Option<String>,
 
+  /// Version of the widget config format. Older files are upgraded on
+  /// read.
+  #[serde(default)]
+  pub version: u64,
+
   /// Relative path to entry point HTML file.
   pub html_path: PathBuf,
 
@@ -94,9 +159,7 @@ pub struct WidgetConfig {
   #[serde(default)]
   pub privileges: WidgetPrivileges,
 
-  /// Where to place the widget. Add alias for `defaultPlacements` for
-  /// compatibility with v2.3.0 and earlier.
-  #[serde(alias = "defaultPlacements")]
+  /// Where to place the widget.
   pub presets: Vec<WidgetPreset>,
 }
 
@@ -108,7 +171,7 @@ pub enum ZOrder {
   TopMost,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct WidgetCaching {
   /// Default duration to cache network resources for (in seconds).
@@ -127,7 +190,7 @@ impl Default for WidgetCaching {
   }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WidgetCachingRule {
   /// URL regex pattern to match.
@@ -137,7 +200,7 @@ pub struct WidgetCachingRule {
   pub duration: u32,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WidgetPreset {
   #[serde(default = "default_preset_name")]
@@ -171,6 +234,11 @@ pub struct WidgetPlacement {
   /// How to reserve space for the widget.
   #[serde(default)]
   pub dock_to_edge: DockConfig,
+
+  /// Whether to hide the widget while a fullscreen window is focused on
+  /// the same monitor.
+  #[serde(default)]
+  pub hide_on_fullscreen: bool,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, ValueEnum)]
@@ -196,6 +264,30 @@ pub enum MonitorSelection {
   Secondary,
   Index(usize),
   Name(String),
+
+  /// Monitors with a name matching the regex.
+  NameRegex(String),
+
+  /// Monitor with the largest area.
+  Largest,
+
+  /// Monitors that are taller than they are wide.
+  Portrait,
+
+  /// Monitors that are wider than they are tall.
+  Landscape,
+
+  /// Monitors with a width of at least the given physical pixels.
+  MinWidth(u32),
+
+  /// Monitors that don't match any of the selections.
+  AllExcept(Vec<MonitorSelection>),
+
+  /// Monitors that match any of the selections.
+  AnyOf(Vec<MonitorSelection>),
+
+  /// Monitors that match all of the selections.
+  AllOf(Vec<MonitorSelection>),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
@@ -261,6 +353,21 @@ pub struct Config {
   /// List of widget configs.
   pub widget_configs: Arc<Mutex<HashMap<PathBuf, WidgetConfig>>>,
 
+  /// Widget packs found within the config directory.
+  pub widget_packs: Arc<Mutex<Vec<WidgetPack>>>,
+
+  /// Errors of config files that failed to validate.
+  pub diagnostics: Arc<ConfigDiagnostics>,
+
+  /// Previous versions of config files written by Zebar.
+  backups: ConfigBackups,
+
+  /// Profile that was activated because it matches the connected
+  /// monitors. Takes precedence over the active profile in the settings,
+  /// but is kept in memory only, so that the settings file isn't written
+  /// on every launch.
+  matched_profile: Mutex<Option<String>>,
+
   _settings_change_rx: broadcast::Receiver<SettingsConfig>,
 
   pub settings_change_tx: broadcast::Sender<SettingsConfig>,
@@ -282,23 +389,28 @@ impl Config {
   pub fn new(app_handle: &AppHandle, config_dir_override: Option<PathBuf>) -> anyhow::Result<Self> {
     let config_dir = match config_dir_override {
       Some(dir) => dir,
-      None => app_handle
-        .path()
-        .resolve(".glzr/zebar", BaseDirectory::Home)
-        .context("Unable to get home directory.")?,
+      None => Self::default_dir()?,
     };
 
-    let settings = Self::read_settings_or_init(app_handle, &config_dir)?;
-    let widget_configs = Self::read_widget_configs(&config_dir)?;
+    let diagnostics = Arc::new(ConfigDiagnostics::new());
+    let settings = Self::read_settings_or_init(app_handle, &config_dir, &diagnostics)?;
+    let config_dir = config_dir.to_absolute()?;
+
+    let (widget_configs, widget_packs) =
+      Self::read_widget_configs(&config_dir, &settings.widget_discovery, &diagnostics)?;
 
     let (settings_change_tx, _settings_change_rx) = broadcast::channel(16);
     let (widget_configs_change_tx, _widget_configs_change_rx) = broadcast::channel(16);
 
     Ok(Self {
       app_handle: app_handle.clone(),
-      config_dir: config_dir.to_absolute()?,
+      config_dir: config_dir.clone(),
       settings: Arc::new(Mutex::new(settings)),
       widget_configs: Arc::new(Mutex::new(widget_configs)),
+      widget_packs: Arc::new(Mutex::new(widget_packs)),
+      diagnostics,
+      backups: ConfigBackups::new(&config_dir),
+      matched_profile: Mutex::new(None),
       _settings_change_rx,
       settings_change_tx,
       _widget_configs_change_rx,
@@ -306,10 +418,157 @@ impl Config {
     })
   }
 
+  /// Returns the default config directory (`~/.glzr/zebar`).
+  pub fn default_dir() -> anyhow::Result<PathBuf> {
+    Ok(
+      dirs::home_dir()
+        .context("Unable to get home directory.")?
+        .join(".glzr/zebar"),
+    )
+  }
+
+  /// Validates the settings and widget configs within a config directory,
+  /// without initializing it.
+  ///
+  /// Beyond the JSON schemas, this checks that HTML entry points exist,
+  /// that startup configs refer to existing configs and presets, that
+  /// the active profile exists, and that shell privilege regexes compile.
+  pub fn validate_dir(config_dir: &PathBuf) -> anyhow::Result<Vec<ConfigDiagnostic>> {
+    let config_dir = config_dir
+      .to_absolute()
+      .with_context(|| format!("Config directory not found: {}", config_dir.display()))?;
+
+    let diagnostics = ConfigDiagnostics::new();
+    let settings = Self::read_settings(&config_dir, &diagnostics)
+      .unwrap_or(None)
+      .unwrap_or_default();
+
+    let (widget_configs, _) =
+      Self::read_widget_configs(&config_dir, &settings.widget_discovery, &diagnostics)?;
+
+    for (config_path, widget_config) in &widget_configs {
+      let html_path = config_path
+        .parent()
+        .map(|dir| dir.join(&widget_config.html_path))
+        .unwrap_or_default();
+
+      if !html_path.is_file() {
+        diagnostics.report(
+          config_path,
+          "/htmlPath",
+          format!("HTML file not found: {}", html_path.display()),
+        );
+      }
+
+      for (index, privilege) in widget_config.privileges.shell_commands.iter().enumerate() {
+        if let Err(err) = regex::Regex::new(&privilege.args_regex) {
+          diagnostics.report(
+            config_path,
+            &format!("/privileges/shellCommands/{}/argsRegex", index),
+            format!("Invalid regex: {}", err),
+          );
+        }
+      }
+    }
+
+    let settings_path = config_dir.join("settings.json");
+
+    if let Some(active_profile) = &settings.active_profile {
+      if settings.active_profile().is_none() {
+        diagnostics.report(
+          &settings_path,
+          "/activeProfile",
+          format!("No profile found with the name '{}'.", active_profile),
+        );
+      }
+    }
+
+    // Check the startup configs of each profile as well.
+    let startup_configs = settings
+      .startup_configs
+      .iter()
+      .enumerate()
+      .map(|(index, config)| (format!("/startupConfigs/{}", index), config))
+      .chain(
+        settings
+          .profiles
+          .iter()
+          .enumerate()
+          .flat_map(|(profile_index, profile)| {
+            profile
+              .startup_configs
+              .iter()
+              .enumerate()
+              .map(move |(index, config)| {
+                (
+                  format!("/profiles/{}/startupConfigs/{}", profile_index, index),
+                  config,
+                )
+              })
+          }),
+      );
+
+    for (pointer, startup_config) in startup_configs {
+      let widget_config = config_dir
+        .join(&startup_config.path)
+        .to_absolute()
+        .ok()
+        .and_then(|path| widget_configs.get(&path));
+
+      let error = match widget_config {
+        None => Some(format!(
+          "Startup config refers to a missing or invalid widget config: {}",
+          startup_config.path.display()
+        )),
+        Some(widget_config)
+          if !widget_config
+            .presets
+            .iter()
+            .any(|preset| preset.name == startup_config.preset) =>
+        {
+          Some(format!(
+            "Startup config refers to a missing preset '{}' in {}.",
+            startup_config.preset,
+            startup_config.path.display()
+          ))
+        }
+        Some(_) => None,
+      };
+
+      if let Some(error) = error {
+        diagnostics.report(&settings_path, &pointer, error);
+      }
+    }
+
+    Ok(diagnostics.all())
+  }
+
+  /// Finds the widget packs within a config directory, without
+  /// initializing it.
+  pub fn widget_packs_in_dir(config_dir: &PathBuf) -> anyhow::Result<Vec<WidgetPack>> {
+    let diagnostics = ConfigDiagnostics::new();
+
+    let settings = Self::read_settings(config_dir, &diagnostics)
+      .unwrap_or(None)
+      .unwrap_or_default();
+
+    Ok(
+      settings
+        .widget_discovery
+        .discover(config_dir, &diagnostics)?
+        .packs,
+    )
+  }
+
   /// Re-evaluates config files within the config directory.
   pub async fn reload(&self) -> anyhow::Result<()> {
-    let new_settings = Self::read_settings_or_init(&self.app_handle, &self.config_dir)?;
-    let new_widget_configs = Self::read_widget_configs(&self.config_dir)?;
+    let new_settings =
+      Self::read_settings_or_init(&self.app_handle, &self.config_dir, &self.diagnostics)?;
+    let (new_widget_configs, new_widget_packs) = Self::read_widget_configs(
+      &self.config_dir,
+      &new_settings.widget_discovery,
+      &self.diagnostics,
+    )?;
 
     {
       let mut settings = self.settings.lock().await;
@@ -321,27 +580,119 @@ impl Config {
       *widget_configs = new_widget_configs.clone();
     }
 
+    *self.widget_packs.lock().await = new_widget_packs;
+
     self.settings_change_tx.send(new_settings)?;
     self.widget_configs_change_tx.send(new_widget_configs)?;
 
     Ok(())
   }
 
+  /// Re-reads the global settings file, and broadcasts the settings if
+  /// they changed.
+  pub async fn reload_settings(&self) -> anyhow::Result<()> {
+    // Keep the current settings if the file was removed.
+    let Some(new_settings) = Self::read_settings(&self.config_dir, &self.diagnostics)? else {
+      return Ok(());
+    };
+
+    let is_discovery_changed = {
+      let mut settings = self.settings.lock().await;
+
+      if *settings == new_settings {
+        return Ok(());
+      }
+
+      let is_discovery_changed = settings.widget_discovery != new_settings.widget_discovery;
+      *settings = new_settings.clone();
+      is_discovery_changed
+    };
+
+    info!("Settings changed on disk.");
+    self.settings_change_tx.send(new_settings)?;
+
+    // Configs may have been added or removed by the new search options.
+    if is_discovery_changed {
+      let config_paths = self.widget_config_paths_within(&self.config_dir).await?;
+      self.reload_widget_configs(&config_paths).await?;
+    }
+
+    Ok(())
+  }
+
+  /// Re-reads the given widget config files, and broadcasts the configs
+  /// that changed. Configs that fail to parse keep their previous value.
+  ///
+  /// Returns the changed configs.
+  pub async fn reload_widget_configs(
+    &self,
+    config_paths: &[PathBuf],
+  ) -> anyhow::Result<HashMap<PathBuf, WidgetConfig>> {
+    let mut changed_configs = HashMap::new();
+
+    let discovered = self
+      .widget_discovery()
+      .await
+      .discover(&self.config_dir, &self.diagnostics)?;
+
+    *self.widget_packs.lock().await = discovered.packs;
+
+    {
+      let mut widget_configs = self.widget_configs.lock().await;
+
+      for path in config_paths {
+        // Configs that were removed or are no longer found (e.g. due to
+        // an ignore pattern) are forgotten.
+        if !discovered.config_paths.contains(path) {
+          if widget_configs.remove(path).is_some() {
+            info!("Widget config removed at: {}", path.display());
+          }
+
+          self.diagnostics.remove(path);
+
+          continue;
+        }
+
+        match Self::parse_widget_config(path, &self.diagnostics) {
+          Ok((config_path, config)) => {
+            if widget_configs.get(&config_path) != Some(&config) {
+              info!("Widget config changed at: {}", config_path.display());
+              widget_configs.insert(config_path.clone(), config.clone());
+              changed_configs.insert(config_path, config);
+            }
+          }
+          Err(err) => {
+            error!("{:?}", err);
+          }
+        }
+      }
+    }
+
+    if !changed_configs.is_empty() {
+      self
+        .widget_configs_change_tx
+        .send(changed_configs.clone())?;
+    }
+
+    Ok(changed_configs)
+  }
+
   /// Reads the global settings file or initializes it with the starter.
   ///
   /// Returns the parsed `SettingsConfig`.
   fn read_settings_or_init(
     app_handle: &AppHandle,
     dir: &PathBuf,
+    diagnostics: &ConfigDiagnostics,
   ) -> anyhow::Result<SettingsConfig> {
-    let settings = Self::read_settings(&dir)?;
+    let settings = Self::read_settings(&dir, diagnostics)?;
 
     match settings {
       Some(settings) => Ok(settings),
       None => {
         Self::create_from_examples(app_handle, dir)?;
 
-        Self::read_settings(&dir)?.context("Failed to create settings config.")
+        Self::read_settings(&dir, diagnostics)?.context("Failed to create settings config.")
       }
     }
   }
@@ -349,22 +700,32 @@ impl Config {
   /// Reads the global settings file.
   ///
   /// Returns the parsed `SettingsConfig` if found.
-  fn read_settings(dir: &PathBuf) -> anyhow::Result<Option<SettingsConfig>> {
+  fn read_settings(
+    dir: &PathBuf,
+    diagnostics: &ConfigDiagnostics,
+  ) -> anyhow::Result<Option<SettingsConfig>> {
     let settings_path = dir.join("settings.json");
 
     match settings_path.exists() {
       false => Ok(None),
-      true => read_and_parse_json(&settings_path),
+      true => diagnostics
+        .read_config_with(
+          &settings_path.to_absolute()?,
+          ConfigSchema::Settings,
+          |value| Ok(config_migration::migrate(ConfigSchema::Settings, value)),
+        )
+        .map(Some),
     }
   }
 
-  /// Writes to the global settings file.
+  /// Writes to the global settings file, keeping a backup of the
+  /// previous version.
   async fn write_settings(&self, new_settings: SettingsConfig) -> anyhow::Result<()> {
     let settings_path = self.config_dir.join("settings.json");
 
-    fs::write(
+    self.backups.write(
       &settings_path,
-      serde_json::to_string_pretty(&new_settings)? + "\n",
+      &(serde_json::to_string_pretty(&new_settings)? + "\n"),
     )?;
 
     let mut settings = self.settings.lock().await;
@@ -375,32 +736,35 @@ impl Config {
     Ok(())
   }
 
-  /// Aggregates all valid widget configs at the 2nd-level of the given
-  /// directory (i.e. `<CONFIG_DIR>/*/*.zebar.json`).
+  /// Aggregates all valid widget configs within the given directory
+  /// (e.g. `<CONFIG_DIR>/*/*.zebar.json`), searching subdirectories as
+  /// configured by `discovery`. JSON5, YAML and TOML configs
+  /// (`.zebar.json5`, `.zebar.yaml` and `.zebar.toml`) are found as well.
   ///
-  /// Returns a hashmap of config paths to their `WidgetConfig` instances.
-  fn read_widget_configs(dir: &PathBuf) -> anyhow::Result<HashMap<PathBuf, WidgetConfig>> {
-    let dir_paths = fs::read_dir(dir)
-      .with_context(|| format!("Failed to read directory: {}", dir.display()))?
-      .filter_map(|entry| Some(entry.ok()?.path()));
-
-    // Scan the 2nd-level of the config directory.
-    let subdir_paths = dir_paths
-      .filter(|path| path.is_dir())
-      .filter_map(|dir| fs::read_dir(dir).ok())
-      .flatten()
-      .filter_map(|entry| Some(entry.ok()?.path()));
-
-    // Collect the found config files.
-    let config_paths = subdir_paths
-      .filter(|path| path.is_file() && has_extension(&path, ".zebar.json"))
-      .collect::<Vec<PathBuf>>();
+  /// Returns a hashmap of config paths to their `WidgetConfig` instances,
+  /// and the widget packs that were found.
+  fn read_widget_configs(
+    dir: &PathBuf,
+    discovery: &WidgetDiscovery,
+    diagnostics: &ConfigDiagnostics,
+  ) -> anyhow::Result<(HashMap<PathBuf, WidgetConfig>, Vec<WidgetPack>)> {
+    let discovered = discovery.discover(dir, diagnostics)?;
+
+    // Forget errors of widget configs and packs that were removed.
+    diagnostics.retain(|path| {
+      let is_manifest = path.ends_with(PACK_MANIFEST_FILE);
+
+      match ConfigFormat::is_widget_path(path) {
+        true => discovered.config_paths.contains(path),
+        false => !is_manifest || path.is_file(),
+      }
+    });
 
     let mut configs = HashMap::new();
 
     // Parse the found config files.
-    for path in config_paths {
-      match Self::parse_widget_config(&path) {
+    for path in &discovered.config_paths {
+      match Self::parse_widget_config(path, diagnostics) {
         Ok((config_path, config)) => {
           info!("Found valid widget config at: {}", config_path.display());
           configs.insert(config_path, config);
@@ -411,21 +775,77 @@ impl Config {
       }
     }
 
-    Ok(configs)
+    Ok((configs, discovered.packs))
   }
 
-  fn parse_widget_config(config_path: &PathBuf) -> anyhow::Result<(PathBuf, WidgetConfig)> {
+  /// Returns the paths of widget configs within the given directory,
+  /// including both loaded configs and any newly found on disk.
+  pub async fn widget_config_paths_within(&self, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let discovered = self
+      .widget_discovery()
+      .await
+      .discover(&self.config_dir, &self.diagnostics)?;
+
+    let mut config_paths = self
+      .widget_configs
+      .lock()
+      .await
+      .keys()
+      .cloned()
+      .chain(discovered.config_paths)
+      .filter(|path| path.starts_with(dir))
+      .collect::<Vec<_>>();
+
+    config_paths.sort();
+    config_paths.dedup();
+
+    Ok(config_paths)
+  }
+
+  /// Returns the paths of widget configs that extend any of the given
+  /// files, including indirectly.
+  pub async fn widget_configs_extending(&self, paths: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    // Only config files can be extended.
+    let paths = paths
+      .iter()
+      .filter(|path| {
+        matches!(
+          path.extension().and_then(|ext| ext.to_str()),
+          Some("json" | "json5" | "yaml" | "toml")
+        )
+      })
+      .collect::<Vec<_>>();
+
+    if paths.is_empty() {
+      return Ok(Vec::new());
+    }
+
+    Ok(
+      self
+        .widget_config_paths_within(&self.config_dir)
+        .await?
+        .into_iter()
+        .filter(|config_path| {
+          config_inheritance::base_paths(config_path)
+            .iter()
+            .any(|base_path| paths.contains(&base_path))
+        })
+        .collect(),
+    )
+  }
+
+  pub fn parse_widget_config(
+    config_path: &PathBuf,
+    diagnostics: &ConfigDiagnostics,
+  ) -> anyhow::Result<(PathBuf, WidgetConfig)> {
     let abs_path = config_path
       .to_absolute()
       .with_context(|| format!("Invalid widget config path '{}'.", config_path.display()))?;
 
-    let config = read_and_parse_json::<WidgetConfig>(&abs_path).map_err(|err| {
-      anyhow::anyhow!(
-        "Failed to parse widget config at '{}': {:?}",
-        abs_path.display(),
-        err
-      )
-    })?;
+    let config =
+      diagnostics.read_config_with::<WidgetConfig>(&abs_path, ConfigSchema::Widget, |value| {
+        config_inheritance::resolve(&abs_path, value)
+      })?;
 
     Ok((abs_path, config))
   }
@@ -450,13 +870,15 @@ impl Config {
     copy_dir_all(&starter_path, config_dir, false)?;
 
     let default_settings = SettingsConfig {
-      schema: Some(
-        "https://github.com/glzr-io/zebar/raw/v2.4.0/resources/settings-schema.json".into(),
-      ),
+      schema: Some(config_migration::schema_url(ConfigSchema::Settings)),
+      version: CONFIG_VERSION,
       startup_configs: vec![StartupConfig {
         path: "macos/macos.zebar.json".into(),
         preset: "default".into(),
       }],
+      profiles: Vec::new(),
+      active_profile: None,
+      widget_discovery: WidgetDiscovery::default(),
     };
 
     let settings_path = config_dir.join("settings.json");
@@ -472,9 +894,95 @@ impl Config {
     self.widget_configs.lock().await.clone()
   }
 
-  /// Returns the widget configs to open on startup.
+  /// Returns the widget packs found within the config directory.
+  pub async fn widget_packs(&self) -> Vec<WidgetPack> {
+    self.widget_packs.lock().await.clone()
+  }
+
+  async fn widget_discovery(&self) -> WidgetDiscovery {
+    self.settings.lock().await.widget_discovery.clone()
+  }
+
+  /// Returns the widget configs to open on startup, which are those of
+  /// the active profile if one is set.
   pub async fn startup_configs(&self) -> Vec<StartupConfig> {
-    self.settings.lock().await.startup_configs.clone()
+    self
+      .active_settings()
+      .await
+      .active_startup_configs()
+      .clone()
+  }
+
+  /// Returns the profiles defined in the settings.
+  pub async fn profiles(&self) -> Vec<Profile> {
+    self.settings.lock().await.profiles.clone()
+  }
+
+  /// Returns the name of the active profile, if any.
+  pub async fn active_profile(&self) -> Option<String> {
+    self.active_settings().await.active_profile
+  }
+
+  /// Returns the settings with the matched profile as the active profile,
+  /// if it still exists.
+  async fn active_settings(&self) -> SettingsConfig {
+    let matched_profile = { self.matched_profile.lock().await.clone() };
+    let mut settings = { self.settings.lock().await.clone() };
+
+    if let Some(profile_name) = matched_profile {
+      if settings
+        .profiles
+        .iter()
+        .any(|profile| profile.name == profile_name)
+      {
+        settings.active_profile = Some(profile_name);
+      }
+    }
+
+    settings
+  }
+
+  /// Returns the name of the first profile whose monitor set matches the
+  /// connected monitors, if any.
+  pub async fn profile_by_monitors(&self, monitors: &[Monitor]) -> Option<String> {
+    self
+      .settings
+      .lock()
+      .await
+      .profiles
+      .iter()
+      .find(|profile| profile.matches_monitors(monitors))
+      .map(|profile| profile.name.clone())
+  }
+
+  /// Sets the active profile and writes it to the settings file.
+  ///
+  /// Replaces any profile that was matched by the connected monitors.
+  pub async fn set_active_profile(&self, profile_name: &str) -> anyhow::Result<()> {
+    let mut new_settings = { self.settings.lock().await.clone() };
+
+    if !new_settings
+      .profiles
+      .iter()
+      .any(|profile| profile.name == profile_name)
+    {
+      bail!("No profile found with the name '{}'.", profile_name);
+    }
+
+    *self.matched_profile.lock().await = None;
+
+    if new_settings.active_profile.as_deref() == Some(profile_name) {
+      return Ok(());
+    }
+
+    new_settings.active_profile = Some(profile_name.to_string());
+    self.write_settings(new_settings).await
+  }
+
+  /// Activates the profile that matches the connected monitors, without
+  /// writing it to the settings file.
+  pub async fn set_matched_profile(&self, profile_name: &str) {
+    *self.matched_profile.lock().await = Some(profile_name.to_string());
   }
 
   /// Returns the widget configs to open on startup.
@@ -501,33 +1009,87 @@ impl Config {
   ) -> anyhow::Result<()> {
     info!("Updating widget config at {}.", config_path.display());
 
-    {
-      let mut widget_configs = self.widget_configs.lock().await;
-
-      let config_entry = widget_configs.get_mut(config_path).context(format!(
+    let old_config = self
+      .widget_configs
+      .lock()
+      .await
+      .get(config_path)
+      .cloned()
+      .context(format!(
         "Widget config not found at {}.",
         config_path.display()
       ))?;
 
-      // Update the config in state.
-      *config_entry = new_config.clone();
-    }
+    // Write the updated config to file in its original format. Only the
+    // changed values are written, so that comments are kept where the
+    // format allows, and values inherited via `extends` aren't copied
+    // into the file.
+    let format = ConfigFormat::from_path(config_path);
+    let new_value = serde_json::to_value(&new_config)?;
+    let previous_content = fs::read_to_string(config_path).ok();
+
+    let previous_value = previous_content
+      .as_deref()
+      .and_then(|content| format.parse(content).ok());
+
+    let content = match (previous_content.as_deref(), previous_value) {
+      (Some(previous_content), Some(previous_value)) => {
+        let value = config_inheritance::overrides(
+          &serde_json::to_value(&old_config)?,
+          &new_value,
+          &previous_value,
+        );
+
+        format.serialize(&value, Some((previous_content, &previous_value)))?
+      }
+      _ => format.serialize(&new_value, None)?,
+    };
 
-    // Emit the changed config.
+    self.backups.write(config_path, &content)?;
+
+    // Update the config in state and emit it once it's been written.
     self
-      .widget_configs_change_tx
-      .send(HashMap::from([(config_path.clone(), new_config.clone())]))?;
+      .widget_configs
+      .lock()
+      .await
+      .insert(config_path.clone(), new_config.clone());
 
-    // Write the updated config to file.
-    fs::write(
-      &config_path,
-      serde_json::to_string_pretty(&new_config)? + "\n",
-    )?;
+    self
+      .widget_configs_change_tx
+      .send(HashMap::from([(config_path.clone(), new_config)]))?;
 
     Ok(())
   }
 
-  /// Adds the given config to be launched on startup.
+  /// Whether there's a write to a config file that can be undone.
+  pub fn can_undo(&self) -> bool {
+    self.backups.can_undo()
+  }
+
+  /// Restores the config file that was last written by Zebar to its
+  /// previous version, and reloads it.
+  ///
+  /// Returns the path of the restored file, if there was a change to
+  /// undo.
+  pub async fn undo(&self) -> anyhow::Result<Option<PathBuf>> {
+    let Some(path) = self.backups.undo()? else {
+      return Ok(None);
+    };
+
+    info!("Restored previous version of {}.", path.display());
+
+    match path == self.config_dir.join("settings.json") {
+      true => self.reload_settings().await?,
+      false => {
+        self.reload_widget_configs(&[path.clone()]).await?;
+      }
+    }
+
+    Ok(Some(path))
+  }
+
+  /// Adds the given config to be launched on startup, within the active
+  /// profile if one is set.
   ///
   /// Config path can be either absolute or relative.
   pub async fn add_startup_config(
@@ -535,6 +1097,7 @@ impl Config {
     config_path: &PathBuf,
     preset_name: &str,
   ) -> anyhow::Result<()> {
+    let active_profile = self.active_profile().await;
     let mut new_settings = { self.settings.lock().await.clone() };
 
     let startup_config = StartupConfig {
@@ -542,15 +1105,18 @@ impl Config {
       preset: preset_name.to_string(),
     };
 
-    if new_settings.startup_configs.contains(&startup_config) {
+    let startup_configs = new_settings.startup_configs_mut(active_profile.as_deref());
+
+    if startup_configs.contains(&startup_config) {
       return Ok(());
     }
 
-    new_settings.startup_configs.push(startup_config);
+    startup_configs.push(startup_config);
     self.write_settings(new_settings).await
   }
 
-  /// Removes the given config from being launched on startup.
+  /// Removes the given config from being launched on startup, within the
+  /// active profile if one is set.
   ///
   /// Config path can be either absolute or relative.
   pub async fn remove_startup_config(
@@ -558,11 +1124,12 @@ impl Config {
     config_path: &PathBuf,
     preset_name: &str,
   ) -> anyhow::Result<()> {
+    let active_profile = self.active_profile().await;
     let mut new_settings = { self.settings.lock().await.clone() };
     let rel_path = self.to_relative_path(config_path);
 
     new_settings
-      .startup_configs
+      .startup_configs_mut(active_profile.as_deref())
       .retain(|config| config.path != rel_path || config.preset != preset_name);
 
     self.write_settings(new_settings).await
@@ -591,8 +1158,8 @@ impl Config {
 
   /// Formats a widget's config path for display.
   ///
-  /// Returns relative path without the `.zebar.json` suffix (e.g.
-  /// `starter/vanilla`).
+  /// Returns relative path without the `.zebar.json` (or other format's)
+  /// suffix (e.g. `starter/vanilla`).
   pub fn formatted_widget_path(&self, config_path: &PathBuf) -> String {
     let path = self.to_relative_path(config_path).to_unicode_string();
 
@@ -600,7 +1167,9 @@ impl Config {
     #[cfg(windows)]
     let path = path.replace('\\', "/");
 
-    path.strip_suffix(".zebar.json").unwrap_or(&path).into()
+    ConfigFormat::strip_widget_extension(&path)
+      .unwrap_or(&path)
+      .into()
   }
 
   /// Returns the widget config at the given path.
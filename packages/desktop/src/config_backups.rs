@@ -0,0 +1,185 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{read_and_parse_json, write_atomic};
+
+/// Name of the folder within the config directory that holds backups.
+pub const BACKUPS_DIR: &str = ".backups";
+
+/// File within the backups folder that lists the backups, oldest first.
+const INDEX_FILE: &str = "index.json";
+
+/// Number of previous versions that are kept per file.
+const MAX_BACKUPS_PER_FILE: usize = 10;
+
+/// Previous versions of config files that were written by Zebar, kept so
+/// that changes can be undone.
+#[derive(Debug)]
+pub struct ConfigBackups {
+  config_dir: PathBuf,
+}
+
+/// A previous version of a config file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupEntry {
+  /// Path of the config file, relative to the config directory.
+  path: PathBuf,
+
+  /// Name of the backup file within the backups folder.
+  backup_file: String,
+}
+
+impl ConfigBackups {
+  pub fn new(config_dir: &Path) -> Self {
+    Self {
+      config_dir: config_dir.to_path_buf(),
+    }
+  }
+
+  /// Writes the given content to a config file. The file is replaced
+  /// atomically, and its previous content is backed up if it existed.
+  pub fn write(&self, path: &Path, content: &str) -> anyhow::Result<()> {
+    if let Ok(previous_content) = fs::read(path) {
+      self
+        .backup(path, &previous_content)
+        .with_context(|| format!("Failed to back up {}.", path.display()))?;
+    }
+
+    write_atomic(path, content)
+  }
+
+  /// Whether there's a change that can be undone.
+  pub fn can_undo(&self) -> bool {
+    !self.read_index().is_empty()
+  }
+
+  /// Restores the most recently backed up file to its previous content.
+  ///
+  /// Returns the absolute path of the restored file, if there was a
+  /// backup to restore.
+  pub fn undo(&self) -> anyhow::Result<Option<PathBuf>> {
+    let mut index = self.read_index();
+
+    let Some(entry) = index.pop() else {
+      return Ok(None);
+    };
+
+    let backup_path = self.dir().join(&entry.backup_file);
+    let content = fs::read(&backup_path)
+      .with_context(|| format!("Failed to read backup {}.", backup_path.display()))?;
+
+    let path = self.config_dir.join(&entry.path);
+    write_atomic(&path, content)?;
+
+    self.write_index(&index)?;
+    let _ = fs::remove_file(backup_path);
+
+    Ok(Some(path))
+  }
+
+  fn backup(&self, path: &Path, content: &[u8]) -> anyhow::Result<()> {
+    let rel_path = path
+      .strip_prefix(&self.config_dir)
+      .with_context(|| format!("{} is not within the config directory.", path.display()))?
+      .to_path_buf();
+
+    let file_name = path
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string())
+      .unwrap_or_default();
+
+    // The `.bak` suffix keeps backups of widget configs from being
+    // discovered as configs themselves.
+    let backup_file = |millis: u128| format!("{}-{}.bak", millis, file_name);
+
+    // Writes within the same millisecond get the next free timestamp.
+    let mut millis = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+
+    while self.dir().join(backup_file(millis)).exists() {
+      millis += 1;
+    }
+
+    let entry = BackupEntry {
+      path: rel_path,
+      backup_file: backup_file(millis),
+    };
+
+    fs::create_dir_all(self.dir())?;
+    fs::write(self.dir().join(&entry.backup_file), content)?;
+
+    let mut index = self.read_index();
+    index.push(entry.clone());
+
+    // Drop the oldest backups of the file beyond the limit.
+    let backup_count = index.iter().filter(|e| e.path == entry.path).count();
+    let mut excess = backup_count.saturating_sub(MAX_BACKUPS_PER_FILE);
+
+    index.retain(|e| {
+      if excess > 0 && e.path == entry.path {
+        excess -= 1;
+        let _ = fs::remove_file(self.dir().join(&e.backup_file));
+        return false;
+      }
+
+      true
+    });
+
+    self.write_index(&index)
+  }
+
+  fn dir(&self) -> PathBuf {
+    self.config_dir.join(BACKUPS_DIR)
+  }
+
+  /// Reads the list of backups. Entries whose backup file is missing are
+  /// left out.
+  fn read_index(&self) -> Vec<BackupEntry> {
+    read_and_parse_json::<Vec<BackupEntry>>(&self.dir().join(INDEX_FILE))
+      .unwrap_or_default()
+      .into_iter()
+      .filter(|entry| self.dir().join(&entry.backup_file).is_file())
+      .collect()
+  }
+
+  fn write_index(&self, index: &[BackupEntry]) -> anyhow::Result<()> {
+    write_atomic(
+      &self.dir().join(INDEX_FILE),
+      serde_json::to_string_pretty(index)? + "\n",
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn undoes_writes_in_reverse_order() {
+    let config_dir = std::env::temp_dir().join(format!("zebar-backups-{}", uuid::Uuid::new_v4()));
+    let path = config_dir.join("settings.json");
+    fs::create_dir_all(&config_dir).unwrap();
+
+    let backups = ConfigBackups::new(&config_dir);
+    backups.write(&path, "1").unwrap();
+    assert!(!backups.can_undo());
+
+    for content in ["2", "3"] {
+      backups.write(&path, content).unwrap();
+    }
+
+    assert_eq!(backups.undo().unwrap(), Some(path.clone()));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "2");
+    assert_eq!(backups.undo().unwrap(), Some(path.clone()));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "1");
+    assert_eq!(backups.undo().unwrap(), None);
+
+    fs::remove_dir_all(config_dir).unwrap();
+  }
+}
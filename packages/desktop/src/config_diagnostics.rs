@@ -0,0 +1,372 @@
+use std::{
+  collections::HashMap,
+  fmt, fs,
+  path::{Path, PathBuf},
+  sync::{Mutex, OnceLock},
+};
+
+use jsonschema::Validator;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+use crate::config_format::ConfigFormat;
+
+/// JSON schemas that config files are validated against.
+#[derive(Clone, Copy, Debug)]
+pub enum ConfigSchema {
+  Widget,
+  Settings,
+  Pack,
+}
+
+impl ConfigSchema {
+  /// Returns the compiled validator for the schema.
+  fn validator(&self) -> &'static Validator {
+    static WIDGET_VALIDATOR: OnceLock<Validator> = OnceLock::new();
+    static SETTINGS_VALIDATOR: OnceLock<Validator> = OnceLock::new();
+    static PACK_VALIDATOR: OnceLock<Validator> = OnceLock::new();
+
+    let (validator, schema) = match self {
+      ConfigSchema::Widget => (
+        &WIDGET_VALIDATOR,
+        include_str!("../../../resources/widget-schema.json"),
+      ),
+      ConfigSchema::Settings => (
+        &SETTINGS_VALIDATOR,
+        include_str!("../../../resources/settings-schema.json"),
+      ),
+      ConfigSchema::Pack => (
+        &PACK_VALIDATOR,
+        include_str!("../../../resources/pack-schema.json"),
+      ),
+    };
+
+    validator.get_or_init(|| {
+      let schema = serde_json::from_str(schema).expect("Bundled JSON schema is invalid JSON.");
+      jsonschema::validator_for(&schema).expect("Bundled JSON schema is invalid.")
+    })
+  }
+}
+
+/// An error within a config file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDiagnostic {
+  /// Absolute path to the config file.
+  pub path: PathBuf,
+
+  /// 1-based line of the error, if it could be located.
+  pub line: Option<usize>,
+
+  /// 1-based column of the error, if it could be located.
+  pub column: Option<usize>,
+
+  /// JSON pointer to the invalid value (e.g. `/presets/0/anchor`). Empty
+  /// if the error is at the root of the file.
+  pub pointer: String,
+
+  pub message: String,
+}
+
+impl fmt::Display for ConfigDiagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.path.display())?;
+
+    if let (Some(line), Some(column)) = (self.line, self.column) {
+      write!(f, ":{}:{}", line, column)?;
+    }
+
+    match self.pointer.is_empty() {
+      true => write!(f, ": {}", self.message),
+      false => write!(f, ": {} (at `{}`)", self.message, self.pointer),
+    }
+  }
+}
+
+/// Errors of config files that failed to validate, keyed by file path.
+#[derive(Debug)]
+pub struct ConfigDiagnostics {
+  diagnostics: Mutex<HashMap<PathBuf, Vec<ConfigDiagnostic>>>,
+
+  _change_rx: broadcast::Receiver<Vec<ConfigDiagnostic>>,
+
+  pub change_tx: broadcast::Sender<Vec<ConfigDiagnostic>>,
+}
+
+impl ConfigDiagnostics {
+  /// Creates a new `ConfigDiagnostics` instance.
+  pub fn new() -> Self {
+    let (change_tx, _change_rx) = broadcast::channel(16);
+
+    Self {
+      diagnostics: Mutex::new(HashMap::new()),
+      _change_rx,
+      change_tx,
+    }
+  }
+
+  /// Returns all diagnostics, sorted by file and location.
+  pub fn all(&self) -> Vec<ConfigDiagnostic> {
+    let mut diagnostics = self
+      .diagnostics
+      .lock()
+      .unwrap()
+      .values()
+      .flatten()
+      .cloned()
+      .collect::<Vec<_>>();
+
+    diagnostics.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+    diagnostics
+  }
+
+  /// Reads a config file, validates it against the given schema, and
+  /// parses it into `T`. The file's format is inferred from its
+  /// extension.
+  ///
+  /// Replaces the file's diagnostics with any errors found.
+  pub fn read_config<T: DeserializeOwned>(
+    &self,
+    path: &Path,
+    schema: ConfigSchema,
+  ) -> anyhow::Result<T> {
+    self.read_config_with(path, schema, Ok)
+  }
+
+  /// Like `read_config`, but transforms the parsed value with `resolve`
+  /// before it's validated (e.g. to merge in other config files).
+  ///
+  /// Errors from `resolve` are reported at the file's `extends` field.
+  pub fn read_config_with<T: DeserializeOwned>(
+    &self,
+    path: &Path,
+    schema: ConfigSchema,
+    resolve: impl FnOnce(Value) -> anyhow::Result<Value>,
+  ) -> anyhow::Result<T> {
+    let res = fs::read_to_string(path)
+      .map_err(|err| vec![diagnostic(path, None, "", err.to_string())])
+      .and_then(|content| validate(path, &content, schema, resolve));
+
+    match res {
+      Ok(config) => {
+        self.set(path, Vec::new());
+        Ok(config)
+      }
+      Err(diagnostics) => {
+        let message = diagnostics
+          .iter()
+          .map(ToString::to_string)
+          .collect::<Vec<_>>()
+          .join("\n");
+
+        self.set(path, diagnostics);
+        anyhow::bail!("Invalid config file:\n{}", message)
+      }
+    }
+  }
+
+  /// Adds an error for the value at a JSON pointer within a file, for
+  /// checks beyond what the schema covers.
+  pub fn report(&self, path: &Path, pointer: &str, message: String) {
+    let location = fs::read_to_string(path)
+      .ok()
+      .and_then(|content| ConfigFormat::from_path(path).locate(&content, pointer));
+
+    let mut file_diagnostics = self
+      .diagnostics
+      .lock()
+      .unwrap()
+      .get(path)
+      .cloned()
+      .unwrap_or_default();
+
+    file_diagnostics.push(diagnostic(path, location, pointer, message));
+    self.set(path, file_diagnostics);
+  }
+
+  /// Removes the diagnostics of a file (e.g. when it was deleted).
+  pub fn remove(&self, path: &Path) {
+    self.set(path, Vec::new());
+  }
+
+  /// Removes the diagnostics of files that don't match the predicate.
+  pub fn retain(&self, predicate: impl Fn(&PathBuf) -> bool) {
+    let is_changed = {
+      let mut diagnostics = self.diagnostics.lock().unwrap();
+      let count = diagnostics.len();
+      diagnostics.retain(|path, _| predicate(path));
+      diagnostics.len() != count
+    };
+
+    if is_changed {
+      let _ = self.change_tx.send(self.all());
+    }
+  }
+
+  fn set(&self, path: &Path, file_diagnostics: Vec<ConfigDiagnostic>) {
+    let is_changed = {
+      let mut diagnostics = self.diagnostics.lock().unwrap();
+
+      let previous = match file_diagnostics.is_empty() {
+        true => diagnostics.remove(path),
+        false => diagnostics.insert(path.to_path_buf(), file_diagnostics.clone()),
+      };
+
+      previous.unwrap_or_default() != file_diagnostics
+    };
+
+    if is_changed {
+      let _ = self.change_tx.send(self.all());
+    }
+  }
+}
+
+/// Validates the content of a config file against a schema and parses it
+/// into `T`.
+///
+/// Returns all errors that were found on failure.
+fn validate<T: DeserializeOwned>(
+  path: &Path,
+  content: &str,
+  schema: ConfigSchema,
+  resolve: impl FnOnce(Value) -> anyhow::Result<Value>,
+) -> Result<T, Vec<ConfigDiagnostic>> {
+  let format = ConfigFormat::from_path(path);
+
+  let value = format
+    .parse(content)
+    .map_err(|err| vec![diagnostic(path, err.location, "", err.message)])?;
+
+  let resolved_value = resolve(value.clone()).map_err(|err| {
+    vec![diagnostic(
+      path,
+      format.locate(content, "/extends"),
+      "/extends",
+      format!("{:#}", err),
+    )]
+  })?;
+
+  let schema_errors = schema
+    .validator()
+    .iter_errors(&resolved_value)
+    .map(|err| {
+      let pointer = err.instance_path.to_string();
+
+      diagnostic(
+        path,
+        format.locate(content, &pointer),
+        &pointer,
+        err.to_string(),
+      )
+    })
+    .collect::<Vec<_>>();
+
+  if !schema_errors.is_empty() {
+    return Err(schema_errors);
+  }
+
+  match format {
+    // Parse from the original content so that errors not covered by the
+    // schema (e.g. invalid length values) still have a location.
+    ConfigFormat::Json if resolved_value == value => {
+      serde_json::from_str(content).map_err(|err| vec![serde_diagnostic(path, err)])
+    }
+    _ => serde_json::from_value(resolved_value).map_err(|err| vec![serde_diagnostic(path, err)]),
+  }
+}
+
+fn diagnostic(
+  path: &Path,
+  location: Option<(usize, usize)>,
+  pointer: &str,
+  message: String,
+) -> ConfigDiagnostic {
+  ConfigDiagnostic {
+    path: path.to_path_buf(),
+    line: location.map(|(line, _)| line),
+    column: location.map(|(_, column)| column),
+    pointer: pointer.to_string(),
+    message,
+  }
+}
+
+fn serde_diagnostic(path: &Path, err: serde_json::Error) -> ConfigDiagnostic {
+  let location = (err.line() > 0).then(|| (err.line(), err.column()));
+
+  // Strip the location suffix, since it's reported separately.
+  let message = err.to_string();
+  let message = message
+    .rsplit_once(" at line ")
+    .map_or(message.as_str(), |(message, _)| message)
+    .to_string();
+
+  diagnostic(path, location, "", message)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const JSON: &str = r#"{
+  "htmlPath": "index.html",
+  "presets": [
+    { "name": "a/b", "anchor": "top_left" },
+    {
+      "name": "default",
+      "anchor": "nope"
+    }
+  ]
+}"#;
+
+  #[test]
+  fn reports_schema_errors() {
+    let errors =
+      validate::<Value>(Path::new("a.zebar.json"), JSON, ConfigSchema::Widget, Ok).unwrap_err();
+
+    let preset_error = errors
+      .iter()
+      .find(|err| err.pointer == "/presets/0")
+      .unwrap();
+
+    assert_eq!((preset_error.line, preset_error.column), (Some(4), Some(5)));
+  }
+
+  #[test]
+  fn reports_errors_of_other_formats() {
+    let errors = validate::<Value>(
+      Path::new("a.zebar.toml"),
+      "htmlPath = \"index.html\"\n\n[[presets]]\nanchor = \"nope\"\n",
+      ConfigSchema::Widget,
+      Ok,
+    )
+    .unwrap_err();
+
+    assert!(errors.iter().any(|err| err.pointer == "/presets/0/anchor"));
+
+    let errors = validate::<Value>(
+      Path::new("a.zebar.yaml"),
+      "htmlPath: index.html\npresets: [\n",
+      ConfigSchema::Widget,
+      Ok,
+    )
+    .unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].line.is_some());
+  }
+
+  #[test]
+  fn reports_syntax_errors() {
+    let errors = validate::<Value>(
+      Path::new("settings.json"),
+      "{\n  \"startupConfigs\": [,]\n}",
+      ConfigSchema::Settings,
+      Ok,
+    )
+    .unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(2));
+  }
+}
@@ -0,0 +1,496 @@
+use std::path::Path;
+
+use serde_json::{Map, Value};
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, TableLike};
+
+use crate::common::{line_column, object_contents_start, pointer_segments, value_span};
+
+/// File extensions of widget configs in each of the supported formats.
+const WIDGET_EXTENSIONS: [&str; 4] = [".zebar.json", ".zebar.json5", ".zebar.yaml", ".zebar.toml"];
+
+/// File formats that config files can be written in.
+///
+/// All formats are parsed into the same JSON data model, so that they
+/// share the JSON schemas and serde types.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigFormat {
+  Json,
+  Json5,
+  Yaml,
+  Toml,
+}
+
+/// A syntax error within a config file.
+#[derive(Debug)]
+pub struct ParseError {
+  pub message: String,
+
+  /// 1-based line and column of the error, if known.
+  pub location: Option<(usize, usize)>,
+}
+
+/// A changed value within a config, identified by its path of object
+/// keys and array indices.
+type Change = (Vec<String>, Value);
+
+impl ConfigFormat {
+  /// Gets the format of a config file from its extension. Defaults to
+  /// JSON.
+  pub fn from_path(path: &Path) -> Self {
+    match path.extension().and_then(|ext| ext.to_str()) {
+      Some("json5") => ConfigFormat::Json5,
+      Some("yaml") => ConfigFormat::Yaml,
+      Some("toml") => ConfigFormat::Toml,
+      _ => ConfigFormat::Json,
+    }
+  }
+
+  /// Returns whether the path is a widget config in any of the supported
+  /// formats (e.g. `vanilla.zebar.json` or `vanilla.zebar.toml`).
+  pub fn is_widget_path(path: &Path) -> bool {
+    path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .is_some_and(|name| Self::strip_widget_extension(name).is_some())
+  }
+
+  /// Strips the widget config extension from a path string (e.g.
+  /// `starter/vanilla.zebar.yaml` -> `starter/vanilla`).
+  pub fn strip_widget_extension(path: &str) -> Option<&str> {
+    WIDGET_EXTENSIONS
+      .iter()
+      .find_map(|extension| path.strip_suffix(extension))
+  }
+
+  /// Parses the contents of a config file into a JSON value.
+  pub fn parse(&self, content: &str) -> Result<Value, ParseError> {
+    match self {
+      ConfigFormat::Json => serde_json::from_str(content).map_err(|err| ParseError {
+        location: (err.line() > 0).then(|| (err.line(), err.column())),
+        message: strip_location(err.to_string()),
+      }),
+      ConfigFormat::Json5 => json5::from_str(content).map_err(|err| match err {
+        json5::Error::Message { msg, location } => ParseError {
+          message: msg,
+          location: location.map(|location| (location.line, location.column)),
+        },
+      }),
+      ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|err| ParseError {
+        location: err
+          .location()
+          .map(|location| (location.line(), location.column())),
+        message: strip_location(err.to_string()),
+      }),
+      ConfigFormat::Toml => toml_edit::de::from_str(content).map_err(|err| ParseError {
+        message: err.message().to_string(),
+        location: err.span().map(|span| line_column(content, span.start)),
+      }),
+    }
+  }
+
+  /// Finds the 1-based line and column of the value at a JSON pointer
+  /// within the contents of a config file.
+  ///
+  /// Only supported for JSON and JSON5.
+  pub fn locate(&self, content: &str, pointer: &str) -> Option<(usize, usize)> {
+    match self {
+      ConfigFormat::Json | ConfigFormat::Json5 => {
+        value_span(content, &pointer_segments(pointer)).map(|span| line_column(content, span.start))
+      }
+      ConfigFormat::Yaml | ConfigFormat::Toml => None,
+    }
+  }
+
+  /// Serializes a value into the contents of a config file.
+  ///
+  /// If the file's previous contents are given, along with the value
+  /// they were parsed into, only the changed values are written to the
+  /// existing contents. This keeps comments and formatting for JSON,
+  /// JSON5 and TOML. YAML files are always rewritten in full.
+  pub fn serialize(
+    &self,
+    value: &Value,
+    previous: Option<(&str, &Value)>,
+  ) -> anyhow::Result<String> {
+    if let Some((content, previous_value)) = previous {
+      let mut changes = Vec::new();
+      collect_changes(&mut Vec::new(), previous_value, value, &mut changes);
+
+      let patched = match self {
+        ConfigFormat::Json | ConfigFormat::Json5 => patch_json(content, &changes),
+        ConfigFormat::Toml => patch_toml(content, &changes),
+        ConfigFormat::Yaml => None,
+      };
+
+      // TOML has no null, so null values are left out of the file.
+      let expected = match self {
+        ConfigFormat::Toml => without_nulls(value),
+        _ => value.clone(),
+      };
+
+      // Patches that don't read back as the given value (e.g. due to
+      // unusual formatting) are discarded for a full rewrite.
+      let is_valid = |patched: &String| self.parse(patched).is_ok_and(|parsed| parsed == expected);
+
+      if let Some(patched) = patched.filter(is_valid) {
+        return Ok(patched);
+      }
+    }
+
+    Ok(match self {
+      ConfigFormat::Json | ConfigFormat::Json5 => serde_json::to_string_pretty(value)? + "\n",
+      ConfigFormat::Yaml => serde_yaml::to_string(value)?,
+      ConfigFormat::Toml => match to_toml_item(value) {
+        Some(Item::Table(table)) => DocumentMut::from(table).to_string(),
+        _ => anyhow::bail!("TOML config must be a table."),
+      },
+    })
+  }
+}
+
+/// Strips the location suffix from serde error messages, since it's
+/// reported separately.
+fn strip_location(message: String) -> String {
+  message
+    .rsplit_once(" at line ")
+    .map_or(message.as_str(), |(message, _)| message)
+    .to_string()
+}
+
+/// Collects the differences between two values as a list of changes.
+///
+/// Objects and equal-length arrays are compared recursively, so that
+/// changes are as small as possible.
+fn collect_changes(path: &mut Vec<String>, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+  match (old, new) {
+    _ if old == new => {}
+    (Value::Object(old_map), Value::Object(new_map))
+      if old_map.keys().all(|key| new_map.contains_key(key)) =>
+    {
+      for (key, new_value) in new_map {
+        path.push(key.clone());
+        collect_changes(
+          path,
+          old_map.get(key).unwrap_or(&Value::Null),
+          new_value,
+          changes,
+        );
+        path.pop();
+      }
+    }
+    (Value::Array(old_items), Value::Array(new_items)) if old_items.len() == new_items.len() => {
+      for (index, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+        path.push(index.to_string());
+        collect_changes(path, old_item, new_item, changes);
+        path.pop();
+      }
+    }
+    _ => changes.push((path.clone(), new.clone())),
+  }
+}
+
+/// Wraps a value in nested objects for the given keys (e.g. `["a", "b"]`
+/// -> `{ "a": { "b": value } }`).
+fn nest(keys: &[String], value: &Value) -> Value {
+  keys.iter().rev().fold(value.clone(), |value, key| {
+    Value::Object(Map::from_iter([(key.clone(), value)]))
+  })
+}
+
+/// Applies changes to JSON or JSON5 text by replacing the changed values
+/// in place.
+///
+/// Values that don't exist yet are inserted into the closest existing
+/// parent object.
+fn patch_json(content: &str, changes: &[Change]) -> Option<String> {
+  let mut content = content.to_string();
+
+  for (path, value) in changes {
+    if let Some(span) = value_span(&content, path) {
+      let json = to_indented_json(value, line_indent(&content, span.start))?;
+      content.replace_range(span, &json);
+      continue;
+    }
+
+    // Find the closest parent object that exists.
+    let (depth, parent_span) = (0..path.len())
+      .rev()
+      .find_map(|depth| Some((depth, value_span(&content, &path[..depth])?)))?;
+
+    if !content[parent_span.start..].starts_with('{') {
+      return None;
+    }
+
+    // Insert as the first member, reusing the indentation of the
+    // existing members.
+    let insert_pos = parent_span.start + 1;
+    let first_member_pos = object_contents_start(&content, parent_span.start);
+    let separator = &content[insert_pos..first_member_pos];
+
+    let indent = match separator.rfind('\n') {
+      Some(newline) => &separator[newline + 1..],
+      None => line_indent(&content, insert_pos),
+    };
+
+    let member = format!(
+      "{}: {}",
+      serde_json::to_string(&path[depth]).ok()?,
+      to_indented_json(&nest(&path[depth + 1..], value), indent)?
+    );
+
+    let insertion = match (
+      content[first_member_pos..].starts_with('}'),
+      separator.contains('\n'),
+    ) {
+      (true, _) => member,
+      (false, true) => format!("\n{}{},", indent, member),
+      (false, false) => format!(" {},", member),
+    };
+
+    content.insert_str(insert_pos, &insertion);
+  }
+
+  Some(content)
+}
+
+/// Returns the leading whitespace of the line at `pos`.
+fn line_indent(content: &str, pos: usize) -> &str {
+  let line_start = content[..pos].rfind('\n').map_or(0, |newline| newline + 1);
+  let line = &content[line_start..];
+
+  &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Pretty-prints a value, indenting all but the first line by `indent`.
+fn to_indented_json(value: &Value, indent: &str) -> Option<String> {
+  let json = serde_json::to_string_pretty(value).ok()?;
+  Some(json.replace('\n', &format!("\n{}", indent)))
+}
+
+/// Applies changes to a TOML document, keeping its comments and
+/// formatting.
+fn patch_toml(content: &str, changes: &[Change]) -> Option<String> {
+  let mut document = content.parse::<DocumentMut>().ok()?;
+
+  for (path, value) in changes {
+    set_in_table(document.as_table_mut(), path, value)?;
+  }
+
+  Some(document.to_string())
+}
+
+fn set_in_table(table: &mut dyn TableLike, path: &[String], value: &Value) -> Option<()> {
+  let (key, rest) = path.split_first()?;
+
+  if !rest.is_empty() {
+    return match table.get_mut(key) {
+      Some(item) => set_in_item(item, rest, value),
+      None => {
+        table.insert(key, to_toml_item(&nest(rest, value))?);
+        Some(())
+      }
+    };
+  }
+
+  // TOML has no null, so null values are removed instead.
+  let Some(mut new_item) = to_toml_item(value) else {
+    table.remove(key);
+    return Some(());
+  };
+
+  match table.get_mut(key) {
+    Some(item) => {
+      // Keep comments and whitespace around the existing value.
+      if let (Some(old_value), Some(new_value)) = (item.as_value(), new_item.as_value_mut()) {
+        *new_value.decor_mut() = old_value.decor().clone();
+      }
+
+      *item = new_item;
+    }
+    None => {
+      table.insert(key, new_item);
+    }
+  }
+
+  Some(())
+}
+
+fn set_in_item(item: &mut Item, path: &[String], value: &Value) -> Option<()> {
+  match item {
+    Item::ArrayOfTables(tables) => {
+      let (index, rest) = path.split_first()?;
+      let table = tables.get_mut(index.parse().ok()?)?;
+
+      match rest.is_empty() {
+        true => None,
+        false => set_in_table(table, rest, value),
+      }
+    }
+    Item::Value(toml_edit::Value::Array(array)) => {
+      let (index, rest) = path.split_first()?;
+      let array_item = array.get_mut(index.parse().ok()?)?;
+
+      match array_item {
+        _ if rest.is_empty() => {
+          let mut new_value = to_toml_value(value)?;
+          *new_value.decor_mut() = array_item.decor().clone();
+          *array_item = new_value;
+          Some(())
+        }
+        toml_edit::Value::InlineTable(table) => set_in_table(table, rest, value),
+        _ => None,
+      }
+    }
+    _ => set_in_table(item.as_table_like_mut()?, path, value),
+  }
+}
+
+/// Converts a JSON value into a TOML item, using tables for objects and
+/// arrays of tables for arrays of objects.
+///
+/// Returns `None` for null values.
+/// Removes null values from objects, recursively.
+fn without_nulls(value: &Value) -> Value {
+  match value {
+    Value::Object(map) => Value::Object(
+      map
+        .iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| (key.clone(), without_nulls(value)))
+        .collect(),
+    ),
+    Value::Array(items) => Value::Array(items.iter().map(without_nulls).collect()),
+    _ => value.clone(),
+  }
+}
+
+fn to_toml_item(value: &Value) -> Option<Item> {
+  match value {
+    Value::Object(map) => {
+      let mut table = Table::new();
+
+      for (key, value) in map {
+        if let Some(item) = to_toml_item(value) {
+          table.insert(key, item);
+        }
+      }
+
+      Some(Item::Table(table))
+    }
+    Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => {
+      let mut tables = ArrayOfTables::new();
+
+      for item in items {
+        if let Some(Item::Table(table)) = to_toml_item(item) {
+          tables.push(table);
+        }
+      }
+
+      Some(Item::ArrayOfTables(tables))
+    }
+    _ => to_toml_value(value).map(Item::Value),
+  }
+}
+
+/// Converts a JSON value into an inline TOML value.
+///
+/// Returns `None` for null values.
+fn to_toml_value(value: &Value) -> Option<toml_edit::Value> {
+  match value {
+    Value::Null => None,
+    Value::Bool(bool) => Some((*bool).into()),
+    Value::Number(number) => number
+      .as_i64()
+      .map(Into::into)
+      .or_else(|| number.as_f64().map(Into::into)),
+    Value::String(string) => Some(string.as_str().into()),
+    Value::Array(items) => Some(toml_edit::Value::Array(
+      items.iter().filter_map(to_toml_value).collect(),
+    )),
+    Value::Object(map) => Some(toml_edit::Value::InlineTable(
+      map
+        .iter()
+        .filter_map(|(key, value)| Some((key.as_str(), to_toml_value(value)?)))
+        .collect(),
+    )),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  fn serialize(format: ConfigFormat, content: &str, value: &Value) -> String {
+    let previous_value = format.parse(content).unwrap();
+    format
+      .serialize(value, Some((content, &previous_value)))
+      .unwrap()
+  }
+
+  #[test]
+  fn patches_json5_in_place() {
+    let content = "{\n  // Entry point.\n  htmlPath: 'index.html',\n  presets: [{ name: 'default', width: '100%' }],\n}\n";
+
+    let patched = serialize(
+      ConfigFormat::Json5,
+      content,
+      &json!({
+        "htmlPath": "index.html",
+        "presets": [{ "name": "default", "width": "50%" }],
+        "caching": { "defaultDuration": 60 },
+      }),
+    );
+
+    assert_eq!(
+      patched,
+      "{\n  \"caching\": {\n    \"defaultDuration\": 60\n  },\n  // Entry point.\n  htmlPath: 'index.html',\n  presets: [{ name: 'default', width: \"50%\" }],\n}\n"
+    );
+  }
+
+  #[test]
+  fn patches_toml_in_place() {
+    let content = "# Entry point.\nhtmlPath = \"index.html\" # relative\n\n[[presets]]\nname = \"default\"\nwidth = \"100%\"\n";
+
+    let patched = serialize(
+      ConfigFormat::Toml,
+      content,
+      &json!({
+        "htmlPath": "main.html",
+        "presets": [{ "name": "default", "width": "100%", "anchor": null }],
+      }),
+    );
+
+    assert_eq!(
+      patched,
+      "# Entry point.\nhtmlPath = \"main.html\" # relative\n\n[[presets]]\nname = \"default\"\nwidth = \"100%\"\n"
+    );
+  }
+
+  #[test]
+  fn parses_all_formats() {
+    let expected = json!({ "htmlPath": "index.html", "presets": [{ "name": "default" }] });
+
+    for (format, content) in [
+      (
+        ConfigFormat::Json,
+        r#"{ "htmlPath": "index.html", "presets": [{ "name": "default" }] }"#,
+      ),
+      (
+        ConfigFormat::Json5,
+        "{ htmlPath: 'index.html', presets: [{ name: 'default' }] }",
+      ),
+      (
+        ConfigFormat::Yaml,
+        "htmlPath: index.html\npresets:\n  - name: default\n",
+      ),
+      (
+        ConfigFormat::Toml,
+        "htmlPath = \"index.html\"\n\n[[presets]]\nname = \"default\"\n",
+      ),
+    ] {
+      assert_eq!(format.parse(content).unwrap(), expected);
+    }
+  }
+}
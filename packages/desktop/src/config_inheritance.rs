@@ -0,0 +1,460 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use serde_json::{Map, Value};
+
+use crate::{
+  common::PathExt, config_diagnostics::ConfigSchema, config_format::ConfigFormat, config_migration,
+};
+
+/// Arrays whose items are merged by a key field rather than replaced, as
+/// pairs of JSON pointers and key fields.
+const KEYED_ARRAYS: [(&str, &str); 2] = [("/presets", "name"), ("/caching/rules", "urlRegex")];
+
+/// Arrays whose items are combined with the base's items, as JSON
+/// pointers.
+const UNION_ARRAYS: [&str; 1] = ["/privileges/shellCommands"];
+
+/// Name of presets that don't specify one.
+const DEFAULT_PRESET_NAME: &str = "default";
+
+/// Merges the configs that a widget config `extends` into its value.
+///
+/// `extends` is either a path or a list of paths, relative to the
+/// config's directory. Bases are merged in order, and the config itself
+/// is merged on top. Objects are merged deeply, presets are merged by
+/// name, caching rules by URL regex, and shell privileges are combined.
+/// Other values of the config replace those of its bases.
+pub fn resolve(path: &Path, value: Value) -> anyhow::Result<Value> {
+  // Normalized like the base paths, so that cycles back to the config
+  // itself are detected right away.
+  let path = path.to_absolute().unwrap_or_else(|_| path.to_path_buf());
+
+  resolve_with_stack(&path, value, &mut vec![path.clone()])
+}
+
+/// Returns the paths of all configs that the config at the given path
+/// extends, including indirectly.
+///
+/// Bases that can't be read are skipped.
+pub fn base_paths(path: &Path) -> Vec<PathBuf> {
+  let mut base_paths = Vec::new();
+  let mut pending = vec![path.to_path_buf()];
+
+  while let Some(path) = pending.pop() {
+    let Ok(value) = read_value(&path) else {
+      continue;
+    };
+
+    let paths = extends_paths(&path, &value)
+      .unwrap_or_default()
+      .into_iter()
+      .filter_map(|base_path| base_path.to_absolute().ok());
+
+    for base_path in paths {
+      if !base_paths.contains(&base_path) {
+        base_paths.push(base_path.clone());
+        pending.push(base_path);
+      }
+    }
+  }
+
+  base_paths
+}
+
+/// Computes the contents to write back to a config file, after its
+/// resolved config changed from `old` to `new`.
+///
+/// Starts from the file's `existing` contents, and only sets the values
+/// that changed. This way, values inherited from its bases aren't copied
+/// into the file, and values it overrides stay overridden.
+pub fn overrides(old: &Value, new: &Value, existing: &Value) -> Value {
+  diff(Some(old), new, Some(&migrate(existing.clone())), "")
+    .unwrap_or_else(|| Value::Object(Map::new()))
+}
+
+fn resolve_with_stack(
+  path: &Path,
+  value: Value,
+  stack: &mut Vec<PathBuf>,
+) -> anyhow::Result<Value> {
+  let value = migrate(value);
+  let mut merged_bases = None;
+
+  for base_path in extends_paths(path, &value)? {
+    let base_path = base_path
+      .to_absolute()
+      .with_context(|| format!("Base config not found: {}", base_path.display()))?;
+
+    if stack.contains(&base_path) {
+      bail!(
+        "Config extends itself: {} -> {}",
+        stack
+          .iter()
+          .map(|path| path.display().to_string())
+          .collect::<Vec<_>>()
+          .join(" -> "),
+        base_path.display()
+      );
+    }
+
+    let base_value = read_value(&base_path)?;
+
+    stack.push(base_path.clone());
+    let mut base_value = resolve_with_stack(&base_path, base_value, stack)?;
+    stack.pop();
+
+    // Only the config's own `extends` is kept in the result.
+    if let Value::Object(map) = &mut base_value {
+      map.remove("extends");
+    }
+
+    merged_bases = Some(match merged_bases {
+      Some(merged_bases) => merge(merged_bases, base_value, ""),
+      None => base_value,
+    });
+  }
+
+  Ok(match merged_bases {
+    Some(merged_bases) => merge(merged_bases, value, ""),
+    None => value,
+  })
+}
+
+/// Reads and parses a config file in any of the supported formats.
+fn read_value(path: &Path) -> anyhow::Result<Value> {
+  let content = fs::read_to_string(path)
+    .with_context(|| format!("Failed to read base config: {}", path.display()))?;
+
+  ConfigFormat::from_path(path)
+    .parse(&content)
+    .map_err(|err| anyhow::anyhow!("Invalid base config {}: {}", path.display(), err.message))
+}
+
+/// Returns the paths in the config's `extends` field, resolved relative
+/// to the config's directory.
+fn extends_paths(path: &Path, value: &Value) -> anyhow::Result<Vec<PathBuf>> {
+  let dir = path.parent().unwrap_or(Path::new(""));
+
+  let paths = match value.get("extends") {
+    None | Some(Value::Null) => Vec::new(),
+    Some(Value::String(base_path)) => vec![base_path.as_str()],
+    Some(Value::Array(base_paths)) => base_paths
+      .iter()
+      .map(|base_path| {
+        base_path
+          .as_str()
+          .context("`extends` must only contain paths.")
+      })
+      .collect::<anyhow::Result<_>>()?,
+    Some(_) => bail!("`extends` must be a path or a list of paths."),
+  };
+
+  Ok(
+    paths
+      .into_iter()
+      .map(|base_path| dir.join(base_path))
+      .collect(),
+  )
+}
+
+/// Upgrades a config that was written for an older version of Zebar,
+/// so that it's merged in the current format.
+fn migrate(value: Value) -> Value {
+  config_migration::migrate(ConfigSchema::Widget, value)
+}
+
+fn merge(base: Value, value: Value, pointer: &str) -> Value {
+  match (base, value) {
+    (Value::Object(mut base_map), Value::Object(map)) => {
+      for (key, value) in map {
+        let merged = match base_map.remove(&key) {
+          Some(base_value) => merge(base_value, value, &format!("{}/{}", pointer, key)),
+          None => value,
+        };
+
+        base_map.insert(key, merged);
+      }
+
+      Value::Object(base_map)
+    }
+    (Value::Array(mut base_items), Value::Array(items)) => match array_key(pointer) {
+      Some(key) => {
+        for item in items {
+          let index = base_items
+            .iter()
+            .position(|base_item| item_key(base_item, key) == item_key(&item, key));
+
+          match index {
+            Some(index) => {
+              let base_item = base_items.remove(index);
+              base_items.insert(index, merge(base_item, item, &format!("{}/-", pointer)));
+            }
+            None => base_items.push(item),
+          }
+        }
+
+        Value::Array(base_items)
+      }
+      None if UNION_ARRAYS.contains(&pointer) => {
+        for item in items {
+          if !base_items.contains(&item) {
+            base_items.push(item);
+          }
+        }
+
+        Value::Array(base_items)
+      }
+      None => Value::Array(items),
+    },
+    (_, value) => value,
+  }
+}
+
+/// Returns the value to write for `new`, given its previous value `old`
+/// and what's currently in the file (`existing`).
+///
+/// Returns `None` if nothing needs to be written.
+fn diff(
+  old: Option<&Value>,
+  new: &Value,
+  existing: Option<&Value>,
+  pointer: &str,
+) -> Option<Value> {
+  if old == Some(new) {
+    return existing.cloned();
+  }
+
+  // Whether the result was pieced together from the new and existing
+  // values, rather than copied from the new value.
+  let (result, is_merged) = match (old, new) {
+    (Some(Value::Object(old_map)), Value::Object(new_map)) => {
+      let mut result = Map::new();
+
+      for (key, new_value) in new_map {
+        let existing_value = existing.and_then(|existing| existing.get(key));
+        let pointer = format!("{}/{}", pointer, key);
+
+        if let Some(value) = diff(old_map.get(key), new_value, existing_value, &pointer) {
+          result.insert(key.clone(), value);
+        }
+      }
+
+      // Keep values that aren't part of the config (e.g. `extends`).
+      if let Some(Value::Object(existing_map)) = existing {
+        for (key, value) in existing_map {
+          if !new_map.contains_key(key) && !old_map.contains_key(key) {
+            result.insert(key.clone(), value.clone());
+          }
+        }
+      }
+
+      (Value::Object(result), true)
+    }
+    (Some(Value::Array(old_items)), Value::Array(new_items)) if array_key(pointer).is_some() => {
+      let key = array_key(pointer)?;
+      let existing_items = existing.and_then(Value::as_array);
+      let mut result = Vec::new();
+
+      for new_item in new_items {
+        let find_item = |items: &Vec<Value>| {
+          items
+            .iter()
+            .find(|item| item_key(item, key) == item_key(new_item, key))
+            .cloned()
+        };
+
+        let old_item = find_item(old_items);
+        let existing_item = existing_items.and_then(find_item);
+        let pointer = format!("{}/-", pointer);
+
+        if let Some(mut item) = diff(
+          old_item.as_ref(),
+          new_item,
+          existing_item.as_ref(),
+          &pointer,
+        ) {
+          // Partial items need their key to be merged with the base.
+          if let Value::Object(map) = &mut item {
+            map
+              .entry(key)
+              .or_insert_with(|| Value::String(item_key(new_item, key)));
+          }
+
+          result.push(item);
+        }
+      }
+
+      (Value::Array(result), true)
+    }
+    (Some(Value::Array(old_items)), Value::Array(new_items)) if UNION_ARRAYS.contains(&pointer) => {
+      let existing_items = existing.and_then(Value::as_array);
+
+      let items = new_items
+        .iter()
+        .filter(|item| {
+          !old_items.contains(item)
+            || existing_items.is_some_and(|existing_items| existing_items.contains(item))
+        })
+        .cloned()
+        .collect();
+
+      (Value::Array(items), true)
+    }
+    _ => (new.clone(), false),
+  };
+
+  // Empty values are only written if the file already had them.
+  let is_empty = match &result {
+    Value::Object(map) => map.is_empty(),
+    Value::Array(items) => is_merged && items.is_empty(),
+    Value::Null => old.is_none(),
+    _ => false,
+  };
+
+  match is_empty && existing.is_none() {
+    true => None,
+    false => Some(result),
+  }
+}
+
+fn array_key(pointer: &str) -> Option<&'static str> {
+  KEYED_ARRAYS
+    .iter()
+    .find(|(array_pointer, _)| *array_pointer == pointer)
+    .map(|(_, key)| *key)
+}
+
+/// Returns the key of an item within a keyed array.
+fn item_key(item: &Value, key: &str) -> String {
+  match item.get(key).and_then(Value::as_str) {
+    Some(value) => value.to_string(),
+    None if key == "name" => DEFAULT_PRESET_NAME.to_string(),
+    None => String::new(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+  use crate::config_migration::CONFIG_VERSION;
+
+  #[test]
+  fn merges_configs() {
+    let base = json!({
+      "htmlPath": "index.html",
+      "presets": [{ "name": "default", "width": "100%", "height": "40px" }],
+      "privileges": { "shellCommands": [{ "program": "a", "argsRegex": ".*" }] },
+    });
+
+    let value = json!({
+      "presets": [{ "width": "50%" }, { "name": "small", "width": "10%" }],
+      "privileges": { "shellCommands": [{ "program": "b", "argsRegex": ".*" }] },
+    });
+
+    assert_eq!(
+      merge(base, value, ""),
+      json!({
+        "htmlPath": "index.html",
+        "presets": [
+          { "name": "default", "width": "50%", "height": "40px" },
+          { "name": "small", "width": "10%" },
+        ],
+        "privileges": {
+          "shellCommands": [
+            { "program": "a", "argsRegex": ".*" },
+            { "program": "b", "argsRegex": ".*" },
+          ],
+        },
+      })
+    );
+  }
+
+  /// Resolves the config at the given path, and returns the error.
+  fn resolve_err(path: &Path) -> String {
+    let value = read_value(path).unwrap();
+    resolve(path, value).unwrap_err().to_string()
+  }
+
+  #[test]
+  fn rejects_cyclic_extends() {
+    let dir = std::env::temp_dir().join(format!("zebar-extends-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(dir.join("sub")).unwrap();
+
+    fs::write(
+      dir.join("self.zebar.json"),
+      r#"{ "extends": "./self.zebar.json" }"#,
+    )
+    .unwrap();
+    fs::write(dir.join("a.zebar.json"), r#"{ "extends": "b.zebar.json" }"#).unwrap();
+    fs::write(
+      dir.join("b.zebar.json"),
+      r#"{ "extends": "sub/../a.zebar.json" }"#,
+    )
+    .unwrap();
+
+    let abs_path = |name: &str| dir.join(name).to_absolute().unwrap().display().to_string();
+
+    // Paths are compared once normalized.
+    assert_eq!(
+      resolve_err(&dir.join("sub/../self.zebar.json")),
+      format!(
+        "Config extends itself: {} -> {}",
+        abs_path("self.zebar.json"),
+        abs_path("self.zebar.json")
+      )
+    );
+
+    assert_eq!(
+      resolve_err(&dir.join("a.zebar.json")),
+      format!(
+        "Config extends itself: {} -> {} -> {}",
+        abs_path("a.zebar.json"),
+        abs_path("b.zebar.json"),
+        abs_path("a.zebar.json")
+      )
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn writes_back_only_overrides() {
+    let old = json!({
+      "htmlPath": "index.html",
+      "focused": false,
+      "presets": [
+        { "name": "default", "width": "50%", "height": "40px" },
+        { "name": "small", "width": "10%", "height": "40px" },
+      ],
+    });
+
+    let mut new = old.clone();
+    new["focused"] = json!(true);
+    new["presets"][1]["height"] = json!("20px");
+
+    // Files from before versioning are upgraded when written back.
+    let existing = json!({
+      "extends": "../base/bar.zebar.json",
+      "presets": [{ "name": "default", "width": "50%" }],
+    });
+
+    assert_eq!(
+      overrides(&old, &new, &existing),
+      json!({
+        "extends": "../base/bar.zebar.json",
+        "version": CONFIG_VERSION,
+        "focused": true,
+        "presets": [
+          { "name": "default", "width": "50%" },
+          { "name": "small", "height": "20px" },
+        ],
+      })
+    );
+  }
+}
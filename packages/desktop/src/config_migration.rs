@@ -0,0 +1,429 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde_json::{Map, Value};
+
+use crate::{
+  common::PathExt,
+  config_backups::ConfigBackups,
+  config_diagnostics::{ConfigDiagnostics, ConfigSchema},
+  config_format::ConfigFormat,
+  pack_manager,
+  widget_discovery::WidgetDiscovery,
+  widget_pack::ZEBAR_VERSION,
+};
+
+/// Current version of the settings and widget config formats. Files
+/// without a `version` field are treated as version 1.
+///
+/// When bumping this, add a migration step for each format and update the
+/// maximum `version` in the JSON schemas.
+pub const CONFIG_VERSION: u64 = 2;
+
+/// Upgrades a config from the version before it.
+type Migration = fn(&mut Map<String, Value>);
+
+/// Migration steps of settings, where the step at index `i` upgrades
+/// from version `i + 1`.
+const SETTINGS_MIGRATIONS: [Migration; 1] = [startup_configs_to_objects];
+
+/// Migration steps of widget configs, where the step at index `i`
+/// upgrades from version `i + 1`.
+const WIDGET_MIGRATIONS: [Migration; 1] = [default_placements_to_presets];
+
+/// A config file that needs to be upgraded to the current version.
+#[derive(Debug)]
+pub struct FileMigration {
+  pub path: PathBuf,
+  pub from_version: u64,
+  pub to_version: u64,
+  previous_content: String,
+  content: String,
+}
+
+impl FileMigration {
+  /// Line diff between the file's current and upgraded contents.
+  pub fn diff(&self) -> String {
+    line_diff(&self.path, &self.previous_content, &self.content)
+  }
+}
+
+/// Upgrades a parsed config to the current version, step by step.
+///
+/// Configs with a newer version than supported are returned as is, and
+/// are then caught by the schema's maximum `version`.
+pub fn migrate(schema: ConfigSchema, value: Value) -> Value {
+  let migrations: &[Migration] = match schema {
+    ConfigSchema::Settings => &SETTINGS_MIGRATIONS,
+    ConfigSchema::Widget => &WIDGET_MIGRATIONS,
+    ConfigSchema::Pack => return value,
+  };
+
+  let Value::Object(mut map) = value else {
+    return value;
+  };
+
+  let version = config_version(&map);
+
+  if version >= CONFIG_VERSION {
+    return Value::Object(map);
+  }
+
+  for migration in migrations.iter().skip(version.saturating_sub(1) as usize) {
+    migration(&mut map);
+  }
+
+  // Point the config at the schema of the running version.
+  if map.contains_key("$schema") {
+    map.insert("$schema".into(), schema_url(schema).into());
+  }
+
+  map.insert("version".into(), CONFIG_VERSION.into());
+
+  Value::Object(map)
+}
+
+/// URL of the JSON schema for the running version of Zebar.
+pub fn schema_url(schema: ConfigSchema) -> String {
+  let file_name = match schema {
+    ConfigSchema::Settings => "settings-schema.json",
+    ConfigSchema::Widget => "widget-schema.json",
+    ConfigSchema::Pack => "pack-schema.json",
+  };
+
+  // Development builds point at the latest schema.
+  let git_ref = match ZEBAR_VERSION {
+    "0.0.0" => "main".to_string(),
+    version => format!("v{}", version),
+  };
+
+  format!(
+    "https://github.com/glzr-io/zebar/raw/{}/resources/{}",
+    git_ref, file_name
+  )
+}
+
+/// Finds the settings and widget configs within the config directory
+/// that are on an older version.
+///
+/// Configs are upgraded in memory whenever they're read, so only files
+/// with changes beyond their `version` and `$schema` are included.
+/// Files that fail to parse are skipped, since they're reported when
+/// read anyway, as are configs of packs installed via `zebar pack
+/// install`, which would otherwise count as locally modified.
+pub fn pending_migrations(config_dir: &Path) -> anyhow::Result<Vec<FileMigration>> {
+  let config_dir = config_dir
+    .to_absolute()
+    .with_context(|| format!("Config directory not found: {}", config_dir.display()))?;
+
+  let settings_path = config_dir.join("settings.json");
+
+  let discovery = fs::read_to_string(&settings_path)
+    .ok()
+    .and_then(|content| ConfigFormat::Json.parse(&content).ok())
+    .and_then(|settings| serde_json::from_value(settings.get("widgetDiscovery")?.clone()).ok())
+    .unwrap_or_else(WidgetDiscovery::default);
+
+  let discovered = discovery.discover(&config_dir, &ConfigDiagnostics::new())?;
+
+  let managed_pack_dirs = discovered
+    .packs
+    .iter()
+    .map(|pack| &pack.path)
+    .filter(|pack_dir| pack_manager::is_managed(pack_dir))
+    .collect::<Vec<_>>();
+
+  let widget_paths = discovered.config_paths.into_iter().filter(|path| {
+    !managed_pack_dirs
+      .iter()
+      .any(|pack_dir| path.starts_with(pack_dir))
+  });
+
+  let paths = std::iter::once((settings_path, ConfigSchema::Settings))
+    .chain(widget_paths.map(|path| (path, ConfigSchema::Widget)));
+
+  let mut migrations = Vec::new();
+
+  for (path, schema) in paths {
+    if let Some(migration) = pending_migration(&path, schema)? {
+      migrations.push(migration);
+    }
+  }
+
+  Ok(migrations)
+}
+
+/// Writes the upgraded contents of the given files. The previous
+/// versions are kept as backups, so that they can be undone.
+pub fn apply(config_dir: &Path, migrations: &[FileMigration]) -> anyhow::Result<()> {
+  let backups = ConfigBackups::new(&config_dir.to_absolute()?);
+
+  for migration in migrations {
+    backups.write(&migration.path, &migration.content)?;
+  }
+
+  Ok(())
+}
+
+/// Output of `zebar migrate`. With `--dry-run`, the diff of each file is
+/// output instead of the list of migrated files.
+pub fn run_command(config_dir: Option<PathBuf>, dry_run: bool) -> anyhow::Result<String> {
+  let config_dir = match config_dir {
+    Some(dir) => dir,
+    None => crate::config::Config::default_dir()?,
+  };
+
+  let migrations = pending_migrations(&config_dir)?;
+
+  if migrations.is_empty() {
+    return Ok("All configs are up to date.\n".into());
+  }
+
+  if dry_run {
+    return Ok(migrations.iter().map(FileMigration::diff).collect());
+  }
+
+  apply(&config_dir, &migrations)?;
+
+  Ok(
+    migrations
+      .iter()
+      .map(|migration| {
+        format!(
+          "Migrated {} from version {} to {}.\n",
+          migration.path.display(),
+          migration.from_version,
+          migration.to_version
+        )
+      })
+      .collect(),
+  )
+}
+
+fn pending_migration(path: &Path, schema: ConfigSchema) -> anyhow::Result<Option<FileMigration>> {
+  let Ok(previous_content) = fs::read_to_string(path) else {
+    return Ok(None);
+  };
+
+  let format = ConfigFormat::from_path(path);
+
+  let Ok(previous_value) = format.parse(&previous_content) else {
+    return Ok(None);
+  };
+
+  let from_version = previous_value
+    .as_object()
+    .map(config_version)
+    .unwrap_or(CONFIG_VERSION);
+
+  if from_version >= CONFIG_VERSION {
+    return Ok(None);
+  }
+
+  let value = migrate(schema, previous_value.clone());
+
+  if without_version(&value) == without_version(&previous_value) {
+    return Ok(None);
+  }
+
+  let content = format.serialize(&value, Some((&previous_content, &previous_value)))?;
+
+  Ok(Some(FileMigration {
+    path: path.to_path_buf(),
+    from_version,
+    to_version: CONFIG_VERSION,
+    previous_content,
+    content,
+  }))
+}
+
+fn config_version(map: &Map<String, Value>) -> u64 {
+  map.get("version").and_then(Value::as_u64).unwrap_or(1)
+}
+
+/// Removes the fields that every migration changes, to compare configs
+/// by their contents.
+fn without_version(value: &Value) -> Value {
+  let mut value = value.clone();
+
+  if let Value::Object(map) = &mut value {
+    map.remove("version");
+    map.remove("$schema");
+  }
+
+  value
+}
+
+/// Settings v1 -> v2: startup configs given as a path (v2.3.0 and
+/// earlier) are converted to objects with the `default` preset.
+fn startup_configs_to_objects(settings: &mut Map<String, Value>) {
+  let to_objects = |startup_configs: Option<&mut Value>| {
+    for startup_config in startup_configs
+      .and_then(Value::as_array_mut)
+      .into_iter()
+      .flatten()
+    {
+      if let Value::String(path) = startup_config {
+        *startup_config = serde_json::json!({
+          "path": path,
+          "preset": "default",
+        });
+      }
+    }
+  };
+
+  to_objects(settings.get_mut("startupConfigs"));
+
+  if let Some(Value::Array(profiles)) = settings.get_mut("profiles") {
+    for profile in profiles {
+      to_objects(profile.get_mut("startupConfigs"));
+    }
+  }
+}
+
+/// Widget config v1 -> v2: `defaultPlacements` (v2.3.0 and earlier) is
+/// renamed to `presets`.
+fn default_placements_to_presets(config: &mut Map<String, Value>) {
+  if let Some(presets) = config.remove("defaultPlacements") {
+    config.entry("presets").or_insert(presets);
+  }
+}
+
+/// Formats a unified diff of the changed lines, with a few lines of
+/// context around each change.
+fn line_diff(path: &Path, old: &str, new: &str) -> String {
+  const CONTEXT_LINES: usize = 2;
+
+  let old_lines = old.lines().collect::<Vec<_>>();
+  let new_lines = new.lines().collect::<Vec<_>>();
+
+  // Longest common subsequence lengths of the remaining lines.
+  let mut lengths = vec![vec![0; new_lines.len() + 1]; old_lines.len() + 1];
+
+  for i in (0..old_lines.len()).rev() {
+    for j in (0..new_lines.len()).rev() {
+      lengths[i][j] = match old_lines[i] == new_lines[j] {
+        true => lengths[i + 1][j + 1] + 1,
+        false => lengths[i + 1][j].max(lengths[i][j + 1]),
+      };
+    }
+  }
+
+  // Edits as pairs of a prefix (' ', '-' or '+') and the line.
+  let mut edits = Vec::new();
+  let (mut i, mut j) = (0, 0);
+
+  while i < old_lines.len() || j < new_lines.len() {
+    if i < old_lines.len() && j < new_lines.len() && old_lines[i] == new_lines[j] {
+      edits.push((' ', old_lines[i]));
+      (i, j) = (i + 1, j + 1);
+    } else if i < old_lines.len()
+      && (j == new_lines.len() || lengths[i + 1][j] >= lengths[i][j + 1])
+    {
+      edits.push(('-', old_lines[i]));
+      i += 1;
+    } else {
+      edits.push(('+', new_lines[j]));
+      j += 1;
+    }
+  }
+
+  let is_near_change = |index: usize| {
+    edits[index.saturating_sub(CONTEXT_LINES)..(index + CONTEXT_LINES + 1).min(edits.len())]
+      .iter()
+      .any(|(prefix, _)| *prefix != ' ')
+  };
+
+  let mut diff = format!("--- {0}\n+++ {0}\n", path.display());
+  let mut is_skipping = false;
+
+  for (index, (prefix, line)) in edits.iter().enumerate() {
+    if !is_near_change(index) {
+      is_skipping = true;
+      continue;
+    }
+
+    if is_skipping || index == 0 {
+      diff.push_str("@@\n");
+      is_skipping = false;
+    }
+
+    diff.push_str(&format!("{}{}\n", prefix, line));
+  }
+
+  diff
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn migrates_configs_step_by_step() {
+    let settings = migrate(
+      ConfigSchema::Settings,
+      json!({ "startupConfigs": ["bar/bar.zebar.json"] }),
+    );
+
+    assert_eq!(
+      settings,
+      json!({
+        "startupConfigs": [{ "path": "bar/bar.zebar.json", "preset": "default" }],
+        "version": CONFIG_VERSION,
+      })
+    );
+
+    let widget = migrate(
+      ConfigSchema::Widget,
+      json!({ "defaultPlacements": [{ "name": "default" }] }),
+    );
+
+    assert_eq!(
+      widget,
+      json!({ "presets": [{ "name": "default" }], "version": CONFIG_VERSION })
+    );
+
+    // Configs on the current or a newer version are left untouched.
+    let newer = json!({ "defaultPlacements": [], "version": CONFIG_VERSION + 1 });
+    assert_eq!(migrate(ConfigSchema::Widget, newer.clone()), newer);
+  }
+
+  #[test]
+  fn skips_version_only_changes() {
+    let dir = std::env::temp_dir().join(format!("zebar-migration-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("settings.json");
+    fs::write(&path, r#"{ "startupConfigs": [] }"#).unwrap();
+    assert!(pending_migration(&path, ConfigSchema::Settings)
+      .unwrap()
+      .is_none());
+
+    fs::write(&path, r#"{ "startupConfigs": ["bar/bar.zebar.json"] }"#).unwrap();
+    assert!(pending_migration(&path, ConfigSchema::Settings)
+      .unwrap()
+      .is_some());
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn schemas_accept_current_version() {
+    for schema in [
+      include_str!("../../../resources/settings-schema.json"),
+      include_str!("../../../resources/widget-schema.json"),
+    ] {
+      let schema = serde_json::from_str::<Value>(schema).unwrap();
+
+      assert_eq!(
+        schema["properties"]["version"]["maximum"],
+        json!(CONFIG_VERSION)
+      );
+    }
+  }
+}
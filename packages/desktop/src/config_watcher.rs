@@ -0,0 +1,171 @@
+use std::{
+  path::{Path, PathBuf},
+  sync::Arc,
+  time::Duration,
+};
+
+use notify_debouncer_mini::{new_debouncer, notify::RecursiveMode, DebounceEventResult};
+use tokio::{sync::mpsc, task};
+use tracing::{error, info};
+
+use crate::{
+  config::Config, config_format::ConfigFormat, widget_discovery::is_ignored_dir,
+  widget_factory::WidgetFactory, widget_pack::PACK_MANIFEST_FILE, window_state::WINDOW_STATE_FILE,
+};
+
+/// How long to wait for further changes before reloading. Editors often
+/// save a file in several steps (e.g. write to a temp file and rename).
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(250);
+
+/// Watches the config directory and reloads whatever changed on disk.
+///
+/// Changed widget configs are broadcast via `widget_configs_change_tx`,
+/// which relaunches their widgets. This includes configs that extend a
+/// changed file (via `extends`) and configs of a pack whose manifest
+/// changed. Changes to other files within a widget's directory (e.g.
+/// HTML, CSS, JS) reload the widget in place.
+pub struct ConfigWatcher {
+  config: Arc<Config>,
+  widget_factory: Arc<WidgetFactory>,
+}
+
+impl ConfigWatcher {
+  /// Creates a new `ConfigWatcher` instance.
+  pub fn new(config: Arc<Config>, widget_factory: Arc<WidgetFactory>) -> Self {
+    Self {
+      config,
+      widget_factory,
+    }
+  }
+
+  /// Starts watching the config directory in a background task.
+  pub fn start(self) -> anyhow::Result<()> {
+    let (change_tx, mut change_rx) = mpsc::unbounded_channel::<Vec<PathBuf>>();
+
+    let mut debouncer = new_debouncer(DEBOUNCE_DELAY, move |res: DebounceEventResult| match res {
+      Ok(events) => {
+        let _ = change_tx.send(events.into_iter().map(|event| event.path).collect());
+      }
+      Err(err) => error!("Error watching config directory: {:?}", err),
+    })?;
+
+    debouncer
+      .watcher()
+      .watch(&self.config.config_dir, RecursiveMode::Recursive)?;
+
+    info!(
+      "Watching config directory {} for changes.",
+      self.config.config_dir.display()
+    );
+
+    task::spawn(async move {
+      // The watcher stops when dropped, so keep it alive alongside the
+      // receiver.
+      let _debouncer = debouncer;
+
+      while let Some(changed_paths) = change_rx.recv().await {
+        if let Err(err) = self.handle_changes(changed_paths).await {
+          error!("Failed to reload changed config files: {:?}", err);
+        }
+      }
+    });
+
+    Ok(())
+  }
+
+  async fn handle_changes(&self, changed_paths: Vec<PathBuf>) -> anyhow::Result<()> {
+    let changed_paths = changed_paths
+      .into_iter()
+      .filter(|path| !self.is_written_by_zebar(path))
+      .collect::<Vec<_>>();
+
+    if changed_paths.is_empty() {
+      return Ok(());
+    }
+
+    let settings_path = self.config.config_dir.join("settings.json");
+
+    if changed_paths.contains(&settings_path) {
+      self.config.reload_settings().await?;
+    }
+
+    // Directories are also reported when their contents change.
+    let (mut config_paths, asset_paths): (Vec<_>, Vec<_>) = changed_paths
+      .into_iter()
+      .filter(|path| *path != settings_path && !path.is_dir())
+      .partition(|path| ConfigFormat::is_widget_path(path));
+
+    let mut related_paths = self
+      .config
+      .widget_configs_extending(&[config_paths.clone(), asset_paths.clone()].concat())
+      .await?;
+
+    // A changed pack manifest can add or remove the pack's configs.
+    for manifest_path in asset_paths
+      .iter()
+      .filter(|path| path.ends_with(PACK_MANIFEST_FILE))
+    {
+      if let Some(pack_dir) = manifest_path.parent() {
+        related_paths.extend(self.config.widget_config_paths_within(pack_dir).await?);
+      }
+    }
+
+    // Configs that extend a changed file are re-read as well.
+    for config_path in related_paths {
+      if !config_paths.contains(&config_path) {
+        config_paths.push(config_path);
+      }
+    }
+
+    // Skip searching the config directory if no configs were changed.
+    let changed_configs = match config_paths.is_empty() {
+      true => Default::default(),
+      false => self.config.reload_widget_configs(&config_paths).await?,
+    };
+
+    // Widgets with a changed config are relaunched, so there's no need to
+    // also reload their assets.
+    let asset_paths = asset_paths
+      .into_iter()
+      .filter(|path| {
+        !changed_configs.keys().any(|config_path| {
+          config_path
+            .parent()
+            .is_some_and(|widget_dir| path.starts_with(widget_dir))
+        })
+      })
+      .collect::<Vec<_>>();
+
+    if !asset_paths.is_empty() {
+      self
+        .widget_factory
+        .reload_by_asset_paths(&asset_paths)
+        .await?;
+    }
+
+    Ok(())
+  }
+
+  /// Whether the path is one that Zebar writes to itself, i.e. the saved
+  /// window state, config backups, or webview data directories.
+  fn is_written_by_zebar(&self, path: &Path) -> bool {
+    let Ok(relative_path) = path.strip_prefix(&self.config.config_dir) else {
+      return false;
+    };
+
+    // The state file is written atomically via a temporary file.
+    let is_window_state = [
+      WINDOW_STATE_FILE.to_string(),
+      format!(".{}.tmp", WINDOW_STATE_FILE),
+    ]
+    .iter()
+    .any(|file| relative_path == Path::new(file));
+
+    is_window_state
+      || relative_path.parent().is_some_and(|dir| {
+        dir
+          .iter()
+          .any(|name| is_ignored_dir(&name.to_string_lossy()))
+      })
+  }
+}
@@ -0,0 +1,132 @@
+use std::{fs, net::TcpListener, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::{
+  asset_server::ASSET_SERVER_PORT, config::Config, config_diagnostics::ConfigDiagnostic,
+};
+
+/// Output of `zebar validate`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateReport {
+  pub valid: bool,
+  pub diagnostics: Vec<ConfigDiagnostic>,
+}
+
+/// Output of `zebar doctor`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorReport {
+  pub healthy: bool,
+  pub checks: Vec<DoctorCheck>,
+  pub diagnostics: Vec<ConfigDiagnostic>,
+}
+
+/// Result of a single environment check.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorCheck {
+  pub name: &'static str,
+  pub ok: bool,
+  pub message: String,
+}
+
+impl DoctorCheck {
+  fn new(name: &'static str, res: anyhow::Result<String>) -> Self {
+    match res {
+      Ok(message) => Self {
+        name,
+        ok: true,
+        message,
+      },
+      Err(err) => Self {
+        name,
+        ok: false,
+        message: format!("{:#}", err),
+      },
+    }
+  }
+}
+
+/// Validates the configs within the given (or default) config directory.
+pub fn validate(config_dir: Option<PathBuf>) -> anyhow::Result<ValidateReport> {
+  let config_dir = match config_dir {
+    Some(dir) => dir,
+    None => Config::default_dir()?,
+  };
+
+  let diagnostics = Config::validate_dir(&config_dir)?;
+
+  Ok(ValidateReport {
+    valid: diagnostics.is_empty(),
+    diagnostics,
+  })
+}
+
+/// Validates the configs, and checks that the environment is able to
+/// run Zebar.
+pub fn doctor(config_dir: Option<PathBuf>) -> anyhow::Result<DoctorReport> {
+  let config_dir = match config_dir {
+    Some(dir) => dir,
+    None => Config::default_dir()?,
+  };
+
+  let diagnostics = Config::validate_dir(&config_dir).unwrap_or_else(|err| {
+    vec![ConfigDiagnostic {
+      path: config_dir.clone(),
+      line: None,
+      column: None,
+      pointer: String::new(),
+      message: format!("{:#}", err),
+    }]
+  });
+
+  let checks = vec![
+    DoctorCheck::new("configDirectory", check_writable(&config_dir)),
+    // Webview data of widgets is stored in the default config directory,
+    // even if another config directory is used.
+    DoctorCheck::new(
+      "dataDirectory",
+      Config::default_dir().and_then(|dir| check_writable(&dir)),
+    ),
+    DoctorCheck::new("assetServerPort", check_asset_server_port()),
+    DoctorCheck::new(
+      "webview",
+      tauri::webview_version()
+        .map(|version| format!("Webview version {}.", version))
+        .map_err(|err| anyhow::anyhow!("No webview runtime found: {}", err)),
+    ),
+  ];
+
+  Ok(DoctorReport {
+    healthy: diagnostics.is_empty() && checks.iter().all(|check| check.ok),
+    checks,
+    diagnostics,
+  })
+}
+
+/// Checks that files can be created within the directory.
+fn check_writable(dir: &PathBuf) -> anyhow::Result<String> {
+  anyhow::ensure!(dir.is_dir(), "Directory not found: {}", dir.display());
+
+  let test_path = dir.join(".zebar-doctor");
+  fs::write(&test_path, "")
+    .map_err(|err| anyhow::anyhow!("Directory {} is not writable: {}", dir.display(), err))?;
+  let _ = fs::remove_file(&test_path);
+
+  Ok(format!("Directory {} is writable.", dir.display()))
+}
+
+/// Checks that the asset server is able to listen on its port.
+fn check_asset_server_port() -> anyhow::Result<String> {
+  TcpListener::bind(("127.0.0.1", ASSET_SERVER_PORT)).map_err(|err| {
+    anyhow::anyhow!(
+      "Port {} is unavailable ({}). This is expected if Zebar is already running.",
+      ASSET_SERVER_PORT,
+      err
+    )
+  })?;
+
+  Ok(format!("Port {} is available.", ASSET_SERVER_PORT))
+}
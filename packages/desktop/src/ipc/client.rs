@@ -0,0 +1,141 @@
+use std::env;
+
+use anyhow::Context;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use super::{IpcRequest, IpcResponse};
+
+/// Runs the given CLI arguments in an already running instance of Zebar.
+///
+/// Returns `None` if no instance is listening for commands.
+pub async fn send_to_running_instance(args: Vec<String>) -> anyhow::Result<Option<IpcResponse>> {
+  let request = IpcRequest {
+    args,
+    cwd: env::current_dir().ok(),
+  };
+
+  #[cfg(unix)]
+  {
+    let Ok(stream) = tokio::net::UnixStream::connect(super::socket_path()).await else {
+      return Ok(None);
+    };
+
+    send_request(stream, &request).await.map(Some)
+  }
+
+  #[cfg(windows)]
+  {
+    use std::time::Duration;
+
+    use tokio::net::windows::named_pipe::ClientOptions;
+    use windows::Win32::Foundation::ERROR_PIPE_BUSY;
+
+    let stream = loop {
+      match ClientOptions::new().open(super::PIPE_NAME) {
+        Ok(stream) => break stream,
+        // All pipe instances are in use, so wait for the server to create
+        // another one.
+        Err(err) if err.raw_os_error() == Some(ERROR_PIPE_BUSY.0 as i32) => {
+          tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        Err(_) => return Ok(None),
+      }
+    };
+
+    send_request(stream, &request).await.map(Some)
+  }
+}
+
+async fn send_request<S>(stream: S, request: &IpcRequest) -> anyhow::Result<IpcResponse>
+where
+  S: AsyncRead + AsyncWrite,
+{
+  let (reader, mut writer) = tokio::io::split(stream);
+
+  writer
+    .write_all((serde_json::to_string(request)? + "\n").as_bytes())
+    .await?;
+
+  let mut line = String::new();
+  BufReader::new(reader).read_line(&mut line).await?;
+
+  serde_json::from_str(&line).context("Invalid reply from running instance of Zebar.")
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use serde_json::json;
+
+  use super::*;
+
+  /// Replies to a single request with the given response, and returns
+  /// the request.
+  async fn reply<S>(stream: S, response: IpcResponse) -> IpcRequest
+  where
+    S: AsyncRead + AsyncWrite,
+  {
+    let (reader, mut writer) = tokio::io::split(stream);
+
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await.unwrap();
+
+    let response = serde_json::to_string(&response).unwrap() + "\n";
+    writer.write_all(response.as_bytes()).await.unwrap();
+
+    serde_json::from_str(&line).unwrap()
+  }
+
+  #[tokio::test]
+  async fn sends_requests_and_reads_replies() {
+    let (client, server) = tokio::io::duplex(1024);
+
+    let request = IpcRequest {
+      args: vec!["zebar".to_string(), "list-widgets".to_string()],
+      cwd: Some(PathBuf::from("/home/user")),
+    };
+
+    let server = tokio::spawn(reply(server, IpcResponse::from(Ok(json!([{ "id": "a" }])))));
+    let response = send_request(client, &request).await.unwrap();
+    let received = server.await.unwrap();
+
+    assert_eq!(received.args, request.args);
+    assert_eq!(received.cwd, request.cwd);
+    assert_eq!(
+      response.into_output().unwrap(),
+      "[\n  {\n    \"id\": \"a\"\n  }\n]\n"
+    );
+  }
+
+  #[tokio::test]
+  async fn returns_errors_of_replies() {
+    let (client, server) = tokio::io::duplex(1024);
+
+    let request = IpcRequest {
+      args: vec!["zebar".to_string(), "reload".to_string()],
+      cwd: None,
+    };
+
+    let server = tokio::spawn(reply(
+      server,
+      IpcResponse::from(Err(anyhow::anyhow!("Failed to reload."))),
+    ));
+
+    let response = send_request(client, &request).await.unwrap();
+    server.await.unwrap();
+
+    assert_eq!(
+      response.into_output().unwrap_err().to_string(),
+      "Failed to reload."
+    );
+  }
+
+  #[test]
+  fn reads_requests_without_cwd() {
+    let request = serde_json::from_str::<IpcRequest>(r#"{"args":["zebar"]}"#).unwrap();
+
+    assert_eq!(request.args, vec!["zebar"]);
+    assert_eq!(request.cwd, None);
+  }
+}
@@ -0,0 +1,93 @@
+mod client;
+mod server;
+
+#[cfg(unix)]
+use std::env;
+use std::path::PathBuf;
+
+pub use client::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+pub use server::*;
+
+/// Name of the named pipe used for IPC on Windows.
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\zebar";
+
+/// Path to the socket used for IPC on Linux and MacOS.
+///
+/// Uses the user's runtime directory if available, and otherwise the
+/// temp directory. The temp directory might be shared between users, so
+/// the socket is named after the user ID there.
+#[cfg(unix)]
+fn socket_path() -> PathBuf {
+  match dirs::runtime_dir() {
+    Some(dir) => dir.join("zebar.sock"),
+    None => env::temp_dir().join(format!("zebar-{}.sock", current_uid())),
+  }
+}
+
+/// ID of the user running this process.
+#[cfg(unix)]
+fn current_uid() -> u32 {
+  unsafe { libc::getuid() }
+}
+
+/// Request sent to a running instance of Zebar.
+///
+/// Sent as a single line of JSON.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IpcRequest {
+  /// CLI arguments to run, including the program name.
+  pub args: Vec<String>,
+
+  /// Working directory of the client, which relative config paths in
+  /// the arguments are resolved against.
+  #[serde(default)]
+  pub cwd: Option<PathBuf>,
+}
+
+/// Reply from a running instance of Zebar.
+///
+/// Sent as a single line of JSON.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IpcResponse {
+  pub success: bool,
+
+  /// Output of the command. `null` for commands without output.
+  #[serde(default)]
+  pub data: Value,
+
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+impl IpcResponse {
+  /// Converts the reply into output for printing to the console.
+  pub fn into_output(self) -> anyhow::Result<String> {
+    match (self.success, self.data) {
+      (false, _) => Err(anyhow::anyhow!(self
+        .error
+        .unwrap_or_else(|| "Unknown error.".to_string()))),
+      (true, Value::Null) => Ok(String::new()),
+      (true, data) => Ok(serde_json::to_string_pretty(&data)? + "\n"),
+    }
+  }
+}
+
+impl From<anyhow::Result<Value>> for IpcResponse {
+  fn from(result: anyhow::Result<Value>) -> Self {
+    match result {
+      Ok(data) => Self {
+        success: true,
+        data,
+        error: None,
+      },
+      Err(err) => Self {
+        success: false,
+        data: Value::Null,
+        error: Some(err.to_string()),
+      },
+    }
+  }
+}
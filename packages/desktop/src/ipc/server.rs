@@ -0,0 +1,448 @@
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use anyhow::{bail, Context};
+use clap::Parser;
+use serde_json::{json, Value};
+use tokio::{
+  io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+  task,
+};
+use tracing::{error, info};
+
+use super::{IpcRequest, IpcResponse};
+use crate::{
+  cli::{Cli, CliCommand, ProfileArgs, QueryArgs},
+  common::PathExt,
+  config::{Config, WidgetPlacement},
+  message_bus::MessageBus,
+  monitor_state::MonitorState,
+  providers::ProviderManager,
+  widget_factory::{WidgetFactory, WidgetOpenOptions},
+};
+
+/// Runs CLI commands sent by other processes (e.g. `zebar list-widgets`)
+/// against this instance of Zebar.
+pub struct IpcServer {
+  config: Arc<Config>,
+  monitor_state: Arc<MonitorState>,
+  widget_factory: Arc<WidgetFactory>,
+  message_bus: Arc<MessageBus>,
+  provider_manager: Arc<ProviderManager>,
+}
+
+impl IpcServer {
+  /// Creates a new `IpcServer` instance.
+  pub fn new(
+    config: Arc<Config>,
+    monitor_state: Arc<MonitorState>,
+    widget_factory: Arc<WidgetFactory>,
+    message_bus: Arc<MessageBus>,
+    provider_manager: Arc<ProviderManager>,
+  ) -> Self {
+    Self {
+      config,
+      monitor_state,
+      widget_factory,
+      message_bus,
+      provider_manager,
+    }
+  }
+
+  /// Starts listening for commands on the IPC socket.
+  #[cfg(unix)]
+  pub fn start(self: &Arc<Self>) -> anyhow::Result<()> {
+    use tokio::net::UnixListener;
+
+    let socket_path = super::socket_path();
+
+    // A socket file is left behind if the previous instance didn't exit
+    // cleanly. This is only reached if no instance is listening on it.
+    if let Ok(metadata) = std::fs::symlink_metadata(&socket_path) {
+      use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+      if !metadata.file_type().is_socket() || metadata.uid() != super::current_uid() {
+        bail!(
+          "{} exists and isn't a socket of the current user.",
+          socket_path.display()
+        );
+      }
+
+      std::fs::remove_file(&socket_path)
+        .with_context(|| format!("Failed to remove stale socket {}.", socket_path.display()))?;
+    }
+
+    let listener = UnixListener::bind(&socket_path)
+      .with_context(|| format!("Failed to bind to socket {}.", socket_path.display()))?;
+
+    info!("Listening for commands at {}.", socket_path.display());
+
+    let server = self.clone();
+
+    task::spawn(async move {
+      loop {
+        match listener.accept().await {
+          Ok((stream, _)) => {
+            let server = server.clone();
+            task::spawn(async move { server.handle_connection(stream).await });
+          }
+          Err(err) => error!("Failed to accept IPC connection: {:?}", err),
+        }
+      }
+    });
+
+    Ok(())
+  }
+
+  /// Starts listening for commands on the IPC named pipe.
+  #[cfg(windows)]
+  pub fn start(self: &Arc<Self>) -> anyhow::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut pipe = ServerOptions::new()
+      .first_pipe_instance(true)
+      .create(super::PIPE_NAME)
+      .context("Failed to create named pipe.")?;
+
+    info!("Listening for commands at {}.", super::PIPE_NAME);
+
+    let server = self.clone();
+
+    task::spawn(async move {
+      loop {
+        let connect_res = pipe.connect().await;
+
+        // Create the next pipe instance before handling the connection,
+        // so that clients don't find the pipe missing in between.
+        let connected_pipe = pipe;
+        pipe = match ServerOptions::new().create(super::PIPE_NAME) {
+          Ok(pipe) => pipe,
+          Err(err) => {
+            error!("Failed to create named pipe: {:?}", err);
+            break;
+          }
+        };
+
+        match connect_res {
+          Ok(_) => {
+            let server = server.clone();
+            task::spawn(async move { server.handle_connection(connected_pipe).await });
+          }
+          Err(err) => error!("Failed to accept IPC connection: {:?}", err),
+        }
+      }
+    });
+
+    Ok(())
+  }
+
+  /// Reads a single request from the connection and writes the reply.
+  async fn handle_connection<S>(&self, stream: S)
+  where
+    S: AsyncRead + AsyncWrite,
+  {
+    let (reader, mut writer) = tokio::io::split(stream);
+
+    let mut line = String::new();
+    let response = match BufReader::new(reader).read_line(&mut line).await {
+      Ok(_) => IpcResponse::from(self.handle_request(&line).await),
+      Err(err) => IpcResponse::from(Err(err.into())),
+    };
+
+    let write_res = match serde_json::to_string(&response) {
+      Ok(response) => writer
+        .write_all((response + "\n").as_bytes())
+        .await
+        .map_err(anyhow::Error::from),
+      Err(err) => Err(err.into()),
+    };
+
+    if let Err(err) = write_res {
+      error!("Failed to reply to IPC request: {:?}", err);
+    }
+  }
+
+  async fn handle_request(&self, line: &str) -> anyhow::Result<Value> {
+    let request = serde_json::from_str::<IpcRequest>(line).context("Invalid IPC request.")?;
+
+    let cli = Cli::try_parse_from(request.args)?;
+    info!("Received command via IPC: {:?}", cli.command());
+
+    self
+      .handle_command(cli.command(), request.cwd.as_deref())
+      .await
+  }
+
+  /// Runs a CLI command against this instance.
+  ///
+  /// Relative config paths are resolved against `cwd`, the working
+  /// directory of the process that sent the command.
+  ///
+  /// Returns the command's output, or `null` if it has none.
+  pub async fn handle_command(
+    &self,
+    command: CliCommand,
+    cwd: Option<&Path>,
+  ) -> anyhow::Result<Value> {
+    match resolve_config_paths(command, &self.config.config_dir, cwd)? {
+      CliCommand::StartWidget(args) => {
+        self
+          .widget_factory
+          .start_widget(
+            &args.config_path,
+            &WidgetOpenOptions::Standalone(WidgetPlacement {
+              anchor: args.anchor,
+              offset_x: args.offset_x,
+              offset_y: args.offset_y,
+              width: args.width,
+              height: args.height,
+              monitor_selection: args.monitor_selection()?,
+              dock_to_edge: Default::default(),
+              hide_on_fullscreen: false,
+            }),
+          )
+          .await?
+      }
+      CliCommand::StartWidgetPreset(args) => {
+        self
+          .widget_factory
+          .start_widget(
+            &args.config_path,
+            &WidgetOpenOptions::Preset(args.preset_name),
+          )
+          .await?
+      }
+      CliCommand::Startup(_) => self.widget_factory.startup().await?,
+      CliCommand::Show(args) => {
+        self
+          .widget_factory
+          .show_by_preset(&args.config_path, &args.preset_name)
+          .await?
+      }
+      CliCommand::Hide(args) => {
+        self
+          .widget_factory
+          .hide_by_preset(&args.config_path, &args.preset_name)
+          .await?
+      }
+      CliCommand::Toggle(args) => {
+        self
+          .widget_factory
+          .toggle_by_preset(&args.config_path, &args.preset_name)
+          .await?
+      }
+      CliCommand::ResetWidget(args) => match args.widget_id {
+        Some(widget_id) => {
+          self
+            .widget_factory
+            .reset_window_states(&[widget_id])
+            .await?
+        }
+        None => self.widget_factory.reset_all_window_states().await?,
+      },
+      CliCommand::Publish(args) => {
+        self
+          .message_bus
+          .publish(&args.topic, args.payload, args.retain)
+          .await?
+      }
+      CliCommand::ListWidgets => return self.widget_list().await,
+      CliCommand::StopWidget(args) => self.widget_factory.stop_by_id(&args.widget_id)?,
+      CliCommand::StopPreset(args) => {
+        self
+          .widget_factory
+          .stop_by_preset(&args.config_path, &args.preset_name)
+          .await?
+      }
+      CliCommand::Reload => {
+        self.widget_factory.clear_cache();
+        self.config.reload().await?
+      }
+      CliCommand::Profile(ProfileArgs::List) => return self.profile_list().await,
+      CliCommand::Profile(ProfileArgs::Switch(args)) => {
+        self.widget_factory.switch_profile(&args.name).await?
+      }
+      CliCommand::Query(args) => return self.query(args).await,
+      CliCommand::Validate(_)
+      | CliCommand::Doctor(_)
+      | CliCommand::Pack(_)
+      | CliCommand::Migrate(_) => {
+        bail!("Command is run without a running instance of Zebar.")
+      }
+      // No-op if Zebar is already running.
+      CliCommand::Empty => {}
+    };
+
+    Ok(Value::Null)
+  }
+
+  /// Summary of open widgets, sorted by their config paths.
+  async fn widget_list(&self) -> anyhow::Result<Value> {
+    let mut widget_states = self
+      .widget_factory
+      .states()
+      .await
+      .into_values()
+      .collect::<Vec<_>>();
+
+    widget_states.sort_by(|a, b| (&a.config_path, &a.id).cmp(&(&b.config_path, &b.id)));
+
+    let widgets = widget_states
+      .iter()
+      .map(|state| {
+        json!({
+          "id": state.id,
+          "configPath": self.config.formatted_widget_path(&state.config_path),
+          "preset": match &state.open_options {
+            WidgetOpenOptions::Preset(name) => Some(name),
+            WidgetOpenOptions::Standalone(_) => None,
+          },
+          "monitor": state.monitor.name,
+          "visible": state.visible,
+        })
+      })
+      .collect();
+
+    Ok(Value::Array(widgets))
+  }
+
+  /// Summary of the profiles in the settings.
+  async fn profile_list(&self) -> anyhow::Result<Value> {
+    let active_profile = self.config.active_profile().await;
+
+    let profiles = self
+      .config
+      .profiles()
+      .await
+      .into_iter()
+      .map(|profile| {
+        json!({
+          "name": profile.name,
+          "active": Some(&profile.name) == active_profile.as_ref(),
+          "startupConfigs": profile.startup_configs,
+          "monitors": profile.monitors,
+        })
+      })
+      .collect();
+
+    Ok(Value::Array(profiles))
+  }
+
+  async fn query(&self, args: QueryArgs) -> anyhow::Result<Value> {
+    let output = match args {
+      QueryArgs::Monitors => serde_json::to_value(self.monitor_state.monitors().await)?,
+      QueryArgs::Widgets => serde_json::to_value(self.widget_factory.states().await)?,
+      QueryArgs::Configs => serde_json::to_value(
+        self
+          .config
+          .widget_configs()
+          .await
+          .into_iter()
+          .map(|(path, config)| (self.config.formatted_widget_path(&path), config))
+          .collect::<BTreeMap<_, _>>(),
+      )?,
+      QueryArgs::Providers => serde_json::to_value(self.provider_manager.summaries().await)?,
+    };
+
+    Ok(output)
+  }
+}
+
+/// Resolves the config paths of a command to absolute paths.
+///
+/// Relative paths are resolved against the given working directory if a
+/// file exists there, and otherwise against the config directory.
+fn resolve_config_paths(
+  mut command: CliCommand,
+  config_dir: &Path,
+  cwd: Option<&Path>,
+) -> anyhow::Result<CliCommand> {
+  let config_path = match &mut command {
+    CliCommand::StartWidget(args) => &mut args.config_path,
+    CliCommand::StartWidgetPreset(args) => &mut args.config_path,
+    CliCommand::Show(args)
+    | CliCommand::Hide(args)
+    | CliCommand::Toggle(args)
+    | CliCommand::StopPreset(args) => &mut args.config_path,
+    _ => return Ok(command),
+  };
+
+  let path = match cwd.map(|cwd| cwd.join(&config_path)) {
+    Some(path) if path.exists() => path,
+    _ => config_dir.join(&config_path),
+  };
+
+  *config_path = path
+    .to_absolute()
+    .with_context(|| format!("No config found at path '{}'.", config_path.display()))?;
+
+  Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{fs, path::PathBuf};
+
+  use super::*;
+  use crate::cli::WidgetPresetArgs;
+
+  fn stop_preset(config_path: &str) -> CliCommand {
+    CliCommand::StopPreset(WidgetPresetArgs {
+      config_path: PathBuf::from(config_path),
+      preset_name: "default".to_string(),
+    })
+  }
+
+  fn config_path(command: CliCommand) -> PathBuf {
+    match command {
+      CliCommand::StopPreset(args) => args.config_path,
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn resolves_config_paths_once() {
+    let dir = std::env::temp_dir().join(format!("zebar-ipc-{}", uuid::Uuid::new_v4()));
+
+    let config_dir = dir.join("config");
+    let cwd = dir.join("cwd");
+
+    fs::create_dir_all(config_dir.join("bar")).unwrap();
+    fs::create_dir_all(cwd.join("bar")).unwrap();
+    fs::write(config_dir.join("bar/a.zebar.json"), "{}").unwrap();
+    fs::write(config_dir.join("bar/b.zebar.json"), "{}").unwrap();
+    fs::write(cwd.join("bar/b.zebar.json"), "{}").unwrap();
+
+    let resolve = |path: &str, cwd: Option<&Path>| {
+      resolve_config_paths(stop_preset(path), &config_dir, cwd).map(config_path)
+    };
+
+    // Falls back to the config directory if not found in the working
+    // directory.
+    assert_eq!(
+      resolve("bar/a.zebar.json", Some(&cwd)).unwrap(),
+      config_dir.join("bar/a.zebar.json").to_absolute().unwrap()
+    );
+
+    assert_eq!(
+      resolve("bar/b.zebar.json", Some(&cwd)).unwrap(),
+      cwd.join("bar/b.zebar.json").to_absolute().unwrap()
+    );
+
+    assert_eq!(
+      resolve("bar/b.zebar.json", None).unwrap(),
+      config_dir.join("bar/b.zebar.json").to_absolute().unwrap()
+    );
+
+    // Already resolved paths stay the same.
+    let abs_path = resolve("bar/b.zebar.json", Some(&cwd)).unwrap();
+
+    assert_eq!(
+      resolve(abs_path.to_str().unwrap(), Some(&config_dir)).unwrap(),
+      abs_path
+    );
+
+    assert!(resolve("bar/missing.zebar.json", Some(&cwd)).is_err());
+
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
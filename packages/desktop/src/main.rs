@@ -6,20 +6,24 @@ use std::{env, sync::Arc};
 use clap::Parser;
 use tauri::{async_runtime::block_on, AppHandle, Emitter, Manager, RunEvent};
 use tokio::{sync::mpsc, task};
-use tracing::{error, info, level_filters::LevelFilter};
+use tracing::{error, info, level_filters::LevelFilter, warn};
 use tracing_subscriber::EnvFilter;
 
 #[cfg(target_os = "windows")]
 use crate::common::windows::WindowExtWindows;
 use crate::{
   asset_server::setup_asset_server,
-  cli::{Cli, CliCommand, MonitorType, QueryArgs},
-  config::{Config, MonitorSelection, WidgetPlacement},
+  cli::{Cli, CliCommand, QueryArgs},
+  config::Config,
+  config_watcher::ConfigWatcher,
+  ipc::IpcServer,
+  message_bus::MessageBus,
   monitor_state::MonitorState,
+  pack_manager::StagedPacks,
   providers::{ProviderEmission, ProviderManager},
   shell_state::ShellState,
   sys_tray::SysTray,
-  widget_factory::{WidgetFactory, WidgetOpenOptions},
+  widget_factory::WidgetFactory,
 };
 
 use menu_util::initialize_menu_window;
@@ -29,11 +33,24 @@ mod cli;
 mod commands;
 mod common;
 mod config;
+mod config_backups;
+mod config_diagnostics;
+mod config_format;
+mod config_inheritance;
+mod config_migration;
+mod config_watcher;
+mod doctor;
+mod ipc;
+mod message_bus;
 mod monitor_state;
+mod pack_manager;
 mod providers;
 mod shell_state;
 mod sys_tray;
+mod widget_discovery;
 mod widget_factory;
+mod widget_pack;
+mod window_state;
 
 #[macro_use]
 extern crate rocket;
@@ -53,8 +70,38 @@ async fn main() -> anyhow::Result<()> {
 
   tauri::async_runtime::set(tokio::runtime::Handle::current());
 
+  let cli = Cli::parse();
+
+  // Checks, pack and migrate commands are run headless, without
+  // starting Zebar.
+  match cli.command() {
+    CliCommand::Validate(args) => {
+      cli::print_report_and_exit(doctor::validate(args.config_dir), |report| report.valid)
+    }
+    CliCommand::Doctor(args) => {
+      cli::print_report_and_exit(doctor::doctor(args.config_dir), |report| report.healthy)
+    }
+    CliCommand::Pack(args) => cli::print_and_exit(pack_manager::run_command(args)),
+    CliCommand::Migrate(args) => cli::print_and_exit(config_migration::run_command(
+      args.config_dir,
+      args.dry_run,
+    )),
+    _ => {}
+  }
+
+  // Run the command in an already running instance of Zebar if there is
+  // one, and print its reply.
+  match ipc::send_to_running_instance(env::args().collect()).await {
+    Ok(Some(response)) => cli::print_and_exit(response.into_output()),
+    Ok(None) if cli.command().requires_running_instance() => {
+      cli::print_and_exit(Err(anyhow::anyhow!("No running instance of Zebar.")))
+    }
+    Ok(None) => {}
+    Err(err) => cli::print_and_exit(Err(err)),
+  }
+
   let app = tauri::Builder::default()
-    .setup(|app| {
+    .setup(move |app| {
       // Initialize the menu window
       let app_handle = app.handle();
       if let Err(err) = initialize_menu_window(&app_handle) {
@@ -63,8 +110,6 @@ async fn main() -> anyhow::Result<()> {
 
       task::block_in_place(|| {
         block_on(async move {
-          let cli = Cli::parse();
-
           match cli.command() {
             CliCommand::Query(args) => output_query(app, args),
             _ => {
@@ -87,11 +132,30 @@ async fn main() -> anyhow::Result<()> {
     })
     .invoke_handler(tauri::generate_handler![
       commands::widget_configs,
+      commands::widget_packs,
+      commands::list_packs,
+      commands::preview_pack,
+      commands::install_pack,
+      commands::remove_pack,
+      commands::export_pack,
+      commands::config_diagnostics,
       commands::widget_states,
       commands::start_widget,
       commands::start_preset,
       commands::stop_preset,
+      commands::show_widget,
+      commands::show_preset,
+      commands::hide_widget,
+      commands::hide_preset,
+      commands::toggle_widget,
+      commands::toggle_preset,
+      commands::reset_widget_window,
+      commands::publish,
+      commands::subscribe,
+      commands::unsubscribe,
       commands::update_widget_config,
+      commands::can_undo_config_change,
+      commands::undo_config_change,
       commands::listen_provider,
       commands::unlisten_provider,
       commands::call_provider_function,
@@ -135,6 +199,8 @@ fn output_query(app: &tauri::App, args: QueryArgs) -> anyhow::Result<()> {
       cli::print_and_exit(monitors.output_str());
       Ok(())
     }
+    // Other queries are only run by an already running instance.
+    _ => unreachable!(),
   }
 }
 
@@ -165,10 +231,13 @@ async fn start_app(app: &mut tauri::App, cli: Cli) -> anyhow::Result<()> {
   ));
   app.manage(widget_factory.clone());
 
-  // If this is not the first instance of the app, this will emit within
-  // the original instance and exit immediately. The CLI command is
-  // guaranteed to be one of the open commands here.
-  setup_single_instance(app, widget_factory.clone())?;
+  // Initialize `MessageBus` in Tauri state.
+  let message_bus = Arc::new(MessageBus::new(app.handle()));
+  app.manage(message_bus.clone());
+
+  // CLI commands are sent to an already running instance via IPC, but
+  // this guards against two instances being started at the same time.
+  setup_single_instance(app)?;
 
   setup_asset_server();
 
@@ -182,23 +251,58 @@ async fn start_app(app: &mut tauri::App, cli: Cli) -> anyhow::Result<()> {
     .allow_directory(&config.config_dir, true)?;
 
   app.manage(ShellState::new(app.handle(), widget_factory.clone()));
+  app.manage(StagedPacks::default());
   app.handle().plugin(tauri_plugin_dialog::init())?;
 
   // Initialize `ProviderManager` in Tauri state.
   let (manager, emit_rx) = ProviderManager::new(app.handle());
   app.manage(manager.clone());
 
+  // Listen for CLI commands from other processes.
+  let ipc_server = Arc::new(IpcServer::new(
+    config.clone(),
+    monitor_state.clone(),
+    widget_factory.clone(),
+    message_bus.clone(),
+    manager.clone(),
+  ));
+
+  if let Err(err) = ipc_server.start() {
+    error!("Failed to start IPC server: {:?}", err);
+  }
+
   // Open widgets based on CLI command.
-  open_widgets_by_cli_command(cli, widget_factory.clone()).await?;
+  let open_res = match cli.command() {
+    CliCommand::Empty => widget_factory.startup().await,
+    command => ipc_server
+      .handle_command(command, env::current_dir().ok().as_deref())
+      .await
+      .map(|_| ()),
+  };
+
+  if let Err(err) = open_res {
+    error!("Failed to open widgets: {:?}", err);
+  }
+
+  // Resetting widgets launches Zebar as usual if it wasn't running.
+  if let CliCommand::ResetWidget(_) = cli.command() {
+    widget_factory.startup().await?;
+  }
 
   // Add application icon to system tray.
   let tray = SysTray::new(app.handle(), config.clone(), widget_factory.clone()).await?;
 
+  // Hot reload settings, widget configs and widget assets on change.
+  if let Err(err) = ConfigWatcher::new(config.clone(), widget_factory.clone()).start() {
+    error!("Failed to watch config directory: {:?}", err);
+  }
+
   listen_events(
     app.handle(),
     config,
     monitor_state,
     widget_factory,
+    message_bus,
     tray,
     manager,
     emit_rx,
@@ -212,6 +316,7 @@ fn listen_events(
   config: Arc<Config>,
   monitor_state: Arc<MonitorState>,
   widget_factory: Arc<WidgetFactory>,
+  message_bus: Arc<MessageBus>,
   tray: SysTray,
   manager: Arc<ProviderManager>,
   mut emit_rx: mpsc::UnboundedReceiver<ProviderEmission>,
@@ -219,9 +324,11 @@ fn listen_events(
   let app_handle = app_handle.clone();
   let mut widget_open_rx = widget_factory.open_tx.subscribe();
   let mut widget_close_rx = widget_factory.close_tx.subscribe();
+  let mut widget_visibility_rx = widget_factory.visibility_change_tx.subscribe();
   let mut settings_change_rx = config.settings_change_tx.subscribe();
   let mut monitors_change_rx = monitor_state.change_tx.subscribe();
   let mut widget_configs_change_rx = config.widget_configs_change_tx.subscribe();
+  let mut diagnostics_change_rx = config.diagnostics.change_tx.subscribe();
 
   task::spawn(async move {
     loop {
@@ -239,9 +346,18 @@ fn listen_events(
           if let Err(e) = tray.refresh().await {
             error!("Error refreshing tray: {:?}", e);
           }
+          message_bus.unsubscribe_all(&widget_id).await;
           let _ = app_handle.emit("widget-closed", widget_id);
           Ok(())
         },
+        Ok(widget_state) = widget_visibility_rx.recv() => {
+          info!("Widget visibility changed.");
+          if let Err(e) = tray.refresh().await {
+            error!("Error refreshing tray: {:?}", e);
+          }
+          let _ = app_handle.emit("widget-visibility-changed", widget_state);
+          Ok(())
+        },
         Ok(_) = settings_change_rx.recv() => {
           info!("Settings changed.");
           if let Err(e) = tray.refresh().await {
@@ -251,11 +367,17 @@ fn listen_events(
         },
         Ok(_) = monitors_change_rx.recv() => {
           info!("Monitors changed.");
-          /*
-          if let Err(e) = widget_factory.relaunch_all().await {
-            error!("Error relaunching widgets: {:?}", e);
+          if let Err(e) = widget_factory.switch_profile_by_monitors().await {
+            error!("Error switching profile: {:?}", e);
+          }
+          // Matched profiles aren't written to the settings, so the tray
+          // isn't refreshed by a settings change.
+          if let Err(e) = tray.refresh().await {
+            error!("Error refreshing tray: {:?}", e);
+          }
+          if let Err(e) = widget_factory.reconcile_monitors().await {
+            error!("Error reconciling widgets with monitors: {:?}", e);
           }
-          */
           Ok(())
         },
         Ok(changed_configs) = widget_configs_change_rx.recv() => {
@@ -265,6 +387,14 @@ fn listen_events(
           }
           Ok(())
         },
+        Ok(diagnostics) = diagnostics_change_rx.recv() => {
+          info!("Config diagnostics changed.");
+          if let Err(e) = tray.refresh().await {
+            error!("Error refreshing tray: {:?}", e);
+          }
+          let _ = app_handle.emit("config-diagnostics-changed", diagnostics);
+          Ok(())
+        },
         Some(provider_emission) = emit_rx.recv() => {
           info!("Provider emission: {:?}", provider_emission);
           let _ = app_handle.emit("provider-emit", provider_emission.clone());
@@ -281,78 +411,12 @@ fn listen_events(
 }
 
 /// Setup single instance Tauri plugin.
-fn setup_single_instance(
-  app: &tauri::App,
-  widget_factory: Arc<WidgetFactory>,
-) -> anyhow::Result<()> {
+fn setup_single_instance(app: &tauri::App) -> anyhow::Result<()> {
   app
     .handle()
-    .plugin(tauri_plugin_single_instance::init(move |_, args, _| {
-      let widget_factory = widget_factory.clone();
-
-      task::spawn(async move {
-        let res = match Cli::try_parse_from(args) {
-          Ok(cli) => {
-            // No-op if no subcommand is provided.
-            if cli.command() != CliCommand::Empty {
-              open_widgets_by_cli_command(cli, widget_factory).await
-            } else {
-              Ok(())
-            }
-          }
-          _ => Err(anyhow::anyhow!("Failed to parse CLI arguments.")),
-        };
-
-        if let Err(err) = res {
-          error!("{:?}", err);
-        }
-      });
+    .plugin(tauri_plugin_single_instance::init(|_, args, _| {
+      warn!("Ignoring launch of another instance with args: {:?}", args);
     }))?;
 
   Ok(())
 }
-
-/// Opens widgets based on CLI command.
-async fn open_widgets_by_cli_command(
-  cli: Cli,
-  widget_factory: Arc<WidgetFactory>,
-) -> anyhow::Result<()> {
-  let res = match cli.command() {
-    CliCommand::StartWidget(args) => {
-      widget_factory
-        .start_widget(
-          &args.config_path,
-          &WidgetOpenOptions::Standalone(WidgetPlacement {
-            anchor: args.anchor,
-            offset_x: args.offset_x,
-            offset_y: args.offset_y,
-            width: args.width,
-            height: args.height,
-            monitor_selection: match args.monitor_type {
-              MonitorType::All => MonitorSelection::All,
-              MonitorType::Primary => MonitorSelection::Primary,
-              MonitorType::Secondary => MonitorSelection::Secondary,
-            },
-            dock_to_edge: Default::default(),
-          }),
-        )
-        .await
-    }
-    CliCommand::StartWidgetPreset(args) => {
-      widget_factory
-        .start_widget(
-          &args.config_path,
-          &WidgetOpenOptions::Preset(args.preset_name),
-        )
-        .await
-    }
-    CliCommand::Startup(_) | CliCommand::Empty => widget_factory.startup().await,
-    _ => unreachable!(),
-  };
-
-  if let Err(err) = res {
-    error!("Failed to open widgets: {:?}", err);
-  }
-
-  Ok(())
-}
@@ -0,0 +1,191 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Payload for messages published on the bus.
+///
+/// Sent to subscribed widgets via the `bus-message` event.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusMessage {
+  topic: String,
+  payload: Value,
+}
+
+/// Publish/subscribe bus for messages between widgets (and scripts via
+/// the CLI).
+#[derive(Debug)]
+pub struct MessageBus {
+  app_handle: AppHandle,
+
+  /// Subscriptions and retained payloads of each topic.
+  topics: Mutex<Topics>,
+}
+
+/// Subscriptions and retained payloads of the bus's topics, kept apart
+/// from the delivery of messages to widgets.
+#[derive(Debug, Default)]
+struct Topics {
+  /// Map of topics to the ID's of widgets subscribed to them.
+  subscriptions: HashMap<String, HashSet<String>>,
+
+  /// Last retained payload of each topic, which is sent to widgets when
+  /// they subscribe.
+  retained: HashMap<String, Value>,
+}
+
+impl MessageBus {
+  /// Creates a new `MessageBus` instance.
+  pub fn new(app_handle: &AppHandle) -> Self {
+    Self {
+      app_handle: app_handle.clone(),
+      topics: Mutex::new(Topics::default()),
+    }
+  }
+
+  /// Sends a message to all widgets subscribed to the topic.
+  ///
+  /// If `retain` is set, the payload is kept as the topic's last value
+  /// for future subscribers. Retaining a `null` payload clears it.
+  pub async fn publish(&self, topic: &str, payload: Value, retain: bool) -> anyhow::Result<()> {
+    let widget_ids = self.topics.lock().await.publish(topic, &payload, retain);
+
+    info!(
+      "Publishing message on topic '{}' to {} widget(s).",
+      topic,
+      widget_ids.len()
+    );
+
+    let message = BusMessage {
+      topic: topic.to_string(),
+      payload,
+    };
+
+    for widget_id in widget_ids {
+      self
+        .app_handle
+        .emit_to(widget_id, "bus-message", message.clone())?;
+    }
+
+    Ok(())
+  }
+
+  /// Subscribes a widget to a topic.
+  ///
+  /// Returns the topic's retained payload, if any.
+  pub async fn subscribe(&self, widget_id: &str, topic: &str) -> Option<Value> {
+    self.topics.lock().await.subscribe(widget_id, topic)
+  }
+
+  /// Unsubscribes a widget from a topic.
+  pub async fn unsubscribe(&self, widget_id: &str, topic: &str) {
+    self.topics.lock().await.unsubscribe(widget_id, topic);
+  }
+
+  /// Removes all subscriptions of a widget (e.g. when it's closed).
+  pub async fn unsubscribe_all(&self, widget_id: &str) {
+    self.topics.lock().await.unsubscribe_all(widget_id);
+  }
+}
+
+impl Topics {
+  /// Retains the payload if `retain` is set, and clears the retained
+  /// payload if it's `null`.
+  ///
+  /// Returns the ID's of widgets subscribed to the topic.
+  fn publish(&mut self, topic: &str, payload: &Value, retain: bool) -> HashSet<String> {
+    if retain {
+      match payload.is_null() {
+        true => self.retained.remove(topic),
+        false => self.retained.insert(topic.to_string(), payload.clone()),
+      };
+    }
+
+    self.subscriptions.get(topic).cloned().unwrap_or_default()
+  }
+
+  fn subscribe(&mut self, widget_id: &str, topic: &str) -> Option<Value> {
+    self
+      .subscriptions
+      .entry(topic.to_string())
+      .or_default()
+      .insert(widget_id.to_string());
+
+    self.retained.get(topic).cloned()
+  }
+
+  fn unsubscribe(&mut self, widget_id: &str, topic: &str) {
+    if let Some(widget_ids) = self.subscriptions.get_mut(topic) {
+      widget_ids.remove(widget_id);
+
+      if widget_ids.is_empty() {
+        self.subscriptions.remove(topic);
+      }
+    }
+  }
+
+  fn unsubscribe_all(&mut self, widget_id: &str) {
+    for widget_ids in self.subscriptions.values_mut() {
+      widget_ids.remove(widget_id);
+    }
+
+    self
+      .subscriptions
+      .retain(|_, widget_ids| !widget_ids.is_empty());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  fn ids(widget_ids: &[&str]) -> HashSet<String> {
+    widget_ids.iter().map(|id| id.to_string()).collect()
+  }
+
+  #[test]
+  fn retains_payloads_for_subscribers() {
+    let mut topics = Topics::default();
+
+    // Payloads that aren't retained are only sent to current subscribers.
+    topics.publish("theme", &json!("light"), false);
+    assert_eq!(topics.subscribe("widget-a", "theme"), None);
+
+    assert_eq!(
+      topics.publish("theme", &json!("dark"), true),
+      ids(&["widget-a"])
+    );
+
+    assert_eq!(topics.subscribe("widget-b", "theme"), Some(json!("dark")));
+
+    // Retaining `null` clears the retained payload.
+    topics.publish("theme", &Value::Null, true);
+    assert_eq!(topics.subscribe("widget-c", "theme"), None);
+  }
+
+  #[test]
+  fn removes_subscriptions() {
+    let mut topics = Topics::default();
+
+    topics.subscribe("widget-a", "theme");
+    topics.subscribe("widget-a", "date");
+    topics.subscribe("widget-b", "theme");
+
+    topics.unsubscribe("widget-b", "theme");
+    assert_eq!(
+      topics.publish("theme", &json!("dark"), false),
+      ids(&["widget-a"])
+    );
+
+    // Topics without subscribers are removed.
+    topics.unsubscribe_all("widget-a");
+    assert!(topics.subscriptions.is_empty());
+    assert!(topics.publish("theme", &json!("dark"), false).is_empty());
+  }
+}
@@ -1,14 +1,19 @@
 use std::{sync::Arc, time::Duration};
 
+use regex::Regex;
 use serde::Serialize;
-use tauri::AppHandle;
+use tauri::{AppHandle, PhysicalPosition, PhysicalSize};
 use tokio::{
-  sync::{broadcast, RwLock},
+  sync::{broadcast, mpsc, RwLock},
   task,
 };
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::config::MonitorSelection;
+#[cfg(target_os = "linux")]
+use crate::common::linux::{display_infos, listen_display_changes};
+#[cfg(target_os = "windows")]
+use crate::common::windows::{display_infos, listen_display_changes};
+use crate::config::{DockEdge, MonitorSelection};
 
 #[derive(Debug)]
 pub struct MonitorState {
@@ -23,6 +28,10 @@ pub struct MonitorState {
 #[derive(Clone, Debug, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Monitor {
+  /// Identifier of the physical display, which stays the same across
+  /// reconnections where supported. Falls back to the monitor name, or
+  /// its position if unnamed.
+  pub id: String,
   pub name: Option<String>,
   pub is_primary: bool,
   pub x: i32,
@@ -30,6 +39,79 @@ pub struct Monitor {
   pub width: u32,
   pub height: u32,
   pub scale_factor: f32,
+
+  /// Refresh rate in Hz, if available.
+  pub refresh_rate: Option<f32>,
+
+  /// Area of the monitor that isn't covered by taskbars, docks, or other
+  /// reserved space.
+  pub work_area: WorkArea,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkArea {
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+}
+
+impl WorkArea {
+  /// Shrinks the work area to leave out space reserved at a monitor edge
+  /// (e.g. by a docked widget). Space that's already left out has no
+  /// effect, so reserving the same space again keeps the work area as is.
+  pub fn without_reserved(
+    &self,
+    edge: DockEdge,
+    size: PhysicalSize<i32>,
+    position: PhysicalPosition<i32>,
+  ) -> WorkArea {
+    let (mut left, mut top) = (self.x, self.y);
+    let mut right = self.x + self.width as i32;
+    let mut bottom = self.y + self.height as i32;
+
+    match edge {
+      DockEdge::Top => top = top.max(position.y + size.height),
+      DockEdge::Bottom => bottom = bottom.min(position.y),
+      DockEdge::Left => left = left.max(position.x + size.width),
+      DockEdge::Right => right = right.min(position.x),
+    }
+
+    WorkArea {
+      x: left,
+      y: top,
+      width: (right - left).max(0) as u32,
+      height: (bottom - top).max(0) as u32,
+    }
+  }
+}
+
+/// Platform-specific details of a monitor.
+#[derive(Debug, Default)]
+pub struct DisplayInfo {
+  pub id: Option<String>,
+  pub refresh_rate: Option<f32>,
+}
+
+impl Monitor {
+  /// Whether both monitors refer to the same physical display, even if
+  /// their bounds, work area or scale factor differ.
+  pub fn is_same_display(&self, other: &Monitor) -> bool {
+    self.id == other.id
+  }
+
+  /// Whether both monitors have the same position, size and scale
+  /// factor. Work areas are left out, since they change whenever a
+  /// widget docks to the monitor. Docked widgets instead compare work
+  /// areas without their own reserved space.
+  pub fn same_bounds(&self, other: &Monitor) -> bool {
+    self.x == other.x
+      && self.y == other.y
+      && self.width == other.width
+      && self.height == other.height
+      && self.scale_factor == other.scale_factor
+  }
 }
 
 impl MonitorState {
@@ -50,20 +132,55 @@ impl MonitorState {
 
   /// Listens for display setting changes.
   ///
-  /// Updates monitor state on scaling changes, monitor connections, and
-  /// monitor disconnections. Does not update on working area changes.
+  /// Updates monitor state on scaling changes, monitor connections,
+  /// monitor disconnections, and work area changes. Uses platform
+  /// notifications where available, and otherwise polls for changes.
   fn listen_changes(
     app_handle: AppHandle,
     monitors: Arc<RwLock<Vec<Monitor>>>,
     change_tx: broadcast::Sender<Vec<Monitor>>,
   ) {
+    let (notify_tx, mut notify_rx) = mpsc::unbounded_channel();
+
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    let mut is_notified = match listen_display_changes(notify_tx) {
+      Ok(()) => true,
+      Err(err) => {
+        warn!("Polling for monitor changes instead: {:?}", err);
+        false
+      }
+    };
+
+    #[cfg(target_os = "macos")]
+    let mut is_notified = {
+      drop(notify_tx);
+      false
+    };
+
     task::spawn(async move {
       let mut interval = tokio::time::interval(Duration::from_secs(4));
 
       interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
       loop {
-        interval.tick().await;
+        match is_notified {
+          true => {
+            if notify_rx.recv().await.is_none() {
+              warn!("Display listener stopped. Polling for monitor changes instead.");
+              is_notified = false;
+              continue;
+            }
+
+            // Changes tend to arrive in bursts (e.g. a resolution change
+            // followed by a work area change), so wait for them to settle.
+            tokio::time::sleep(Duration::from_millis(250)).await;
+            while notify_rx.try_recv().is_ok() {}
+          }
+          false => {
+            interval.tick().await;
+          }
+        }
+
         let new_monitors = Self::available_monitors(&app_handle);
 
         let should_update = {
@@ -90,19 +207,42 @@ impl MonitorState {
     let mut monitors = app_handle
       .available_monitors()
       .map(|monitors| {
+        let names = monitors
+          .iter()
+          .map(|monitor| monitor.name().map_or("", String::as_str))
+          .collect::<Vec<_>>();
+
+        let display_infos = Self::display_infos(&names);
+
         monitors
           .into_iter()
-          .map(|monitor| Monitor {
-            name: monitor.name().cloned(),
-            is_primary: primary_monitor
-              .as_ref()
-              .map(|m| m.name() == monitor.name())
-              .unwrap_or(false),
-            x: monitor.position().x,
-            y: monitor.position().y,
-            width: monitor.size().width,
-            height: monitor.size().height,
-            scale_factor: monitor.scale_factor() as f32,
+          .zip(display_infos)
+          .map(|(monitor, display_info)| {
+            let work_area = monitor.work_area();
+
+            Monitor {
+              id: display_info
+                .id
+                .or_else(|| monitor.name().cloned())
+                .unwrap_or_else(|| format!("{},{}", monitor.position().x, monitor.position().y)),
+              name: monitor.name().cloned(),
+              is_primary: primary_monitor
+                .as_ref()
+                .map(|m| m.name() == monitor.name())
+                .unwrap_or(false),
+              x: monitor.position().x,
+              y: monitor.position().y,
+              width: monitor.size().width,
+              height: monitor.size().height,
+              scale_factor: monitor.scale_factor() as f32,
+              refresh_rate: display_info.refresh_rate,
+              work_area: WorkArea {
+                x: work_area.position.x,
+                y: work_area.position.y,
+                width: work_area.size.width,
+                height: work_area.size.height,
+              },
+            }
           })
           .collect()
       })
@@ -120,6 +260,15 @@ impl MonitorState {
     monitors
   }
 
+  /// Gets platform-specific details for each monitor by its name.
+  fn display_infos(names: &[&str]) -> Vec<DisplayInfo> {
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    return display_infos(names);
+
+    #[cfg(target_os = "macos")]
+    names.iter().map(|_| DisplayInfo::default()).collect()
+  }
+
   /// Returns a string representation of the monitors.
   pub fn output_str(&self) -> anyhow::Result<String> {
     let monitors = self.monitors.try_read()?;
@@ -129,24 +278,191 @@ impl MonitorState {
     Ok(monitors_str)
   }
 
+  /// Returns all available monitors, sorted from left-to-right and
+  /// top-to-bottom.
+  pub async fn monitors(&self) -> Vec<Monitor> {
+    self.monitors.read().await.clone()
+  }
+
+  /// Returns the monitors that match the given selection, sorted from
+  /// left-to-right and top-to-bottom.
   pub async fn monitors_by_selection(&self, monitor_selection: &MonitorSelection) -> Vec<Monitor> {
-    let monitors = self.monitors.read().await.clone();
+    let monitors = self.monitors.read().await;
+    Self::select_monitors(&monitors, monitor_selection)
+  }
+
+  fn select_monitors(monitors: &[Monitor], monitor_selection: &MonitorSelection) -> Vec<Monitor> {
+    let filter = |predicate: &dyn Fn(&Monitor) -> bool| {
+      monitors
+        .iter()
+        .filter(|monitor| predicate(monitor))
+        .cloned()
+        .collect()
+    };
+
+    // Whether a monitor is matched by each of the given selections.
+    let matches = |selections: &Vec<MonitorSelection>, monitor: &Monitor| -> Vec<bool> {
+      selections
+        .iter()
+        .map(|selection| {
+          Self::select_monitors(monitors, selection)
+            .iter()
+            .any(|selected| selected.is_same_display(monitor))
+        })
+        .collect()
+    };
 
     match monitor_selection {
-      MonitorSelection::All => monitors,
-      MonitorSelection::Primary => monitors
-        .into_iter()
-        .filter(|monitor| monitor.is_primary)
-        .collect(),
-      MonitorSelection::Secondary => monitors
-        .into_iter()
-        .filter(|monitor| !monitor.is_primary)
-        .collect(),
+      MonitorSelection::All => monitors.to_vec(),
+      MonitorSelection::Primary => filter(&|monitor| monitor.is_primary),
+      MonitorSelection::Secondary => filter(&|monitor| !monitor.is_primary),
       MonitorSelection::Index(index) => monitors.get(*index).cloned().into_iter().collect(),
-      MonitorSelection::Name(name) => monitors
+      MonitorSelection::Name(name) => filter(&|monitor| monitor.name.as_deref() == Some(name)),
+      MonitorSelection::NameRegex(pattern) => match Regex::new(pattern) {
+        Ok(regex) => filter(&|monitor| {
+          monitor
+            .name
+            .as_deref()
+            .is_some_and(|name| regex.is_match(name))
+        }),
+        Err(err) => {
+          warn!("Invalid monitor name regex '{}': {}", pattern, err);
+          Vec::new()
+        }
+      },
+      MonitorSelection::Largest => monitors
+        .iter()
+        .rev()
+        .max_by_key(|monitor| monitor.width as u64 * monitor.height as u64)
+        .cloned()
         .into_iter()
-        .filter(|monitor| monitor.name.as_deref() == Some(name))
         .collect(),
+      MonitorSelection::Portrait => filter(&|monitor| monitor.height > monitor.width),
+      MonitorSelection::Landscape => filter(&|monitor| monitor.width > monitor.height),
+      MonitorSelection::MinWidth(min_width) => filter(&|monitor| monitor.width >= *min_width),
+      MonitorSelection::AllExcept(selections) => {
+        filter(&|monitor| !matches(selections, monitor).contains(&true))
+      }
+      MonitorSelection::AnyOf(selections) => {
+        filter(&|monitor| matches(selections, monitor).contains(&true))
+      }
+      MonitorSelection::AllOf(selections) => {
+        filter(&|monitor| !matches(selections, monitor).contains(&false))
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn monitor(name: &str, is_primary: bool, width: u32, height: u32) -> Monitor {
+    Monitor {
+      id: name.to_string(),
+      name: Some(name.to_string()),
+      is_primary,
+      x: 0,
+      y: 0,
+      width,
+      height,
+      scale_factor: 1.0,
+      refresh_rate: None,
+      work_area: WorkArea {
+        x: 0,
+        y: 0,
+        width,
+        height,
+      },
     }
   }
+
+  fn names(monitors: Vec<Monitor>) -> Vec<String> {
+    monitors
+      .into_iter()
+      .filter_map(|monitor| monitor.name)
+      .collect()
+  }
+
+  #[test]
+  fn test_work_area_without_reserved() {
+    let work_area = monitor("DP-1", true, 1920, 1080).work_area;
+
+    let top_bar = work_area.without_reserved(
+      DockEdge::Top,
+      PhysicalSize::new(1920, 40),
+      PhysicalPosition::new(0, 0),
+    );
+
+    assert_eq!(
+      top_bar,
+      WorkArea {
+        x: 0,
+        y: 40,
+        width: 1920,
+        height: 1040,
+      }
+    );
+
+    // Reserving the same space again has no effect.
+    assert_eq!(
+      top_bar.without_reserved(
+        DockEdge::Top,
+        PhysicalSize::new(1920, 40),
+        PhysicalPosition::new(0, 0),
+      ),
+      top_bar
+    );
+
+    let right_bar = top_bar.without_reserved(
+      DockEdge::Right,
+      PhysicalSize::new(60, 1080),
+      PhysicalPosition::new(1860, 0),
+    );
+
+    assert_eq!(right_bar.width, 1860);
+    assert_eq!(right_bar.height, 1040);
+  }
+
+  #[test]
+  fn test_select_monitors() {
+    let monitors = vec![
+      monitor("DP-1", true, 2560, 1440),
+      monitor("DP-2", false, 1440, 2560),
+      monitor("HDMI-1", false, 1920, 1080),
+    ];
+
+    let select = |selection| names(MonitorState::select_monitors(&monitors, &selection));
+
+    assert_eq!(select(MonitorSelection::Largest), ["DP-1"]);
+    assert_eq!(select(MonitorSelection::Portrait), ["DP-2"]);
+    assert_eq!(
+      select(MonitorSelection::NameRegex("^DP-".into())),
+      ["DP-1", "DP-2"]
+    );
+
+    assert_eq!(
+      select(MonitorSelection::AllExcept(vec![
+        MonitorSelection::Primary,
+        MonitorSelection::Name("HDMI-1".into()),
+      ])),
+      ["DP-2"]
+    );
+
+    assert_eq!(
+      select(MonitorSelection::AllOf(vec![
+        MonitorSelection::Landscape,
+        MonitorSelection::MinWidth(2000),
+      ])),
+      ["DP-1"]
+    );
+
+    assert_eq!(
+      select(MonitorSelection::AnyOf(vec![
+        MonitorSelection::Portrait,
+        MonitorSelection::Index(2),
+      ])),
+      ["DP-2", "HDMI-1"]
+    );
+  }
 }
@@ -0,0 +1,795 @@
+use std::{
+  collections::BTreeMap,
+  env,
+  fs::{self, File},
+  io::{self, BufRead, IsTerminal, Write},
+  path::{Component, Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+  cli::PackArgs,
+  common::{copy_dir_all, PathExt},
+  config::Config,
+  config_diagnostics::ConfigDiagnostics,
+  widget_discovery::{is_ignored_dir, WidgetDiscovery},
+  widget_pack::{WidgetPack, PACK_MANIFEST_FILE},
+};
+
+/// File within an installed pack that records the hashes of its files
+/// at install time, to detect local changes.
+const INSTALL_RECORD_FILE: &str = ".zebar-pack-install.json";
+
+/// Number of packs that are kept staged for approval. Older previews
+/// expire once exceeded.
+const MAX_STAGED_PACKS: usize = 5;
+
+/// Installs, removes and exports the widget packs of a config directory.
+pub struct PackManager {
+  config_dir: PathBuf,
+}
+
+/// A pack found within the config directory.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledPack {
+  #[serde(flatten)]
+  pub pack: WidgetPack,
+
+  /// Whether the pack was installed via `zebar pack install`.
+  pub is_managed: bool,
+
+  /// Whether the pack's files changed since it was installed. Packs that
+  /// weren't installed via `zebar pack install` count as modified.
+  pub is_modified: bool,
+}
+
+/// What installing a pack would do, for the user to approve.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackPreview {
+  /// Identifies the staged pack, so that exactly what was previewed is
+  /// installed once approved.
+  pub install_token: String,
+
+  pub pack: WidgetPack,
+
+  /// Directory the pack would be installed to.
+  pub install_path: PathBuf,
+
+  /// Version of the pack that would be replaced, if any.
+  pub replaced_version: Option<String>,
+
+  /// Shell commands that the pack's widgets request to run.
+  pub shell_commands: Vec<RequestedShellCommand>,
+}
+
+/// A shell command requested via a widget's `privileges`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestedShellCommand {
+  /// Path to the widget config, relative to the pack.
+  pub config_path: PathBuf,
+
+  pub program: String,
+
+  pub args_regex: String,
+}
+
+/// A pack that was extracted or copied to a temporary directory, ready to
+/// be installed.
+pub struct StagedPack {
+  _staging_dir: TempDir,
+  pack_dir: PathBuf,
+  pub preview: PackPreview,
+}
+
+/// Packs that were previewed in the settings UI, awaiting the user's
+/// approval to be installed.
+#[derive(Default)]
+pub struct StagedPacks {
+  packs: Mutex<Vec<StagedPack>>,
+}
+
+impl StagedPacks {
+  /// Keeps a staged pack until it's approved via its install token.
+  pub async fn insert(&self, staged: StagedPack) {
+    let mut packs = self.packs.lock().await;
+    packs.push(staged);
+
+    let excess = packs.len().saturating_sub(MAX_STAGED_PACKS);
+    packs.drain(..excess);
+  }
+
+  /// Removes the staged pack with the given install token.
+  pub async fn take(&self, install_token: &str) -> anyhow::Result<StagedPack> {
+    let mut packs = self.packs.lock().await;
+
+    let index = packs
+      .iter()
+      .position(|staged| staged.preview.install_token == install_token)
+      .context("Pack preview not found or expired. Preview the pack again.")?;
+
+    Ok(packs.remove(index))
+  }
+}
+
+/// Directory that is removed on drop.
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.0);
+  }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallRecord {
+  /// Hashes of the pack's files, keyed by their relative path.
+  files: BTreeMap<String, String>,
+}
+
+impl PackManager {
+  /// Creates a new `PackManager` for the given config directory.
+  pub fn new(config_dir: &Path) -> Self {
+    Self {
+      config_dir: config_dir.to_path_buf(),
+    }
+  }
+
+  /// Returns the packs within the config directory.
+  pub fn list(&self) -> anyhow::Result<Vec<InstalledPack>> {
+    Ok(
+      Config::widget_packs_in_dir(&self.config_dir)?
+        .into_iter()
+        .map(|pack| InstalledPack {
+          is_managed: is_managed(&pack.path),
+          is_modified: is_modified(&pack.path),
+          pack,
+        })
+        .collect(),
+    )
+  }
+
+  /// Extracts a `.zip` archive, or copies a directory, to a temporary
+  /// directory and reads its manifest and requested privileges.
+  ///
+  /// Archive entries that would be written outside of the directory
+  /// (e.g. `../../evil.exe`) and symlinks are rejected.
+  pub fn stage(&self, source: &Path) -> anyhow::Result<StagedPack> {
+    let staging_dir = TempDir(env::temp_dir().join(format!("zebar-pack-{}", Uuid::new_v4())));
+
+    // Extracted into a subdirectory, so that the pack's directory is the
+    // only entry of its parent when searching for widget configs.
+    let extract_dir = staging_dir.0.join("pack");
+    fs::create_dir_all(&extract_dir)?;
+
+    match source.is_dir() {
+      true => copy_pack_files(source, &extract_dir)?,
+      false => extract_zip(source, &extract_dir)?,
+    }
+
+    let pack_dir = find_pack_dir(&extract_dir)?.to_absolute()?;
+
+    let diagnostics = ConfigDiagnostics::new();
+    let pack = WidgetPack::read(&pack_dir, &diagnostics)?;
+
+    if !pack.is_compatible() {
+      bail!(pack.incompatible_message());
+    }
+
+    let install_path = self.config_dir.join(dir_name(&pack.name));
+
+    let replaced_version = WidgetPack::read(&install_path, &diagnostics)
+      .ok()
+      .map(|pack| pack.version);
+
+    let preview = PackPreview {
+      install_token: Uuid::new_v4().simple().to_string(),
+      shell_commands: requested_shell_commands(&pack_dir)?,
+      pack,
+      install_path,
+      replaced_version,
+    };
+
+    Ok(StagedPack {
+      _staging_dir: staging_dir,
+      pack_dir,
+      preview,
+    })
+  }
+
+  /// Installs a staged pack into the config directory, replacing any
+  /// installed version of it.
+  ///
+  /// Fails if the installed version has local changes, unless `force` is
+  /// set.
+  ///
+  /// The pack is copied next to the install path first and then renamed
+  /// into place, so that a failed copy leaves the installed version as
+  /// is.
+  pub fn install(&self, staged: &StagedPack, force: bool) -> anyhow::Result<WidgetPack> {
+    let install_path = &staged.preview.install_path;
+
+    if install_path.exists() {
+      self.check_overwritable(install_path, force)?;
+    }
+
+    // Named `tmp-*`, so that it's never searched for widget configs.
+    let copy_dir = TempDir(self.config_dir.join(format!("tmp-pack-{}", Uuid::new_v4())));
+    copy_dir_all(&staged.pack_dir, &copy_dir.0, true)?;
+
+    let record = InstallRecord {
+      files: hash_pack_files(&copy_dir.0)?,
+    };
+
+    fs::write(
+      copy_dir.0.join(INSTALL_RECORD_FILE),
+      serde_json::to_string_pretty(&record)? + "\n",
+    )?;
+
+    // Directories can't be renamed over existing ones on Windows, so the
+    // installed version is moved aside until the new one is in place.
+    let replaced_dir = TempDir(self.config_dir.join(format!("tmp-pack-{}", Uuid::new_v4())));
+
+    if install_path.exists() {
+      fs::rename(install_path, &replaced_dir.0)
+        .with_context(|| format!("Failed to replace {}.", install_path.display()))?;
+    }
+
+    if let Err(err) = fs::rename(&copy_dir.0, install_path) {
+      let _ = fs::rename(&replaced_dir.0, install_path);
+
+      return Err(err).with_context(|| format!("Failed to install to {}.", install_path.display()));
+    }
+
+    let mut pack = staged.preview.pack.clone();
+    pack.path = install_path.to_absolute()?;
+
+    Ok(pack)
+  }
+
+  /// Removes the pack with the given name from the config directory.
+  ///
+  /// Fails if the pack has local changes, unless `force` is set.
+  pub fn remove(&self, name: &str, force: bool) -> anyhow::Result<WidgetPack> {
+    let pack = self
+      .list()?
+      .into_iter()
+      .find(|installed| installed.pack.name == name)
+      .map(|installed| installed.pack)
+      .with_context(|| format!("No pack found with the name '{}'.", name))?;
+
+    self.check_overwritable(&pack.path, force)?;
+
+    fs::remove_dir_all(&pack.path)
+      .with_context(|| format!("Failed to remove {}.", pack.path.display()))?;
+
+    Ok(pack)
+  }
+
+  /// Writes the pack at the given directory to a `.zip` archive.
+  ///
+  /// Defaults to `<name>-<version>.zip` in the current directory.
+  ///
+  /// Returns the path to the archive.
+  pub fn export(pack_dir: &Path, output: Option<PathBuf>) -> anyhow::Result<PathBuf> {
+    let pack = WidgetPack::read(pack_dir, &ConfigDiagnostics::new())?;
+
+    let output = output
+      .unwrap_or_else(|| PathBuf::from(format!("{}-{}.zip", dir_name(&pack.name), pack.version)));
+
+    let mut writer = ZipWriter::new(
+      File::create(&output).with_context(|| format!("Failed to create {}.", output.display()))?,
+    );
+
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for relative_path in pack_files(pack_dir)? {
+      writer.start_file(zip_entry_name(&relative_path), options)?;
+      io::copy(&mut File::open(pack_dir.join(&relative_path))?, &mut writer)?;
+    }
+
+    writer.finish()?;
+
+    Ok(output)
+  }
+
+  /// Writes a pack within the config directory to a new `.zip` archive,
+  /// for the settings UI.
+  ///
+  /// Unlike `export`, existing files are never replaced, and the output
+  /// must be a file name, which is written to the config directory.
+  pub fn export_installed(
+    &self,
+    pack_dir: &Path,
+    output: Option<PathBuf>,
+  ) -> anyhow::Result<PathBuf> {
+    let pack_dir = pack_dir.to_absolute()?;
+
+    if !pack_dir.starts_with(&self.config_dir) || pack_dir == self.config_dir {
+      bail!("{} is not within the config directory.", pack_dir.display());
+    }
+
+    let pack = WidgetPack::read(&pack_dir, &ConfigDiagnostics::new())?;
+
+    let output = output
+      .unwrap_or_else(|| PathBuf::from(format!("{}-{}.zip", dir_name(&pack.name), pack.version)));
+
+    // Only a plain file name is allowed, so that the archive can't be
+    // written outside of the config directory (e.g. `../evil.zip`).
+    if !matches!(
+      output.components().collect::<Vec<_>>().as_slice(),
+      [Component::Normal(_)]
+    ) {
+      bail!(
+        "Output must be a file name, not a path: {}",
+        output.display()
+      );
+    }
+
+    let output = self.config_dir.join(output);
+
+    if output.extension().and_then(|ext| ext.to_str()) != Some("zip") {
+      bail!("Exported pack must be a .zip file.");
+    }
+
+    if output.exists() {
+      bail!("{} already exists.", output.display());
+    }
+
+    Self::export(&pack_dir, Some(output))
+  }
+
+  /// Checks that the directory can be overwritten or removed, i.e. that
+  /// it's a pack installed via `zebar pack install` without local
+  /// changes.
+  fn check_overwritable(&self, pack_dir: &Path, force: bool) -> anyhow::Result<()> {
+    if force {
+      return Ok(());
+    }
+
+    if !is_managed(pack_dir) {
+      bail!(
+        "{} wasn't installed via `zebar pack install`. Use --force to replace it anyway.",
+        pack_dir.display()
+      );
+    }
+
+    if is_modified(pack_dir) {
+      bail!(
+        "{} has local changes. Use --force to discard them.",
+        pack_dir.display()
+      );
+    }
+
+    Ok(())
+  }
+}
+
+/// Runs a `zebar pack` command, and returns its output as JSON.
+pub fn run_command(args: PackArgs) -> anyhow::Result<String> {
+  let config_dir = |config_dir: Option<PathBuf>| -> anyhow::Result<PathBuf> {
+    let config_dir = match config_dir {
+      Some(dir) => dir,
+      None => Config::default_dir()?,
+    };
+
+    config_dir
+      .to_absolute()
+      .with_context(|| format!("Config directory not found: {}", config_dir.display()))
+  };
+
+  let output = match args {
+    PackArgs::Install(args) => {
+      let manager = PackManager::new(&config_dir(args.config_dir)?);
+      let staged = manager.stage(&args.source)?;
+
+      if !args.yes && !approve_shell_commands(&staged.preview)? {
+        bail!("Installation cancelled.");
+      }
+
+      serde_json::to_value(manager.install(&staged, args.force)?)?
+    }
+    PackArgs::List(args) => {
+      serde_json::to_value(PackManager::new(&config_dir(args.config_dir)?).list()?)?
+    }
+    PackArgs::Remove(args) => serde_json::to_value(
+      PackManager::new(&config_dir(args.config_dir)?).remove(&args.name, args.force)?,
+    )?,
+    PackArgs::Export(args) => {
+      let output = PackManager::export(&args.path, args.output)?;
+      serde_json::json!({ "path": output })
+    }
+  };
+
+  Ok(serde_json::to_string_pretty(&output)? + "\n")
+}
+
+/// Shows the shell commands requested by a pack, and asks the user to
+/// approve them. Packs without shell commands are approved as is.
+fn approve_shell_commands(preview: &PackPreview) -> anyhow::Result<bool> {
+  if preview.shell_commands.is_empty() {
+    return Ok(true);
+  }
+
+  if !io::stdin().is_terminal() {
+    bail!("Pack requests to run shell commands. Use --yes to approve them.");
+  }
+
+  eprintln!(
+    "Pack '{}' v{} requests to run the following shell commands:",
+    preview.pack.name, preview.pack.version
+  );
+
+  for command in &preview.shell_commands {
+    eprintln!(
+      "  {} (args matching `{}`) from {}",
+      command.program,
+      command.args_regex,
+      command.config_path.display()
+    );
+  }
+
+  eprint!("Install anyway? [y/N] ");
+  io::stderr().flush()?;
+
+  let mut answer = String::new();
+  io::stdin().lock().read_line(&mut answer)?;
+
+  Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Returns the directory containing the pack's manifest, which is either
+/// the given directory or its only subdirectory (e.g. for archives that
+/// wrap the pack in a folder).
+fn find_pack_dir(dir: &Path) -> anyhow::Result<PathBuf> {
+  if dir.join(PACK_MANIFEST_FILE).is_file() {
+    return Ok(dir.to_path_buf());
+  }
+
+  let entries = fs::read_dir(dir)?
+    .filter_map(|entry| Some(entry.ok()?.path()))
+    .collect::<Vec<_>>();
+
+  match entries.as_slice() {
+    [subdir] if subdir.join(PACK_MANIFEST_FILE).is_file() => Ok(subdir.clone()),
+    _ => bail!("No {} found in the pack.", PACK_MANIFEST_FILE),
+  }
+}
+
+/// Extracts a `.zip` archive into the given directory.
+fn extract_zip(archive_path: &Path, dir: &Path) -> anyhow::Result<()> {
+  let file = File::open(archive_path)
+    .with_context(|| format!("Failed to open {}.", archive_path.display()))?;
+
+  let mut archive = ZipArchive::new(file)
+    .with_context(|| format!("{} is not a valid zip archive.", archive_path.display()))?;
+
+  for index in 0..archive.len() {
+    let mut entry = archive.by_index(index)?;
+
+    // Entry names are untrusted, so only relative paths that stay within
+    // the directory are allowed.
+    let Some(relative_path) = entry.enclosed_name() else {
+      bail!("Archive contains an unsafe path: {}", entry.name());
+    };
+
+    if entry.is_symlink() {
+      bail!("Archive contains a symlink: {}", entry.name());
+    }
+
+    let path = dir.join(relative_path);
+
+    if entry.is_dir() {
+      fs::create_dir_all(&path)?;
+      continue;
+    }
+
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    io::copy(&mut entry, &mut File::create(&path)?)?;
+  }
+
+  Ok(())
+}
+
+/// Copies the files of a pack directory, without following symlinks.
+fn copy_pack_files(source_dir: &Path, dest_dir: &Path) -> anyhow::Result<()> {
+  for relative_path in pack_files(source_dir)? {
+    let dest_path = dest_dir.join(&relative_path);
+
+    if let Some(parent) = dest_path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    fs::copy(source_dir.join(&relative_path), dest_path)?;
+  }
+
+  Ok(())
+}
+
+/// Returns the relative paths of the files within a pack, sorted.
+///
+/// Skips symlinks, the install record, and directories that are never
+/// searched for widget configs (e.g. `node_modules`).
+fn pack_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+  let mut files = Vec::new();
+  let mut pending = vec![PathBuf::new()];
+
+  while let Some(relative_dir) = pending.pop() {
+    let entries = fs::read_dir(dir.join(&relative_dir)).with_context(|| {
+      format!(
+        "Failed to read directory: {}",
+        dir.join(&relative_dir).display()
+      )
+    })?;
+
+    for entry in entries {
+      let entry = entry?;
+      let file_type = entry.file_type()?;
+      let name = entry.file_name().to_string_lossy().to_string();
+      let relative_path = relative_dir.join(&name);
+
+      if file_type.is_dir() && !is_ignored_dir(&name) {
+        pending.push(relative_path);
+      } else if file_type.is_file() && relative_path != Path::new(INSTALL_RECORD_FILE) {
+        files.push(relative_path);
+      }
+    }
+  }
+
+  files.sort();
+  Ok(files)
+}
+
+/// Hashes the files within a pack with SHA-256, keyed by their relative
+/// path.
+fn hash_pack_files(dir: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+  pack_files(dir)?
+    .into_iter()
+    .map(|relative_path| {
+      let content = fs::read(dir.join(&relative_path))?;
+      let hash = format!("{:x}", Sha256::digest(&content));
+
+      Ok((zip_entry_name(&relative_path), hash))
+    })
+    .collect()
+}
+
+/// Whether the pack at the given directory was installed via `zebar pack
+/// install`.
+pub fn is_managed(pack_dir: &Path) -> bool {
+  pack_dir.join(INSTALL_RECORD_FILE).is_file()
+}
+
+/// Whether the files within an installed pack differ from when it was
+/// installed.
+fn is_modified(pack_dir: &Path) -> bool {
+  let record = fs::read_to_string(pack_dir.join(INSTALL_RECORD_FILE))
+    .ok()
+    .and_then(|content| serde_json::from_str::<InstallRecord>(&content).ok());
+
+  match (record, hash_pack_files(pack_dir)) {
+    (Some(record), Ok(files)) => record.files != files,
+    _ => true,
+  }
+}
+
+/// Returns the shell commands requested by the widget configs of a pack.
+fn requested_shell_commands(pack_dir: &Path) -> anyhow::Result<Vec<RequestedShellCommand>> {
+  let search_dir = pack_dir.parent().unwrap_or(pack_dir);
+  let diagnostics = ConfigDiagnostics::new();
+
+  let discovered = WidgetDiscovery::default().discover(search_dir, &diagnostics)?;
+  let mut shell_commands = Vec::new();
+
+  for config_path in discovered.config_paths {
+    let (config_path, config) = Config::parse_widget_config(&config_path, &diagnostics)
+      .with_context(|| format!("Invalid widget config in pack: {}", config_path.display()))?;
+
+    let relative_path = config_path
+      .strip_prefix(pack_dir)
+      .unwrap_or(&config_path)
+      .to_path_buf();
+
+    shell_commands.extend(
+      config
+        .privileges
+        .shell_commands
+        .into_iter()
+        .map(|privilege| RequestedShellCommand {
+          config_path: relative_path.clone(),
+          program: privilege.program,
+          args_regex: privilege.args_regex,
+        }),
+    );
+  }
+
+  Ok(shell_commands)
+}
+
+/// Converts a pack name into a directory name (e.g. `My Bar` ->
+/// `my-bar`).
+fn dir_name(pack_name: &str) -> String {
+  let name = pack_name
+    .to_lowercase()
+    .chars()
+    .map(|char| match char.is_alphanumeric() {
+      true => char,
+      false => '-',
+    })
+    .collect::<String>();
+
+  match name.trim_matches('-') {
+    "" => "pack".to_string(),
+    name => name.to_string(),
+  }
+}
+
+/// Converts a relative path into a zip entry name, which always uses
+/// forward slashes.
+fn zip_entry_name(relative_path: &Path) -> String {
+  relative_path
+    .components()
+    .map(|component| component.as_os_str().to_string_lossy())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Creates an empty directory that is removed once the test ends.
+  fn test_dir(name: &str) -> TempDir {
+    let dir = env::temp_dir().join(format!("zebar-pack-test-{}-{}", name, Uuid::new_v4()));
+
+    fs::create_dir_all(&dir).unwrap();
+    TempDir(dir.to_absolute().unwrap())
+  }
+
+  /// Writes a minimal pack with a single widget to the given directory.
+  fn write_pack(parent_dir: &Path, name: &str) -> PathBuf {
+    let pack_dir = parent_dir.join(name);
+    fs::create_dir_all(&pack_dir).unwrap();
+
+    fs::write(
+      pack_dir.join(PACK_MANIFEST_FILE),
+      format!(r#"{{ "name": "{}", "version": "1.0.0" }}"#, name),
+    )
+    .unwrap();
+
+    fs::write(pack_dir.join("index.html"), "<div></div>").unwrap();
+    pack_dir
+  }
+
+  #[test]
+  fn converts_pack_names_to_dir_names() {
+    assert_eq!(dir_name("My Bar"), "my-bar");
+    assert_eq!(dir_name("../../evil"), "evil");
+    assert_eq!(dir_name("///"), "pack");
+  }
+
+  /// Writes a `.zip` archive of a pack, with the given extra entries.
+  fn write_zip(path: &Path, extra_entries: impl FnOnce(&mut ZipWriter<File>)) {
+    let mut writer = ZipWriter::new(File::create(path).unwrap());
+    let options = SimpleFileOptions::default();
+
+    writer.start_file(PACK_MANIFEST_FILE, options).unwrap();
+    writer
+      .write_all(br#"{ "name": "my-bar", "version": "1.0.0" }"#)
+      .unwrap();
+
+    extra_entries(&mut writer);
+    writer.finish().unwrap();
+  }
+
+  #[test]
+  fn rejects_unsafe_zip_entries() {
+    let dir = test_dir("unsafe-zip");
+    let manager = PackManager::new(&dir.0);
+
+    for entry_name in ["../evil.txt", "nested/../../evil.txt", "/evil.txt"] {
+      let archive_path = dir.0.join("pack.zip");
+
+      write_zip(&archive_path, |writer| {
+        writer
+          .start_file(entry_name, SimpleFileOptions::default())
+          .unwrap();
+        writer.write_all(b"evil").unwrap();
+      });
+
+      let err = manager.stage(&archive_path).err().unwrap();
+      assert!(err.to_string().contains("unsafe path"), "{}", err);
+    }
+
+    assert!(!dir.0.parent().unwrap().join("evil.txt").exists());
+  }
+
+  #[test]
+  fn rejects_zip_symlinks() {
+    let dir = test_dir("symlink-zip");
+    let archive_path = dir.0.join("pack.zip");
+
+    write_zip(&archive_path, |writer| {
+      writer
+        .add_symlink("passwd", "/etc/passwd", SimpleFileOptions::default())
+        .unwrap();
+    });
+
+    let err = PackManager::new(&dir.0).stage(&archive_path).err().unwrap();
+
+    assert!(err.to_string().contains("symlink"), "{}", err);
+  }
+
+  #[test]
+  fn detects_modified_packs() {
+    let source_dir = test_dir("modified-source");
+    let config_dir = test_dir("modified-config");
+    let manager = PackManager::new(&config_dir.0);
+
+    let staged = manager.stage(&write_pack(&source_dir.0, "my-bar")).unwrap();
+
+    let pack = manager.install(&staged, false).unwrap();
+    assert!(is_managed(&pack.path));
+    assert!(!is_modified(&pack.path));
+
+    fs::write(pack.path.join("index.html"), "<div>edited</div>").unwrap();
+    assert!(is_modified(&pack.path));
+  }
+
+  #[test]
+  fn refuses_to_overwrite_modified_packs() {
+    let source_dir = test_dir("overwrite-source");
+    let config_dir = test_dir("overwrite-config");
+    let manager = PackManager::new(&config_dir.0);
+
+    let staged = manager.stage(&write_pack(&source_dir.0, "my-bar")).unwrap();
+
+    // Unmodified packs are replaced as is.
+    let pack = manager.install(&staged, false).unwrap();
+    manager.install(&staged, false).unwrap();
+
+    fs::write(pack.path.join("index.html"), "<div>edited</div>").unwrap();
+
+    let err = manager.install(&staged, false).err().unwrap();
+    assert!(err.to_string().contains("local changes"), "{}", err);
+    assert_eq!(
+      fs::read_to_string(pack.path.join("index.html")).unwrap(),
+      "<div>edited</div>"
+    );
+
+    manager.install(&staged, true).unwrap();
+    assert!(!is_modified(&pack.path));
+
+    // No leftovers of the copy or the replaced version.
+    let entries = fs::read_dir(&config_dir.0).unwrap().count();
+    assert_eq!(entries, 1);
+  }
+
+  #[test]
+  fn exports_installed_packs_only_to_config_dir() {
+    let config_dir = test_dir("export");
+    let pack_dir = write_pack(&config_dir.0, "my-bar");
+    let manager = PackManager::new(&config_dir.0);
+
+    for output in ["../evil.zip", "nested/evil.zip", "/tmp/evil.zip"] {
+      assert!(manager
+        .export_installed(&pack_dir, Some(PathBuf::from(output)))
+        .is_err());
+    }
+
+    let output = manager.export_installed(&pack_dir, None).unwrap();
+    assert_eq!(output, config_dir.0.join("my-bar-1.0.0.zip"));
+  }
+}
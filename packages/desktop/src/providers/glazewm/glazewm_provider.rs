@@ -0,0 +1,436 @@
+use std::collections::VecDeque;
+
+use anyhow::{bail, Context};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::oneshot;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, warn};
+
+use crate::providers::{
+  CommonProviderState, GlazeWmFunction, Provider, ProviderFunction, ProviderFunctionResponse,
+  ProviderFunctionResult, ProviderInputMsg, RuntimeType,
+};
+
+/// Events to subscribe to on the GlazeWM IPC server.
+const SUBSCRIBED_EVENTS: [&str; 3] = [
+  "focus_changed",
+  "workspace_activated",
+  "binding_modes_changed",
+];
+
+/// Queries that are sent to refresh the provider's output.
+const STATE_QUERIES: [&str; 3] = ["query monitors", "query binding-modes", "query focused"];
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GlazeWmProviderConfig {
+  /// Port of the GlazeWM IPC server.
+  #[serde(default = "default_ipc_port")]
+  pub ipc_port: u16,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlazeWmOutput {
+  pub all_monitors: Vec<Value>,
+  pub all_workspaces: Vec<Value>,
+  pub focused_monitor: Option<Value>,
+  pub focused_workspace: Option<Value>,
+  pub focused_container: Option<Value>,
+  pub binding_modes: Vec<Value>,
+}
+
+/// Message received from the GlazeWM IPC server.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "messageType", rename_all = "snake_case")]
+enum ServerMessage {
+  #[serde(rename_all = "camelCase")]
+  ClientResponse {
+    client_message: String,
+    data: Option<Value>,
+    error: Option<String>,
+    success: bool,
+  },
+  #[serde(rename_all = "camelCase")]
+  EventSubscription {
+    data: Option<Value>,
+    error: Option<String>,
+  },
+}
+
+pub struct GlazeWmProvider {
+  config: GlazeWmProviderConfig,
+  common: CommonProviderState,
+}
+
+impl GlazeWmProvider {
+  pub fn new(config: GlazeWmProviderConfig, common: CommonProviderState) -> GlazeWmProvider {
+    GlazeWmProvider { config, common }
+  }
+
+  async fn create_connection(&mut self) -> anyhow::Result<()> {
+    let url = format!("ws://127.0.0.1:{}", self.config.ipc_port);
+
+    let (stream, _) = connect_async(&url)
+      .await
+      .with_context(|| format!("Failed to connect to GlazeWM at {}.", url))?;
+
+    let (mut sink, mut stream) = stream.split();
+
+    sink
+      .send(Message::Text(format!(
+        "sub -e {}",
+        SUBSCRIBED_EVENTS.join(" ")
+      )))
+      .await?;
+
+    for query in STATE_QUERIES {
+      sink.send(Message::Text(query.into())).await?;
+    }
+
+    let mut output = GlazeWmOutput::default();
+
+    // Number of state queries that have yet to receive a response.
+    let mut pending_queries = STATE_QUERIES.len();
+
+    // Whether an event was received while queries were in flight, in
+    // which case their responses might already be outdated.
+    let mut is_stale = false;
+
+    // Function calls that are awaiting a response, in the order they were
+    // sent.
+    let mut pending_commands: VecDeque<(String, oneshot::Sender<ProviderFunctionResult>)> =
+      VecDeque::new();
+
+    loop {
+      tokio::select! {
+        message = stream.next() => {
+          let text = match message {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(_))) | None => bail!("GlazeWM IPC connection closed."),
+            Some(Ok(_)) => continue,
+            Some(Err(err)) => return Err(err.into()),
+          };
+
+          match serde_json::from_str::<ServerMessage>(&text) {
+            Ok(ServerMessage::EventSubscription { data, error }) => {
+              if let Some(error) = error {
+                warn!("GlazeWM event error: {}", error);
+              }
+
+              debug!("Received GlazeWM event: {:?}", data);
+
+              // Refresh state on every event. Events are only used as a
+              // trigger, since their payloads differ per event type.
+              if pending_queries > 0 {
+                is_stale = true;
+                continue;
+              }
+
+              for query in STATE_QUERIES {
+                sink.send(Message::Text(query.into())).await?;
+              }
+
+              pending_queries = STATE_QUERIES.len();
+            }
+            Ok(ServerMessage::ClientResponse { client_message, data, error, success }) => {
+              if STATE_QUERIES.contains(&client_message.as_str()) {
+                Self::apply_query_response(&mut output, &client_message, data.unwrap_or_default());
+                pending_queries = pending_queries.saturating_sub(1);
+
+                if pending_queries > 0 {
+                  continue;
+                }
+
+                // Query again instead of emitting outdated state.
+                if is_stale {
+                  for query in STATE_QUERIES {
+                    sink.send(Message::Text(query.into())).await?;
+                  }
+
+                  pending_queries = STATE_QUERIES.len();
+                  is_stale = false;
+                  continue;
+                }
+
+                self.common.emitter.emit_output(Ok(output.clone()));
+              } else if let Some(index) = pending_commands
+                .iter()
+                .position(|(command, _)| *command == client_message)
+              {
+                let (_, sender) = pending_commands.remove(index).unwrap();
+
+                let res = match success {
+                  true => Ok(ProviderFunctionResponse::Null),
+                  false => Err(error.unwrap_or_else(|| "Command failed.".into())),
+                };
+
+                let _ = sender.send(res);
+              }
+            }
+            Err(err) => warn!("Unrecognized GlazeWM message: {}", err),
+          }
+        }
+        Some(input) = self.common.input.async_rx.recv() => {
+          match input {
+            ProviderInputMsg::Stop => break,
+            ProviderInputMsg::Function(
+              ProviderFunction::Glazewm(GlazeWmFunction::RunCommand(args)),
+              sender,
+            ) => {
+              let message = match args.subject_container_id {
+                Some(id) => format!("command --id {} {}", id, args.command),
+                None => format!("command {}", args.command),
+              };
+
+              sink.send(Message::Text(message.clone())).await?;
+              pending_commands.push_back((message, sender));
+            }
+            _ => {}
+          }
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Updates the output with the response to one of `STATE_QUERIES`.
+  fn apply_query_response(output: &mut GlazeWmOutput, query: &str, data: Value) {
+    match query {
+      "query monitors" => {
+        output.all_monitors = data["monitors"].as_array().cloned().unwrap_or_default();
+
+        output.all_workspaces = output
+          .all_monitors
+          .iter()
+          .filter_map(|monitor| monitor["children"].as_array())
+          .flatten()
+          .cloned()
+          .collect();
+
+        output.focused_workspace = output
+          .all_workspaces
+          .iter()
+          .find(|workspace| workspace["hasFocus"].as_bool() == Some(true))
+          .cloned();
+
+        output.focused_monitor = output
+          .focused_workspace
+          .as_ref()
+          .and_then(|workspace| {
+            output
+              .all_monitors
+              .iter()
+              .find(|monitor| monitor["id"] == workspace["parentId"])
+          })
+          .cloned();
+      }
+      "query binding-modes" => {
+        output.binding_modes = data["bindingModes"].as_array().cloned().unwrap_or_default();
+      }
+      "query focused" => {
+        output.focused_container = data.get("focused").cloned();
+      }
+      _ => {}
+    }
+  }
+}
+
+#[async_trait]
+impl Provider for GlazeWmProvider {
+  fn runtime_type(&self) -> RuntimeType {
+    RuntimeType::Async
+  }
+
+  async fn start_async(&mut self) {
+    if let Err(err) = self.create_connection().await {
+      self.common.emitter.emit_output::<GlazeWmOutput>(Err(err));
+    }
+  }
+}
+
+/// Helper function for setting the default value for a
+/// `GlazeWmProviderConfig::ipc_port` field.
+fn default_ipc_port() -> u16 {
+  6123
+}
+
+#[cfg(test)]
+mod tests {
+  use tokio::net::TcpListener;
+  use tokio_tungstenite::accept_async;
+
+  use super::*;
+  use crate::providers::{ProviderEmitter, ProviderInput};
+
+  /// Stand-in for the GlazeWM IPC server that replays recorded responses.
+  ///
+  /// Commands containing `fail` are rejected. With `send_events`, an
+  /// event is sent after the first round of queries, and again while the
+  /// second round is in flight, after which workspace 1 is focused.
+  async fn replay_server(listener: TcpListener, send_events: bool) {
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut ws = accept_async(stream).await.unwrap();
+
+    let event = serde_json::json!({ "messageType": "event_subscription", "data": {} });
+    let mut round = 0;
+    let mut focused_workspace = "w2";
+
+    while let Some(Ok(Message::Text(text))) = ws.next().await {
+      if text == "query monitors" {
+        round += 1;
+
+        if send_events && round == 2 {
+          ws.send(Message::Text(event.to_string())).await.unwrap();
+        }
+      }
+
+      let data = match text.as_str() {
+        "query monitors" => serde_json::json!({
+          "monitors": [{
+            "id": "m1",
+            "children": [
+              { "id": "w1", "name": "1", "parentId": "m1", "hasFocus": focused_workspace == "w1" },
+              { "id": "w2", "name": "2", "parentId": "m1", "hasFocus": focused_workspace == "w2" },
+            ],
+          }],
+        }),
+        "query binding-modes" => serde_json::json!({ "bindingModes": [{ "name": "resize" }] }),
+        "query focused" => serde_json::json!({ "focused": { "id": "c1", "type": "window" } }),
+        _ => Value::Null,
+      };
+
+      let success = !text.contains("fail");
+
+      let response = serde_json::json!({
+        "messageType": "client_response",
+        "clientMessage": text,
+        "data": data,
+        "error": (!success).then_some("Invalid command."),
+        "success": success,
+      });
+
+      ws.send(Message::Text(response.to_string())).await.unwrap();
+
+      if send_events && text == "query focused" {
+        match round {
+          1 => ws.send(Message::Text(event.to_string())).await.unwrap(),
+          2 => focused_workspace = "w1",
+          _ => {}
+        }
+      }
+    }
+  }
+
+  /// Starts the provider against a replay server.
+  async fn start_provider(
+    send_events: bool,
+  ) -> (
+    tokio::sync::mpsc::UnboundedReceiver<crate::providers::ProviderEmission>,
+    tokio::sync::mpsc::Sender<ProviderInputMsg>,
+    tokio::task::JoinHandle<()>,
+  ) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(replay_server(listener, send_events));
+
+    let (emit_tx, emit_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (input_tx, async_rx) = tokio::sync::mpsc::channel(1);
+    let (_, sync_rx) = crossbeam::channel::bounded(1);
+
+    let common = CommonProviderState {
+      emitter: ProviderEmitter::new(emit_tx, "test".into()),
+      input: ProviderInput { async_rx, sync_rx },
+      sysinfo: Default::default(),
+    };
+
+    let task = tokio::spawn(async move {
+      GlazeWmProvider::new(GlazeWmProviderConfig { ipc_port: port }, common)
+        .start_async()
+        .await;
+    });
+
+    (emit_rx, input_tx, task)
+  }
+
+  fn output(emission: crate::providers::ProviderEmission) -> GlazeWmOutput {
+    match emission.result.unwrap() {
+      crate::providers::ProviderOutput::Glazewm(output) => output,
+      _ => panic!("Unexpected provider output."),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_replayed_state() {
+    let (mut emit_rx, input_tx, task) = start_provider(false).await;
+
+    let emission = emit_rx.recv().await.unwrap();
+    input_tx.send(ProviderInputMsg::Stop).await.unwrap();
+    task.await.unwrap();
+
+    let output = output(emission);
+
+    assert_eq!(output.all_workspaces.len(), 2);
+    assert_eq!(output.focused_workspace.unwrap()["id"], "w2");
+    assert_eq!(output.focused_monitor.unwrap()["id"], "m1");
+    assert_eq!(output.focused_container.unwrap()["id"], "c1");
+    assert_eq!(output.binding_modes.len(), 1);
+  }
+
+  #[tokio::test]
+  async fn test_requery_on_event_during_queries() {
+    let (mut emit_rx, input_tx, task) = start_provider(true).await;
+
+    let first = output(emit_rx.recv().await.unwrap());
+    assert_eq!(first.focused_workspace.unwrap()["id"], "w2");
+
+    // The second round of queries is outdated by the event received while
+    // it was in flight, so only the third round is emitted.
+    let second = output(emit_rx.recv().await.unwrap());
+    assert_eq!(second.focused_workspace.unwrap()["id"], "w1");
+
+    input_tx.send(ProviderInputMsg::Stop).await.unwrap();
+    task.await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_run_command() {
+    let (mut emit_rx, input_tx, task) = start_provider(false).await;
+    emit_rx.recv().await.unwrap();
+
+    let run_command = |command: &str, subject_container_id: Option<&str>| {
+      let input_tx = input_tx.clone();
+      let function = ProviderFunction::Glazewm(GlazeWmFunction::RunCommand(
+        crate::providers::GlazeWmRunCommandArgs {
+          command: command.into(),
+          subject_container_id: subject_container_id.map(Into::into),
+        },
+      ));
+
+      async move {
+        let (tx, rx) = oneshot::channel();
+        input_tx
+          .send(ProviderInputMsg::Function(function, tx))
+          .await
+          .unwrap();
+        rx.await.unwrap()
+      }
+    };
+
+    assert!(matches!(
+      run_command("focus --workspace 1", Some("c1")).await,
+      Ok(ProviderFunctionResponse::Null)
+    ));
+
+    assert_eq!(
+      run_command("fail", None).await.unwrap_err(),
+      "Invalid command."
+    );
+
+    input_tx.send(ProviderInputMsg::Stop).await.unwrap();
+    task.await.unwrap();
+  }
+}
@@ -0,0 +1,3 @@
+mod glazewm_provider;
+
+pub use glazewm_provider::*;
@@ -0,0 +1,371 @@
+use std::{
+  io::{BufRead, BufReader, Write},
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  thread,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use anyhow::Context;
+use crossbeam::channel::{unbounded, Receiver, Sender};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+#[cfg(windows)]
+use uds_windows::{UnixListener, UnixStream};
+
+use crate::providers::{
+  CommonProviderState, KomorebiFunction, Provider, ProviderFunction, ProviderFunctionResponse,
+  ProviderInputMsg, RuntimeType,
+};
+
+/// Name of the socket that komorebi listens on for commands.
+const KOMOREBI_SOCKET: &str = "komorebi.sock";
+
+/// Prefix of the sockets that komorebi writes notifications to. Each
+/// provider instance gets its own socket, so that instances don't remove
+/// each other's.
+const SUBSCRIBER_SOCKET_PREFIX: &str = "zebar";
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct KomorebiProviderConfig {
+  /// Directory containing komorebi's sockets. Defaults to komorebi's
+  /// data directory (i.e. `%LOCALAPPDATA%/komorebi`).
+  pub socket_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KomorebiOutput {
+  pub all_monitors: Vec<Value>,
+  pub focused_monitor_index: usize,
+  pub focused_monitor: Option<Value>,
+  pub focused_workspace: Option<Value>,
+  pub last_event: Option<Value>,
+}
+
+/// Notification written by komorebi to subscriber sockets.
+#[derive(Deserialize, Debug)]
+struct KomorebiNotification {
+  event: Value,
+  state: Value,
+}
+
+impl From<KomorebiNotification> for KomorebiOutput {
+  fn from(notification: KomorebiNotification) -> Self {
+    let monitors = &notification.state["monitors"];
+
+    let all_monitors = monitors["elements"].as_array().cloned().unwrap_or_default();
+
+    let focused_monitor_index = monitors["focused"].as_u64().unwrap_or(0) as usize;
+    let focused_monitor = all_monitors.get(focused_monitor_index).cloned();
+
+    let focused_workspace = focused_monitor.as_ref().and_then(|monitor| {
+      let workspaces = &monitor["workspaces"];
+      let index = workspaces["focused"].as_u64()? as usize;
+      workspaces["elements"].get(index).cloned()
+    });
+
+    KomorebiOutput {
+      all_monitors,
+      focused_monitor_index,
+      focused_monitor,
+      focused_workspace,
+      last_event: Some(notification.event),
+    }
+  }
+}
+
+pub struct KomorebiProvider {
+  config: KomorebiProviderConfig,
+  common: CommonProviderState,
+
+  /// File name of this instance's subscriber socket.
+  subscriber_socket: String,
+
+  /// Set on stop, so that the thread accepting notifications exits.
+  is_stopped: Arc<AtomicBool>,
+}
+
+impl KomorebiProvider {
+  pub fn new(config: KomorebiProviderConfig, common: CommonProviderState) -> KomorebiProvider {
+    KomorebiProvider {
+      config,
+      common,
+      subscriber_socket: format!(
+        "{}-{}.sock",
+        SUBSCRIBER_SOCKET_PREFIX,
+        uuid::Uuid::new_v4().simple()
+      ),
+      is_stopped: Arc::new(AtomicBool::new(false)),
+    }
+  }
+
+  /// Returns the directory containing komorebi's sockets.
+  fn socket_dir(&self) -> anyhow::Result<PathBuf> {
+    match &self.config.socket_dir {
+      Some(dir) => Ok(dir.clone()),
+      None => Ok(
+        dirs::data_local_dir()
+          .context("Unable to get local data directory.")?
+          .join("komorebi"),
+      ),
+    }
+  }
+
+  /// Creates the subscriber socket and registers it with komorebi.
+  ///
+  /// Returns a receiver for incoming notifications.
+  fn subscribe(&self, socket_dir: &PathBuf) -> anyhow::Result<Receiver<KomorebiNotification>> {
+    let subscriber_path = socket_dir.join(&self.subscriber_socket);
+
+    let listener = UnixListener::bind(&subscriber_path)
+      .with_context(|| format!("Failed to bind to {}.", subscriber_path.display()))?;
+
+    Self::send_message(
+      socket_dir,
+      &serde_json::json!({ "type": "AddSubscriberSocket", "content": self.subscriber_socket }),
+    )?;
+
+    let (notification_tx, notification_rx) = unbounded();
+    let is_stopped = self.is_stopped.clone();
+
+    thread::spawn(move || Self::accept_notifications(listener, notification_tx, is_stopped));
+
+    Ok(notification_rx)
+  }
+
+  /// Unregisters the subscriber socket from komorebi, stops the thread
+  /// accepting notifications, and removes the socket.
+  fn unsubscribe(&self, socket_dir: &PathBuf) {
+    let subscriber_path = socket_dir.join(&self.subscriber_socket);
+
+    let _ = Self::send_message(
+      socket_dir,
+      &serde_json::json!({ "type": "RemoveSubscriberSocket", "content": self.subscriber_socket }),
+    );
+
+    self.is_stopped.store(true, Ordering::SeqCst);
+    let _ = UnixStream::connect(&subscriber_path);
+    let _ = std::fs::remove_file(subscriber_path);
+  }
+
+  /// Accepts each connection that komorebi opens to the subscriber
+  /// socket, and reads it on its own thread. This way, a connection
+  /// that's kept open doesn't block accepting the one made on stop.
+  fn accept_notifications(
+    listener: UnixListener,
+    notification_tx: Sender<KomorebiNotification>,
+    is_stopped: Arc<AtomicBool>,
+  ) {
+    for stream in listener.incoming() {
+      // On stop, a connection is made to unblock the accept.
+      if is_stopped.load(Ordering::SeqCst) {
+        return;
+      }
+
+      let Ok(stream) = stream else {
+        continue;
+      };
+
+      let notification_tx = notification_tx.clone();
+      thread::spawn(move || Self::read_notifications(stream, notification_tx));
+    }
+  }
+
+  /// Reads newline-delimited notifications from a connection until it's
+  /// closed or the provider has stopped.
+  fn read_notifications(stream: UnixStream, notification_tx: Sender<KomorebiNotification>) {
+    for line in BufReader::new(stream).lines() {
+      let Ok(line) = line else {
+        return;
+      };
+
+      match serde_json::from_str::<KomorebiNotification>(&line) {
+        Ok(notification) => {
+          // The receiver is dropped once the provider has stopped.
+          if notification_tx.send(notification).is_err() {
+            return;
+          }
+        }
+        Err(err) => warn!("Unrecognized komorebi notification: {}", err),
+      }
+    }
+  }
+
+  /// Writes a socket message to komorebi's command socket.
+  fn send_message(socket_dir: &PathBuf, message: &Value) -> anyhow::Result<()> {
+    let socket_path = socket_dir.join(KOMOREBI_SOCKET);
+
+    let mut stream = UnixStream::connect(&socket_path).with_context(|| {
+      format!(
+        "Failed to connect to komorebi at {}.",
+        socket_path.display()
+      )
+    })?;
+
+    stream.write_all(message.to_string().as_bytes())?;
+
+    Ok(())
+  }
+
+  fn handle_function(
+    socket_dir: &PathBuf,
+    function: KomorebiFunction,
+  ) -> anyhow::Result<ProviderFunctionResponse> {
+    match function {
+      KomorebiFunction::RunCommand(args) => Self::send_message(socket_dir, &args.command)?,
+    };
+
+    Ok(ProviderFunctionResponse::Null)
+  }
+}
+
+impl Provider for KomorebiProvider {
+  fn runtime_type(&self) -> RuntimeType {
+    RuntimeType::Sync
+  }
+
+  fn start_sync(&mut self) {
+    let subscription = self
+      .socket_dir()
+      .and_then(|dir| self.subscribe(&dir).map(|rx| (dir, rx)));
+
+    let (socket_dir, notification_rx) = match subscription {
+      Ok(subscription) => subscription,
+      Err(err) => {
+        self.common.emitter.emit_output::<KomorebiOutput>(Err(err));
+        return;
+      }
+    };
+
+    loop {
+      crossbeam::select! {
+        recv(notification_rx) -> notification => {
+          if let Ok(notification) = notification {
+            debug!("Received komorebi event: {:?}", notification.event);
+
+            self
+              .common
+              .emitter
+              .emit_output_cached(Ok(KomorebiOutput::from(notification)));
+          }
+        }
+        recv(self.common.input.sync_rx) -> input => {
+          match input {
+            Ok(ProviderInputMsg::Stop) => {
+              break;
+            }
+            Ok(ProviderInputMsg::Function(
+              ProviderFunction::Komorebi(komorebi_function),
+              sender,
+            )) => {
+              let res = Self::handle_function(&socket_dir, komorebi_function)
+                .map_err(|err| err.to_string());
+
+              // The caller might no longer be waiting for a response.
+              let _ = sender.send(res);
+            }
+            _ => {}
+          }
+        }
+      }
+    }
+
+    self.unsubscribe(&socket_dir);
+  }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use super::*;
+  use crate::providers::{ProviderEmitter, ProviderInput, ProviderOutput};
+
+  #[test]
+  fn test_replayed_notification() {
+    let socket_dir = std::env::temp_dir().join(format!("zebar-komorebi-{}", std::process::id()));
+    std::fs::create_dir_all(&socket_dir).unwrap();
+
+    // Stand-in for komorebi that replays a recorded notification once a
+    // subscriber registers itself. The connection is kept open, like
+    // komorebi does, which mustn't block stopping the provider.
+    let command_listener = UnixListener::bind(socket_dir.join(KOMOREBI_SOCKET)).unwrap();
+    let replay_dir = socket_dir.clone();
+    let (subscriber_tx, subscriber_rx) = std::sync::mpsc::channel();
+
+    thread::spawn(move || {
+      let (stream, _) = command_listener.accept().unwrap();
+      let message: Value = serde_json::from_reader(stream).unwrap();
+      assert_eq!(message["type"], "AddSubscriberSocket");
+
+      let mut subscriber =
+        UnixStream::connect(replay_dir.join(message["content"].as_str().unwrap())).unwrap();
+
+      let notification = serde_json::json!({
+        "event": { "type": "FocusWorkspaceNumber", "content": 1 },
+        "state": {
+          "monitors": {
+            "elements": [{
+              "name": "DISPLAY1",
+              "workspaces": {
+                "elements": [{ "name": "I" }, { "name": "II" }],
+                "focused": 1,
+              },
+            }],
+            "focused": 0,
+          },
+        },
+      });
+
+      writeln!(subscriber, "{}", notification).unwrap();
+      subscriber_tx.send(subscriber).unwrap();
+    });
+
+    let (emit_tx, mut emit_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (_, async_rx) = tokio::sync::mpsc::channel(1);
+    let (input_tx, sync_rx) = crossbeam::channel::bounded(1);
+
+    let common = CommonProviderState {
+      emitter: ProviderEmitter::new(emit_tx, "test".into()),
+      input: ProviderInput { async_rx, sync_rx },
+      sysinfo: Default::default(),
+    };
+
+    let config = KomorebiProviderConfig {
+      socket_dir: Some(socket_dir.clone()),
+    };
+
+    let provider_thread = thread::spawn(move || KomorebiProvider::new(config, common).start_sync());
+
+    let emission = emit_rx.blocking_recv().unwrap();
+    input_tx.send(ProviderInputMsg::Stop).unwrap();
+    provider_thread.join().unwrap();
+
+    // The subscriber socket is removed on stop.
+    let is_socket_removed = std::fs::read_dir(&socket_dir).unwrap().all(|entry| {
+      !entry
+        .unwrap()
+        .file_name()
+        .to_string_lossy()
+        .starts_with("zebar")
+    });
+
+    drop(subscriber_rx.recv().unwrap());
+    let _ = std::fs::remove_dir_all(&socket_dir);
+    assert!(is_socket_removed);
+
+    let ProviderOutput::Komorebi(output) = emission.result.unwrap() else {
+      panic!("Unexpected provider output.");
+    };
+
+    assert_eq!(output.all_monitors.len(), 1);
+    assert_eq!(output.focused_monitor.unwrap()["name"], "DISPLAY1");
+    assert_eq!(output.focused_workspace.unwrap()["name"], "II");
+  }
+}
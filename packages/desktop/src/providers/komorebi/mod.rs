@@ -0,0 +1,3 @@
+mod komorebi_provider;
+
+pub use komorebi_provider::*;
@@ -1,7 +1,9 @@
 mod audio;
 mod battery;
 mod cpu;
+mod glazewm;
 mod gpu;
+mod komorebi;
 #[cfg(windows)]
 mod media;
 mod memory;
@@ -12,6 +14,9 @@ mod provider_function;
 mod provider_manager;
 mod provider_output;
 mod systray;
+#[cfg(any(windows, target_os = "linux"))]
+mod taskbar;
+#[cfg(any(windows, target_os = "linux"))]
 mod window;
 
 pub use provider::*;
@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use serde::Serialize;
 
 #[async_trait]
 pub trait Provider {
@@ -38,6 +39,8 @@ pub trait Provider {
 }
 
 /// Determines whether `start_sync` or `start_async` is called.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum RuntimeType {
   Sync,
   Async,
@@ -3,12 +3,14 @@ use serde::Deserialize;
 #[cfg(windows)]
 use super::{
   audio::AudioProviderConfig, media::MediaProviderConfig, systray::SystrayProviderConfig,
-  window::WindowProviderConfig,
 };
 use super::{
-  battery::BatteryProviderConfig, cpu::CpuProviderConfig, gpu::GpuProviderConfig,
-  memory::MemoryProviderConfig, network::NetworkProviderConfig,
+  battery::BatteryProviderConfig, cpu::CpuProviderConfig, glazewm::GlazeWmProviderConfig,
+  gpu::GpuProviderConfig, komorebi::KomorebiProviderConfig, memory::MemoryProviderConfig,
+  network::NetworkProviderConfig,
 };
+#[cfg(any(windows, target_os = "linux"))]
+use super::{taskbar::TaskbarProviderConfig, window::WindowProviderConfig};
 
 #[derive(Deserialize, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -17,10 +19,15 @@ pub enum ProviderConfig {
   Audio(AudioProviderConfig),
   Battery(BatteryProviderConfig),
   Cpu(CpuProviderConfig),
+  Glazewm(GlazeWmProviderConfig),
   Gpu(GpuProviderConfig),
+  Komorebi(KomorebiProviderConfig),
   Media(MediaProviderConfig),
   Memory(MemoryProviderConfig),
   Network(NetworkProviderConfig),
   Systray(SystrayProviderConfig),
+  #[cfg(any(windows, target_os = "linux"))]
+  Taskbar(TaskbarProviderConfig),
+  #[cfg(any(windows, target_os = "linux"))]
   Window(WindowProviderConfig),
 }
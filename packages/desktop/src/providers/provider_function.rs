@@ -4,8 +4,11 @@ use serde::{Deserialize, Serialize};
 #[serde(tag = "type", content = "function", rename_all = "snake_case")]
 pub enum ProviderFunction {
   Audio(AudioFunction),
+  Glazewm(GlazeWmFunction),
+  Komorebi(KomorebiFunction),
   Media(MediaFunction),
   Systray(SystrayFunction),
+  Taskbar(TaskbarFunction),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +23,37 @@ pub struct SetVolumeArgs {
   pub device_id: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "name", content = "args", rename_all = "snake_case")]
+pub enum GlazeWmFunction {
+  RunCommand(GlazeWmRunCommandArgs),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlazeWmRunCommandArgs {
+  /// WM command to run (e.g. `focus --workspace 1`).
+  pub command: String,
+
+  /// ID of the container to run the command on. Defaults to the focused
+  /// container.
+  pub subject_container_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "name", content = "args", rename_all = "snake_case")]
+pub enum KomorebiFunction {
+  RunCommand(KomorebiRunCommandArgs),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KomorebiRunCommandArgs {
+  /// Socket message to send to komorebi (e.g.
+  /// `{ "type": "FocusWorkspaceNumber", "content": 1 }`).
+  pub command: serde_json::Value,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "name", content = "args", rename_all = "snake_case")]
 pub enum MediaFunction {
@@ -53,6 +87,22 @@ pub struct SystrayIconArgs {
   pub icon_id: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "name", content = "args", rename_all = "snake_case")]
+pub enum TaskbarFunction {
+  Focus(TaskbarWindowArgs),
+  Minimize(TaskbarWindowArgs),
+  Maximize(TaskbarWindowArgs),
+  Restore(TaskbarWindowArgs),
+  Close(TaskbarWindowArgs),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskbarWindowArgs {
+  pub window_id: isize,
+}
+
 pub type ProviderFunctionResult = Result<ProviderFunctionResponse, String>;
 
 #[derive(Debug, Clone, Serialize)]
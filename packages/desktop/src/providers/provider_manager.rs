@@ -10,14 +10,15 @@ use tokio::{
 use tracing::info;
 
 #[cfg(windows)]
+use super::{audio::AudioProvider, media::MediaProvider, systray::SystrayProvider};
 use super::{
-  audio::AudioProvider, media::MediaProvider, systray::SystrayProvider, window::WindowProvider,
-};
-use super::{
-  battery::BatteryProvider, cpu::CpuProvider, gpu::GpuProvider, memory::MemoryProvider,
-  network::NetworkProvider, Provider, ProviderConfig, ProviderFunction, ProviderFunctionResponse,
-  ProviderFunctionResult, ProviderOutput, RuntimeType,
+  battery::BatteryProvider, cpu::CpuProvider, glazewm::GlazeWmProvider, gpu::GpuProvider,
+  komorebi::KomorebiProvider, memory::MemoryProvider, network::NetworkProvider, Provider,
+  ProviderConfig, ProviderFunction, ProviderFunctionResponse, ProviderFunctionResult,
+  ProviderOutput, RuntimeType,
 };
+#[cfg(any(windows, target_os = "linux"))]
+use super::{taskbar::TaskbarProvider, window::WindowProvider};
 
 /// Common fields for a provider.
 pub struct CommonProviderState {
@@ -60,6 +61,15 @@ pub struct ProviderEmitter {
 }
 
 impl ProviderEmitter {
+  /// Creates a new emitter for the provider with the given config hash.
+  pub fn new(emit_tx: mpsc::UnboundedSender<ProviderEmission>, config_hash: String) -> Self {
+    Self {
+      emit_tx,
+      config_hash,
+      prev_emission: None,
+    }
+  }
+
   fn emit(&self, emission: ProviderEmission) {
     let send_res = self.emit_tx.send(emission);
 
@@ -112,6 +122,20 @@ pub struct ProviderEmission {
   pub result: Result<ProviderOutput, String>,
 }
 
+/// Summary of an active provider, as output by `zebar query providers`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderSummary {
+  /// Hash of the provider's config.
+  pub config_hash: String,
+
+  /// Runtime type of the provider.
+  pub runtime_type: RuntimeType,
+
+  /// Most recent emission of the provider, if any.
+  pub last_emission: Option<ProviderEmission>,
+}
+
 /// Reference to an active provider.
 struct ProviderRef {
   /// Sender channel for sending inputs to the provider.
@@ -200,11 +224,7 @@ impl ProviderManager {
         async_rx: async_input_rx,
         sync_rx: sync_input_rx,
       },
-      emitter: ProviderEmitter {
-        emit_tx: self.emit_tx.clone(),
-        config_hash: config_hash.clone(),
-        prev_emission: None,
-      },
+      emitter: ProviderEmitter::new(self.emit_tx.clone(), config_hash.clone()),
       sysinfo: self.sysinfo.clone(),
     };
 
@@ -230,7 +250,10 @@ impl ProviderManager {
     common: CommonProviderState,
   ) -> anyhow::Result<(task::JoinHandle<()>, RuntimeType)> {
     let runtime_type = match config {
-      #[cfg(windows)]
+      ProviderConfig::Glazewm(..) => RuntimeType::Async,
+      #[cfg(any(windows, target_os = "linux"))]
+      ProviderConfig::Taskbar(..) => RuntimeType::Async,
+      #[cfg(any(windows, target_os = "linux"))]
       ProviderConfig::Window(..) => RuntimeType::Async,
       #[cfg(windows)]
       ProviderConfig::Systray(..) => RuntimeType::Async,
@@ -241,12 +264,21 @@ impl ProviderManager {
     let task_handle = match &runtime_type {
       RuntimeType::Async => task::spawn(async move {
         match config {
+          ProviderConfig::Glazewm(config) => {
+            let mut provider = GlazeWmProvider::new(config, common);
+            provider.start_async().await;
+          }
           #[cfg(windows)]
           ProviderConfig::Systray(config) => {
             let mut provider = SystrayProvider::new(config, common);
             provider.start_async().await;
           }
-          #[cfg(windows)]
+          #[cfg(any(windows, target_os = "linux"))]
+          ProviderConfig::Taskbar(config) => {
+            let mut provider = TaskbarProvider::new(config, common);
+            provider.start_async().await;
+          }
+          #[cfg(any(windows, target_os = "linux"))]
           ProviderConfig::Window(config) => {
             let mut provider = WindowProvider::new(config, common);
             provider.start_async().await;
@@ -275,6 +307,10 @@ impl ProviderManager {
             let mut provider = GpuProvider::new(config, common);
             provider.start_sync();
           }
+          ProviderConfig::Komorebi(config) => {
+            let mut provider = KomorebiProvider::new(config, common);
+            provider.start_sync();
+          }
           #[cfg(windows)]
           ProviderConfig::Media(config) => {
             let mut provider = MediaProvider::new(config, common);
@@ -381,6 +417,21 @@ impl ProviderManager {
     let mut cache = self.emit_cache.lock().await;
     cache.insert(emission.config_hash.clone(), emission);
   }
+
+  /// Returns a summary of all active providers.
+  pub async fn summaries(&self) -> Vec<ProviderSummary> {
+    let provider_refs = self.provider_refs.lock().await;
+    let emit_cache = self.emit_cache.lock().await;
+
+    provider_refs
+      .iter()
+      .map(|(config_hash, provider_ref)| ProviderSummary {
+        config_hash: config_hash.clone(),
+        runtime_type: provider_ref.runtime_type,
+        last_emission: emit_cache.get(config_hash).cloned(),
+      })
+      .collect()
+  }
 }
 
 /// Custom serializer for Result<ProviderOutput, String> that converts:
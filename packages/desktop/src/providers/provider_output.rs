@@ -1,11 +1,13 @@
 use serde::Serialize;
 
 #[cfg(windows)]
-use super::{audio::AudioOutput, media::MediaOutput, systray::SystrayOutput, window::WindowOutput};
+use super::{audio::AudioOutput, media::MediaOutput, systray::SystrayOutput};
 use super::{
-  battery::BatteryOutput, cpu::CpuOutput, gpu::GpuOutput, memory::MemoryOutput,
-  network::NetworkOutput,
+  battery::BatteryOutput, cpu::CpuOutput, glazewm::GlazeWmOutput, gpu::GpuOutput,
+  komorebi::KomorebiOutput, memory::MemoryOutput, network::NetworkOutput,
 };
+#[cfg(any(windows, target_os = "linux"))]
+use super::{taskbar::TaskbarOutput, window::WindowOutput};
 
 /// Implements `From<T>` for `ProviderOutput` for each given variant.
 macro_rules! impl_provider_output {
@@ -27,18 +29,25 @@ pub enum ProviderOutput {
   Audio(AudioOutput),
   Battery(BatteryOutput),
   Cpu(CpuOutput),
+  Glazewm(GlazeWmOutput),
   Gpu(GpuOutput),
+  Komorebi(KomorebiOutput),
   Media(MediaOutput),
   Memory(MemoryOutput),
   Network(NetworkOutput),
   Systray(SystrayOutput),
+  #[cfg(any(windows, target_os = "linux"))]
+  Taskbar(TaskbarOutput),
+  #[cfg(any(windows, target_os = "linux"))]
   Window(WindowOutput),
 }
 
 impl_provider_output! {
   Battery(BatteryOutput),
   Cpu(CpuOutput),
+  Glazewm(GlazeWmOutput),
   Gpu(GpuOutput),
+  Komorebi(KomorebiOutput),
   Memory(MemoryOutput),
   Network(NetworkOutput),
 }
@@ -48,5 +57,10 @@ impl_provider_output! {
   Audio(AudioOutput),
   Media(MediaOutput),
   Systray(SystrayOutput),
+}
+
+#[cfg(any(windows, target_os = "linux"))]
+impl_provider_output! {
+  Taskbar(TaskbarOutput),
   Window(WindowOutput),
 }
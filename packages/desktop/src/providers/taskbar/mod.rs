@@ -0,0 +1,3 @@
+mod taskbar_provider;
+
+pub use taskbar_provider::*;
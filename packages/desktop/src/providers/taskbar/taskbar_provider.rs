@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use window_util::{ImageFormat, WindowAction, WindowInfo, WindowList, WindowListEvent};
+
+use crate::providers::{
+  CommonProviderState, Provider, ProviderFunction, ProviderFunctionResponse, ProviderInputMsg,
+  RuntimeType, TaskbarFunction,
+};
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskbarProviderConfig {}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskbarOutput {
+  /// All top-level windows, in the order they were opened.
+  pub windows: Vec<TaskbarWindow>,
+
+  /// Changes since the previous emission.
+  pub changes: Vec<TaskbarChange>,
+}
+
+#[derive(Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskbarWindow {
+  /// Window handle on Windows, or the X window ID on Linux.
+  pub id: isize,
+  pub title: String,
+  pub process_name: Option<String>,
+  pub process_path: Option<String>,
+  pub pid: Option<u32>,
+  pub icon_bytes: Option<Vec<u8>>,
+  pub monitor: Option<String>,
+  pub is_minimized: bool,
+  pub is_maximized: bool,
+  pub is_urgent: bool,
+}
+
+impl From<WindowInfo> for TaskbarWindow {
+  fn from(window: WindowInfo) -> Self {
+    TaskbarWindow {
+      id: window.id,
+      icon_bytes: window.to_image_format(ImageFormat::Png).ok(),
+      title: window.title,
+      process_name: window.process_name,
+      process_path: window
+        .process_path
+        .map(|path| path.to_string_lossy().into_owned()),
+      pid: window.pid,
+      monitor: window.monitor,
+      is_minimized: window.is_minimized,
+      is_maximized: window.is_maximized,
+      is_urgent: window.is_urgent,
+    }
+  }
+}
+
+impl std::fmt::Debug for TaskbarWindow {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "TaskbarWindow {{ id: {}, title: {} }}",
+      self.id, self.title
+    )
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskbarChange {
+  #[serde(rename = "type")]
+  pub kind: TaskbarChangeKind,
+  pub window_id: isize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskbarChangeKind {
+  Add,
+  Update,
+  Remove,
+}
+
+pub struct TaskbarProvider {
+  _config: TaskbarProviderConfig,
+  common: CommonProviderState,
+
+  /// Windows with their icons already converted to PNG, so that icons
+  /// are only converted when a window is added or updated.
+  windows: Vec<TaskbarWindow>,
+}
+
+impl TaskbarProvider {
+  pub fn new(_config: TaskbarProviderConfig, common: CommonProviderState) -> TaskbarProvider {
+    TaskbarProvider {
+      _config,
+      common,
+      windows: Vec::new(),
+    }
+  }
+
+  /// Applies an event from the `WindowList` to the output windows.
+  fn apply_event(&mut self, event: WindowListEvent) -> TaskbarChange {
+    match event {
+      WindowListEvent::WindowAdd(window) => {
+        let window_id = window.id;
+        self.windows.push(window.into());
+
+        TaskbarChange {
+          kind: TaskbarChangeKind::Add,
+          window_id,
+        }
+      }
+      WindowListEvent::WindowUpdate(window) => {
+        let window_id = window.id;
+
+        if let Some(found) = self.windows.iter_mut().find(|found| found.id == window_id) {
+          *found = window.into();
+        }
+
+        TaskbarChange {
+          kind: TaskbarChangeKind::Update,
+          window_id,
+        }
+      }
+      WindowListEvent::WindowRemove(window_id) => {
+        self.windows.retain(|window| window.id != window_id);
+
+        TaskbarChange {
+          kind: TaskbarChangeKind::Remove,
+          window_id,
+        }
+      }
+    }
+  }
+
+  fn handle_function(
+    window_list: &WindowList,
+    function: TaskbarFunction,
+  ) -> anyhow::Result<ProviderFunctionResponse> {
+    match &function {
+      TaskbarFunction::Focus(args) => window_list.send_action(args.window_id, &WindowAction::Focus),
+      TaskbarFunction::Minimize(args) => {
+        window_list.send_action(args.window_id, &WindowAction::Minimize)
+      }
+      TaskbarFunction::Maximize(args) => {
+        window_list.send_action(args.window_id, &WindowAction::Maximize)
+      }
+      TaskbarFunction::Restore(args) => {
+        window_list.send_action(args.window_id, &WindowAction::Restore)
+      }
+      TaskbarFunction::Close(args) => window_list.send_action(args.window_id, &WindowAction::Close),
+    }?;
+
+    Ok(ProviderFunctionResponse::Null)
+  }
+}
+
+#[async_trait]
+impl Provider for TaskbarProvider {
+  fn runtime_type(&self) -> RuntimeType {
+    RuntimeType::Async
+  }
+
+  async fn start_async(&mut self) {
+    let mut window_list = match WindowList::new() {
+      Ok(window_list) => window_list,
+      Err(err) => {
+        self.common.emitter.emit_output::<TaskbarOutput>(Err(err));
+        return;
+      }
+    };
+
+    loop {
+      tokio::select! {
+        Some(event) = window_list.events() => {
+          let mut changes = vec![self.apply_event(event)];
+
+          // Emit bursts of events (e.g. the initial window list) at once.
+          while let Some(event) = window_list.try_events() {
+            changes.push(self.apply_event(event));
+          }
+
+          self.common.emitter.emit_output(Ok(TaskbarOutput {
+            windows: self.windows.clone(),
+            changes,
+          }));
+        }
+        Some(input) = self.common.input.async_rx.recv() => {
+          match input {
+            ProviderInputMsg::Stop => {
+              break;
+            }
+            ProviderInputMsg::Function(
+              ProviderFunction::Taskbar(taskbar_function),
+              sender,
+            ) => {
+              let res = Self::handle_function(&window_list, taskbar_function).map_err(|err| err.to_string());
+              sender.send(res).unwrap();
+            }
+            _ => {}
+          }
+        }
+      }
+    }
+  }
+}
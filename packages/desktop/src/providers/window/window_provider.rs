@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use window_util::Window;
+use window_util::{Window, WindowBounds};
 
 use crate::providers::{CommonProviderState, Provider, ProviderInputMsg, RuntimeType};
 
@@ -11,7 +11,22 @@ pub struct WindowProviderConfig {}
 #[serde(rename_all = "camelCase")]
 pub struct WindowOutput {
   pub title: String,
+
+  /// Window handle on Windows, or the X window ID on Linux.
   pub hwnd: isize,
+
+  pub class: Option<String>,
+  pub pid: Option<u32>,
+
+  /// File name of the executable that owns the window.
+  pub process_name: Option<String>,
+
+  pub bounds: Option<WindowBounds>,
+
+  /// Name of the monitor that the window is on.
+  pub monitor: Option<String>,
+
+  pub is_fullscreen: bool,
 }
 
 pub struct WindowProvider {
@@ -43,9 +58,17 @@ impl Provider for WindowProvider {
     loop {
       tokio::select! {
           Some(event) = window.events() => {
-              self.common.emitter.emit_output(Ok(WindowOutput {
+              // Moving a window emits many events, so only emit on
+              // actual changes.
+              self.common.emitter.emit_output_cached(Ok(WindowOutput {
                   title: event.title,
                   hwnd: event.hwnd,
+                  class: event.class,
+                  pid: event.pid,
+                  process_name: event.process_name,
+                  bounds: event.bounds,
+                  monitor: event.monitor,
+                  is_fullscreen: event.is_fullscreen,
               }));
           }
           Some(input) = self.common.input.async_rx.recv() => {
@@ -11,6 +11,9 @@ use tokio::sync::{mpsc, oneshot};
 
 use crate::widget_factory::WidgetFactory;
 
+/// Tauri window label of the dropdown menu window.
+const MENU_WINDOW_LABEL: &str = "macos";
+
 /// Handle for managing a spawned child process.
 #[derive(Debug)]
 pub struct ProcessHandle {
@@ -61,6 +64,10 @@ pub struct ShellState {
   app_handle: AppHandle,
   children: Arc<Mutex<HashMap<ProcessId, ProcessHandle>>>,
   widget_factory: Arc<WidgetFactory>,
+
+  /// ID of the widget that last opened the dropdown menu window, whose
+  /// shell privileges the menu inherits.
+  menu_owner: Mutex<Option<String>>,
 }
 
 impl ShellState {
@@ -70,9 +77,15 @@ impl ShellState {
       children: Arc::new(Mutex::new(HashMap::new())),
       app_handle: app_handle.clone(),
       widget_factory,
+      menu_owner: Mutex::new(None),
     }
   }
 
+  /// Sets the widget that opened the dropdown menu window.
+  pub fn set_menu_owner(&self, widget_id: &str) {
+    *self.menu_owner.lock().unwrap() = Some(widget_id.to_string());
+  }
+
   /// Executes a command as a child process.
   ///
   /// Validates widget's shell privileges before executing the command.
@@ -195,16 +208,21 @@ impl ShellState {
     program: &str,
     args: ShellCommandArgs,
   ) -> anyhow::Result<()> {
-    // Treat "dropdown" as if it were "macos"
-    let effective_widget_id = if widget_id == "macos" {
-      "widget-1"
-    } else {
-      widget_id
+    // The dropdown menu window runs commands on behalf of the widget
+    // that opened it.
+    let effective_widget_id = match widget_id {
+      MENU_WINDOW_LABEL => self
+        .menu_owner
+        .lock()
+        .unwrap()
+        .clone()
+        .context("Menu was not opened by a widget.")?,
+      _ => widget_id.to_string(),
     };
 
     let widget = self
       .widget_factory
-      .state_by_id(effective_widget_id)
+      .state_by_id(&effective_widget_id)
       .await
       .with_context(|| format!("Widget with ID '{widget_id}' not found."))?;
 
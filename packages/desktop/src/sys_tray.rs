@@ -13,14 +13,24 @@ use tracing::{error, info};
 
 use crate::{
   common::PathExt,
-  config::{Config, StartupConfig, WidgetConfig, WidgetPreset},
+  config::{Config, Profile, StartupConfig, WidgetConfig, WidgetPreset},
+  config_diagnostics::ConfigDiagnostic,
   widget_factory::{WidgetFactory, WidgetOpenOptions, WidgetState},
+  widget_pack::WidgetPack,
 };
 
+/// Maximum length of config error labels in the tray menu.
+const DIAGNOSTIC_LABEL_LENGTH: usize = 80;
+
+/// Label of the settings window, which is the only window allowed to
+/// manage widget packs.
+pub const SETTINGS_WINDOW_LABEL: &str = "settings";
+
 #[derive(Debug, Clone)]
 enum MenuEvent {
   ShowConfigFolder,
   ReloadConfigs,
+  UndoConfigChange,
   OpenSettings,
   Exit,
   EditWidget {
@@ -36,6 +46,18 @@ enum MenuEvent {
     preset: String,
     path: PathBuf,
   },
+  ToggleWidgetVisibility {
+    visible: bool,
+    preset: String,
+    path: PathBuf,
+  },
+  ResetWidgetPreset {
+    preset: String,
+    path: PathBuf,
+  },
+  SwitchProfile {
+    name: String,
+  },
 }
 
 impl ToString for MenuEvent {
@@ -43,6 +65,7 @@ impl ToString for MenuEvent {
     match self {
       MenuEvent::ShowConfigFolder => "show_config_folder".to_string(),
       MenuEvent::ReloadConfigs => "reload_configs".to_string(),
+      MenuEvent::UndoConfigChange => "undo_config_change".to_string(),
       MenuEvent::OpenSettings => "open_settings".to_string(),
       MenuEvent::Exit => "exit".to_string(),
       MenuEvent::EditWidget { path } => {
@@ -72,6 +95,28 @@ impl ToString for MenuEvent {
           path.to_unicode_string()
         )
       }
+      MenuEvent::ToggleWidgetVisibility {
+        visible,
+        preset,
+        path,
+      } => {
+        format!(
+          "toggle_widget_visibility_{}_{}_{}",
+          visible,
+          preset,
+          path.to_unicode_string()
+        )
+      }
+      MenuEvent::ResetWidgetPreset { preset, path } => {
+        format!(
+          "reset_widget_preset_{}_{}",
+          preset,
+          path.to_unicode_string()
+        )
+      }
+      MenuEvent::SwitchProfile { name } => {
+        format!("switch_profile_{}", name)
+      }
     }
   }
 }
@@ -85,6 +130,7 @@ impl FromStr for MenuEvent {
     match parts.as_slice() {
       ["show", "config", "folder"] => Ok(Self::ShowConfigFolder),
       ["reload", "configs"] => Ok(Self::ReloadConfigs),
+      ["undo", "config", "change"] => Ok(Self::UndoConfigChange),
       ["open", "settings"] => Ok(Self::OpenSettings),
       ["exit"] => Ok(Self::Exit),
       ["edit", "widget", path @ ..] => Ok(Self::EditWidget {
@@ -104,6 +150,20 @@ impl FromStr for MenuEvent {
           path: PathBuf::from(path.join("_")),
         })
       }
+      ["toggle", "widget", "visibility", visible @ ("true" | "false"), preset, path @ ..] => {
+        Ok(Self::ToggleWidgetVisibility {
+          visible: *visible == "true",
+          preset: preset.to_string(),
+          path: PathBuf::from(path.join("_")),
+        })
+      }
+      ["reset", "widget", "preset", preset, path @ ..] => Ok(Self::ResetWidgetPreset {
+        preset: preset.to_string(),
+        path: PathBuf::from(path.join("_")),
+      }),
+      ["switch", "profile", name @ ..] => Ok(Self::SwitchProfile {
+        name: name.join("_"),
+      }),
       _ => bail!("Invalid menu event: {}", event),
     }
   }
@@ -216,11 +276,16 @@ impl SysTray {
   /// Creates and returns the main system tray menu.
   async fn create_tray_menu(&self) -> anyhow::Result<Menu<Wry>> {
     let widget_configs = self.config.widget_configs().await;
+    let widget_packs = self.config.widget_packs().await;
     let widget_states = self.widget_factory.states_by_path().await;
     let startup_configs = self.config.startup_configs_by_path().await?;
 
-    let configs_menu =
-      self.create_configs_menu(&widget_configs, &widget_states, &startup_configs)?;
+    let configs_menu = self.create_configs_menu(
+      &widget_configs,
+      &widget_packs,
+      &widget_states,
+      &startup_configs,
+    )?;
 
     let mut tray_menu = MenuBuilder::new(&self.app_handle)
       .text(MenuEvent::OpenSettings, "Open settings")
@@ -235,8 +300,34 @@ impl SysTray {
         {
           "Empty cache & reload configs"
         }
-      })
-      .separator();
+      });
+
+    // Add option to revert the last config change made by Zebar.
+    if self.config.can_undo() {
+      tray_menu = tray_menu.text(MenuEvent::UndoConfigChange, "Undo last config change");
+    }
+
+    tray_menu = tray_menu.separator();
+
+    // Add submenu for switching between profiles.
+    let profiles = self.config.profiles().await;
+
+    if !profiles.is_empty() {
+      let active_profile = self.config.active_profile().await;
+
+      tray_menu = tray_menu
+        .item(&self.create_profiles_menu(&profiles, active_profile.as_deref())?)
+        .separator();
+    }
+
+    // Add submenu for config files that failed to validate.
+    let diagnostics = self.config.diagnostics.all();
+
+    if !diagnostics.is_empty() {
+      tray_menu = tray_menu
+        .item(&self.create_diagnostics_menu(&diagnostics)?)
+        .separator();
+    }
 
     // Add submenus for currently active widget.
     if !widget_states.is_empty() {
@@ -285,6 +376,7 @@ impl SysTray {
           widget_factory.clear_cache();
           config.reload().await
         }
+        MenuEvent::UndoConfigChange => config.undo().await.map(|_| ()),
         MenuEvent::OpenSettings => Self::open_settings_window(&app_handle, None),
         MenuEvent::Exit => {
           app_handle.exit(0);
@@ -311,6 +403,31 @@ impl SysTray {
           true => config.add_startup_config(&path, &preset).await,
           false => config.remove_startup_config(&path, &preset).await,
         },
+        MenuEvent::ToggleWidgetVisibility {
+          visible,
+          preset,
+          path,
+        } => match visible {
+          true => widget_factory.show_by_preset(&path, &preset).await,
+          false => widget_factory.hide_by_preset(&path, &preset).await,
+        },
+        MenuEvent::ResetWidgetPreset { preset, path } => {
+          let widget_ids = widget_factory
+            .states_by_path()
+            .await
+            .get(&path)
+            .map(|states| {
+              states
+                .iter()
+                .filter(|state| state.open_options == WidgetOpenOptions::Preset(preset.clone()))
+                .map(|state| state.id.clone())
+                .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+          widget_factory.reset_window_states(&widget_ids).await
+        }
+        MenuEvent::SwitchProfile { name } => widget_factory.switch_profile(&name).await,
       };
 
       if let Err(err) = event_res {
@@ -324,7 +441,7 @@ impl SysTray {
     config_path: Option<&PathBuf>,
   ) -> anyhow::Result<()> {
     // Get existing settings window if it's already open.
-    let settings_window = app_handle.get_webview_window("settings");
+    let settings_window = app_handle.get_webview_window(SETTINGS_WINDOW_LABEL);
 
     let route = match config_path {
       None => "/index.html".to_string(),
@@ -338,14 +455,18 @@ impl SysTray {
 
     match &settings_window {
       None => {
-        WebviewWindowBuilder::new(app_handle, "settings", WebviewUrl::App(route.into()))
-          .title("Settings - Zebar")
-          .focused(true)
-          .visible(true)
-          .inner_size(900., 600.)
-          .skip_taskbar(true)
-          .build()
-          .context("Failed to build the settings window.")?;
+        WebviewWindowBuilder::new(
+          app_handle,
+          SETTINGS_WINDOW_LABEL,
+          WebviewUrl::App(route.into()),
+        )
+        .title("Settings - Zebar")
+        .focused(true)
+        .visible(true)
+        .inner_size(900., 600.)
+        .skip_taskbar(true)
+        .build()
+        .context("Failed to build the settings window.")?;
 
         Ok(())
       }
@@ -363,21 +484,107 @@ impl SysTray {
     }
   }
 
-  /// Creates and returns a submenu for the widget configs.
+  /// Creates and returns a submenu for switching between profiles.
+  fn create_profiles_menu(
+    &self,
+    profiles: &[Profile],
+    active_profile: Option<&str>,
+  ) -> anyhow::Result<Submenu<Wry>> {
+    let mut profiles_menu = SubmenuBuilder::new(&self.app_handle, "Profiles");
+
+    for profile in profiles {
+      let profile_item = CheckMenuItem::with_id(
+        &self.app_handle,
+        MenuEvent::SwitchProfile {
+          name: profile.name.clone(),
+        },
+        &profile.name,
+        true,
+        active_profile == Some(profile.name.as_str()),
+        None::<&str>,
+      )?;
+
+      profiles_menu = profiles_menu.item(&profile_item);
+    }
+
+    Ok(profiles_menu.build()?)
+  }
+
+  /// Creates and returns a submenu listing config errors. Selecting an
+  /// error opens the settings window, where they're shown in full.
+  fn create_diagnostics_menu(
+    &self,
+    diagnostics: &[ConfigDiagnostic],
+  ) -> anyhow::Result<Submenu<Wry>> {
+    let mut diagnostics_menu = SubmenuBuilder::new(
+      &self.app_handle,
+      format!("Config errors ({})", diagnostics.len()),
+    );
+
+    for diagnostic in diagnostics {
+      let location = match (diagnostic.line, diagnostic.column) {
+        (Some(line), Some(column)) => format!(":{}:{}", line, column),
+        _ => String::new(),
+      };
+
+      let mut label = format!(
+        "{}{} {}",
+        self.config.formatted_widget_path(&diagnostic.path),
+        location,
+        diagnostic.message
+      );
+
+      if label.chars().count() > DIAGNOSTIC_LABEL_LENGTH {
+        label = label
+          .chars()
+          .take(DIAGNOSTIC_LABEL_LENGTH)
+          .collect::<String>()
+          + "…";
+      }
+
+      diagnostics_menu = diagnostics_menu.text(MenuEvent::OpenSettings, label);
+    }
+
+    Ok(diagnostics_menu.build()?)
+  }
+
+  /// Creates and returns a submenu for the widget configs. Configs that
+  /// belong to a widget pack are grouped under a submenu for the pack.
   fn create_configs_menu(
     &self,
     widget_configs: &HashMap<PathBuf, WidgetConfig>,
+    widget_packs: &[WidgetPack],
     widget_states: &HashMap<PathBuf, Vec<WidgetState>>,
     startup_configs: &HashMap<PathBuf, StartupConfig>,
   ) -> anyhow::Result<Submenu<Wry>> {
     let mut configs_menu = SubmenuBuilder::new(&self.app_handle, "Widget configs");
+    let mut pack_menus = HashMap::new();
 
-    // Add each widget config to the menu.
+    // Add each widget config to the menu, or to its pack's menu.
     for (config_path, widget_config) in widget_configs {
       let config_menu =
         self.create_config_menu(config_path, &widget_config, widget_states, startup_configs)?;
 
-      configs_menu = configs_menu.item(&config_menu);
+      match WidgetPack::find(widget_packs, config_path) {
+        Some(pack) => pack_menus
+          .entry(&pack.path)
+          .or_insert_with(Vec::new)
+          .push(config_menu),
+        None => configs_menu = configs_menu.item(&config_menu),
+      }
+    }
+
+    for pack in widget_packs {
+      if let Some(config_menus) = pack_menus.get(&pack.path) {
+        let mut pack_menu =
+          SubmenuBuilder::new(&self.app_handle, format!("{} v{}", pack.name, pack.version));
+
+        for config_menu in config_menus {
+          pack_menu = pack_menu.item(config_menu);
+        }
+
+        configs_menu = configs_menu.item(&pack_menu.build()?);
+      }
     }
 
     Ok(configs_menu.build()?)
@@ -411,18 +618,21 @@ impl SysTray {
 
     // Add each widget config to the menu.
     for preset in &widget_config.presets {
+      let preset_states = widget_states
+        .get(config_path)
+        .map(|states| {
+          states
+            .iter()
+            .filter(|state| state.open_options == WidgetOpenOptions::Preset(preset.name.clone()))
+            .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
       let preset_menu = self.create_preset_menu(
         config_path,
         &preset,
-        widget_states
-          .get(config_path)
-          .map(|states| {
-            states
-              .iter()
-              .filter(|state| state.open_options == WidgetOpenOptions::Preset(preset.name.clone()))
-              .count()
-          })
-          .unwrap_or(0),
+        preset_states.len(),
+        preset_states.iter().any(|state| state.visible),
         startup_configs.contains_key(config_path),
       )?;
 
@@ -438,6 +648,7 @@ impl SysTray {
     config_path: &PathBuf,
     preset: &WidgetPreset,
     preset_count: usize,
+    is_visible: bool,
     is_launched_on_startup: bool,
   ) -> anyhow::Result<Submenu<Wry>> {
     let enabled_item = CheckMenuItem::with_id(
@@ -471,10 +682,33 @@ impl SysTray {
       _ => &format!("({}) {}", preset_count, preset.name),
     };
 
-    let config_menu = SubmenuBuilder::new(&self.app_handle, label)
+    let mut config_menu = SubmenuBuilder::new(&self.app_handle, label)
       .item(&enabled_item)
       .item(&startup_item);
 
+    if preset_count > 0 {
+      let visible_item = CheckMenuItem::with_id(
+        &self.app_handle,
+        MenuEvent::ToggleWidgetVisibility {
+          visible: !is_visible,
+          preset: preset.name.clone(),
+          path: config_path.clone(),
+        },
+        "Visible",
+        true,
+        is_visible,
+        None::<&str>,
+      )?;
+
+      config_menu = config_menu.item(&visible_item).text(
+        MenuEvent::ResetWidgetPreset {
+          preset: preset.name.clone(),
+          path: config_path.clone(),
+        },
+        "Reset position",
+      );
+    }
+
     Ok(config_menu.build()?)
   }
 }
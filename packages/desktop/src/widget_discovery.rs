@@ -0,0 +1,245 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  config_backups::BACKUPS_DIR,
+  config_diagnostics::ConfigDiagnostics,
+  config_format::ConfigFormat,
+  widget_pack::{WidgetPack, PACK_MANIFEST_FILE},
+};
+
+/// Directories that are never searched for widget configs. A trailing
+/// `*` matches any suffix, e.g. for the `tmp-*` webview data directories
+/// that Zebar creates within the default config directory.
+pub const IGNORED_DIRS: [&str; 4] = ["node_modules", ".git", BACKUPS_DIR, "tmp-*"];
+
+/// Whether a directory with the given name is never searched for widget
+/// configs.
+pub fn is_ignored_dir(name: &str) -> bool {
+  IGNORED_DIRS
+    .iter()
+    .any(|pattern| match pattern.strip_suffix('*') {
+      Some(prefix) => name.starts_with(prefix),
+      None => name == *pattern,
+    })
+}
+
+/// How widget configs are searched for within the config directory.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WidgetDiscovery {
+  /// How many directories deep to search for widget configs. Configs
+  /// directly within the config directory are never included.
+  pub max_depth: usize,
+
+  /// Glob patterns of files and directories to skip (e.g. `dist` or
+  /// `archive/**`). Patterns containing a `/` are matched against the
+  /// path relative to the config directory, others against file names.
+  pub ignore: Vec<String>,
+}
+
+impl Default for WidgetDiscovery {
+  fn default() -> Self {
+    Self {
+      max_depth: 4,
+      ignore: Vec::new(),
+    }
+  }
+}
+
+/// Widget configs and packs found within a config directory.
+#[derive(Debug, Default)]
+pub struct DiscoveredWidgets {
+  pub config_paths: Vec<PathBuf>,
+  pub packs: Vec<WidgetPack>,
+}
+
+impl WidgetDiscovery {
+  pub fn is_default(&self) -> bool {
+    *self == Self::default()
+  }
+
+  /// Searches the given directory for widget configs and packs.
+  ///
+  /// Errors in pack manifests are reported to `diagnostics`. Configs of
+  /// packs with an invalid manifest are still found, whereas configs of
+  /// packs that need a newer version of Zebar are skipped.
+  pub fn discover(
+    &self,
+    dir: &Path,
+    diagnostics: &ConfigDiagnostics,
+  ) -> anyhow::Result<DiscoveredWidgets> {
+    let ignore = self
+      .ignore
+      .iter()
+      .map(|pattern| IgnorePattern::new(pattern))
+      .collect::<anyhow::Result<Vec<_>>>()?;
+
+    // Unlike subdirectories, an unreadable config directory is an error.
+    fs::read_dir(dir).with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+
+    let mut discovered = DiscoveredWidgets::default();
+    self.search(dir, dir, 0, &ignore, diagnostics, &mut discovered);
+
+    discovered.config_paths.sort();
+    discovered.packs.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(discovered)
+  }
+
+  fn search(
+    &self,
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    ignore: &[IgnorePattern],
+    diagnostics: &ConfigDiagnostics,
+    discovered: &mut DiscoveredWidgets,
+  ) {
+    // The config directory itself can't be a pack.
+    if depth > 0 && dir.join(PACK_MANIFEST_FILE).is_file() {
+      if let Ok(pack) = WidgetPack::read(dir, diagnostics) {
+        if !pack.is_compatible() {
+          diagnostics.report(
+            &pack.manifest_path(),
+            "/minZebarVersion",
+            pack.incompatible_message(),
+          );
+
+          return;
+        }
+
+        let listed_paths = pack.listed_widget_paths();
+        discovered.packs.push(pack);
+
+        // Packs that list their widget configs aren't searched further.
+        if let Some(listed_paths) = listed_paths {
+          discovered.config_paths.extend(
+            listed_paths
+              .into_iter()
+              .filter(|path| path.is_file() && ConfigFormat::is_widget_path(path)),
+          );
+
+          return;
+        }
+      }
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+      return;
+    };
+
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+      if self.is_ignored(root, &path, ignore) {
+        continue;
+      }
+
+      if path.is_dir() {
+        if depth < self.max_depth {
+          self.search(root, &path, depth + 1, ignore, diagnostics, discovered);
+        }
+      } else if depth > 0 && ConfigFormat::is_widget_path(&path) {
+        discovered.config_paths.push(path);
+      }
+    }
+  }
+
+  fn is_ignored(&self, root: &Path, path: &Path, ignore: &[IgnorePattern]) -> bool {
+    let name = path
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string())
+      .unwrap_or_default();
+
+    // Ensure path delimiters are forward slashes on Windows.
+    let relative_path = path
+      .strip_prefix(root)
+      .unwrap_or(path)
+      .to_string_lossy()
+      .replace('\\', "/");
+
+    is_ignored_dir(&name)
+      || ignore.iter().any(|pattern| match pattern.is_path {
+        true => pattern.regex.is_match(&relative_path),
+        false => pattern.regex.is_match(&name),
+      })
+  }
+}
+
+/// A glob pattern from the `ignore` setting.
+struct IgnorePattern {
+  regex: Regex,
+
+  /// Whether the pattern is matched against relative paths rather than
+  /// file names.
+  is_path: bool,
+}
+
+impl IgnorePattern {
+  /// Converts a glob pattern into a regex. `**` matches across
+  /// directories, whereas `*` and `?` don't.
+  fn new(pattern: &str) -> anyhow::Result<Self> {
+    let pattern = pattern.trim_matches('/');
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(char) = chars.next() {
+      match char {
+        '*' if chars.peek() == Some(&'*') => {
+          chars.next();
+          regex.push_str(".*");
+        }
+        '*' => regex.push_str("[^/]*"),
+        '?' => regex.push_str("[^/]"),
+        _ => regex.push_str(&regex::escape(&char.to_string())),
+      }
+    }
+
+    regex.push('$');
+
+    Ok(Self {
+      regex: Regex::new(&regex)
+        .with_context(|| format!("Invalid ignore pattern '{}'.", pattern))?,
+      is_path: pattern.contains('/'),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_ignore_patterns() {
+    let discovery = WidgetDiscovery {
+      max_depth: 4,
+      ignore: vec![
+        "dist".into(),
+        "*.bak.zebar.json".into(),
+        "archive/**".into(),
+      ],
+    };
+
+    let ignore = discovery
+      .ignore
+      .iter()
+      .map(|pattern| IgnorePattern::new(pattern).unwrap())
+      .collect::<Vec<_>>();
+
+    let root = Path::new("/zebar");
+    let is_ignored = |path: &str| discovery.is_ignored(root, &root.join(path), &ignore);
+
+    assert!(is_ignored("repo/node_modules"));
+    assert!(is_ignored("tmp-L3plYmFy"));
+    assert!(is_ignored("repo/dist"));
+    assert!(is_ignored("repo/bar.bak.zebar.json"));
+    assert!(is_ignored("archive/old/bar.zebar.json"));
+    assert!(!is_ignored("repo/bar.zebar.json"));
+    assert!(!is_ignored("repo/archive/bar.zebar.json"));
+  }
+}
@@ -1,10 +1,11 @@
 use std::{
-  collections::HashMap,
+  collections::{HashMap, HashSet},
   path::PathBuf,
   sync::{
     atomic::{AtomicU32, Ordering},
     Arc,
   },
+  time::Duration,
 };
 
 use anyhow::{bail, Context};
@@ -18,8 +19,11 @@ use tokio::{
   sync::{broadcast, Mutex},
   task,
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use uuid::Uuid;
 
+#[cfg(target_os = "linux")]
+use crate::common::linux::WindowExtLinux;
 #[cfg(target_os = "macos")]
 use crate::common::macos::WindowExtMacOs;
 #[cfg(target_os = "windows")]
@@ -27,8 +31,9 @@ use crate::common::windows::{remove_app_bar, WindowExtWindows};
 use crate::{
   asset_server::create_init_url,
   common::PathExt,
-  config::{AnchorPoint, Config, DockConfig, DockEdge, WidgetConfig, WidgetPlacement},
-  monitor_state::{Monitor, MonitorState},
+  config::{AnchorPoint, Config, DockConfig, DockEdge, WidgetConfig, WidgetPlacement, ZOrder},
+  monitor_state::{Monitor, MonitorState, WorkArea},
+  window_state::WindowState,
 };
 
 /// Manages the creation of Zebar widgets.
@@ -48,17 +53,37 @@ pub struct WidgetFactory {
 
   pub open_tx: broadcast::Sender<WidgetState>,
 
+  _visibility_change_rx: broadcast::Receiver<WidgetState>,
+
+  pub visibility_change_tx: broadcast::Sender<WidgetState>,
+
   /// Reference to `MonitorState`.
   monitor_state: Arc<MonitorState>,
 
-  /// Running total of widgets created.
+  /// Running total of widget windows created.
   ///
-  /// Used to generate unique widget ID's which are used as Tauri window
-  /// labels.
-  widget_count: Arc<AtomicU32>,
+  /// Used to tell apart windows with the same widget ID, since ID's are
+  /// reused when a widget is reopened.
+  window_count: Arc<AtomicU32>,
+
+  /// Window bounds of widgets that were moved or resized by the user.
+  window_state: Arc<WindowState>,
 
   /// Map of widget ID's to their states.
   widget_states: Arc<Mutex<HashMap<String, WidgetState>>>,
+
+  /// Widget ID's of windows that are being created, reserved so that
+  /// concurrently opened widgets don't get the same ID.
+  pending_widget_ids: Mutex<HashSet<String>>,
+
+  /// Widgets that were opened while no monitor matched their monitor
+  /// selection, keyed by config path and open options.
+  unplaced_widgets: Mutex<Vec<(PathBuf, WidgetOpenOptions)>>,
+
+  /// Work areas expected after docked widgets reserved their space,
+  /// keyed by monitor ID. Used to tell apart work area changes caused by
+  /// other appbars from the ones caused by the widgets themselves.
+  dock_work_areas: std::sync::Mutex<HashMap<String, WorkArea>>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -85,6 +110,70 @@ pub struct WidgetState {
 
   /// How the widget was opened.
   pub open_options: WidgetOpenOptions,
+
+  /// Monitor that the widget is placed on.
+  pub monitor: Monitor,
+
+  /// Whether the widget is shown. Hidden widgets keep their webview and
+  /// in-page state.
+  pub visible: bool,
+
+  /// Instance of the underlying Tauri window.
+  #[serde(skip)]
+  window_instance: u32,
+
+  /// Size and position that the widget was last placed at.
+  #[serde(skip)]
+  placed_bounds: (PhysicalSize<i32>, PhysicalPosition<i32>),
+}
+
+impl WidgetState {
+  /// Returns the placement that the widget was opened with.
+  pub fn placement(&self) -> Option<&WidgetPlacement> {
+    match &self.open_options {
+      WidgetOpenOptions::Standalone(placement) => Some(placement),
+      WidgetOpenOptions::Preset(name) => self
+        .config
+        .presets
+        .iter()
+        .find(|preset| preset.name == *name)
+        .map(|preset| &preset.placement),
+    }
+  }
+
+  /// Returns the position (relative to the monitor) and size that
+  /// differ from where the widget was placed, i.e. that were changed by
+  /// the user.
+  ///
+  /// Adjustments to docked widgets are ignored, as are size changes of
+  /// widgets that aren't resizable.
+  fn user_adjustment(
+    &self,
+    position: Option<PhysicalPosition<i32>>,
+    size: Option<PhysicalSize<u32>>,
+  ) -> Option<(Option<PhysicalPosition<i32>>, Option<PhysicalSize<u32>>)> {
+    if self
+      .placement()
+      .map_or(true, |placement| placement.dock_to_edge.enabled)
+    {
+      return None;
+    }
+
+    let (placed_size, placed_position) = self.placed_bounds;
+
+    let position = position
+      .filter(|position| *position != placed_position)
+      .map(|position| {
+        PhysicalPosition::new(position.x - self.monitor.x, position.y - self.monitor.y)
+      });
+
+    let size = size.filter(|size| {
+      self.config.resizable
+        && (size.width as i32, size.height as i32) != (placed_size.width, placed_size.height)
+    });
+
+    (position.is_some() || size.is_some()).then_some((position, size))
+  }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize)]
@@ -154,6 +243,13 @@ impl WidgetFactory {
   ) -> Self {
     let (open_tx, _open_rx) = broadcast::channel(16);
     let (close_tx, _close_rx) = broadcast::channel(16);
+    let (visibility_change_tx, _visibility_change_rx) = broadcast::channel(16);
+
+    let widget_states = Arc::new(Mutex::new(HashMap::new()));
+    let window_state = Arc::new(WindowState::new(&config.config_dir));
+
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    Self::listen_window_events(app_handle.clone(), widget_states.clone());
 
     Self {
       app_handle: app_handle.clone(),
@@ -162,12 +258,95 @@ impl WidgetFactory {
       config,
       _open_rx,
       open_tx,
+      _visibility_change_rx,
+      visibility_change_tx,
       monitor_state,
-      widget_count: Arc::new(AtomicU32::new(0)),
-      widget_states: Arc::new(Mutex::new(HashMap::new())),
+      window_count: Arc::new(AtomicU32::new(0)),
+      window_state,
+      widget_states,
+      pending_widget_ids: Mutex::new(HashSet::new()),
+      unplaced_widgets: Mutex::new(Vec::new()),
+      dock_work_areas: std::sync::Mutex::new(HashMap::new()),
     }
   }
 
+  /// Reacts to changes of the foreground window.
+  ///
+  /// Widgets with `hide_on_fullscreen` enabled are hidden while a
+  /// fullscreen window is focused on their monitor, and top-most widgets
+  /// are raised again when another window takes focus.
+  #[cfg(any(target_os = "windows", target_os = "linux"))]
+  fn listen_window_events(
+    app_handle: AppHandle,
+    widget_states: Arc<Mutex<HashMap<String, WidgetState>>>,
+  ) {
+    task::spawn(async move {
+      let mut window = match window_util::Window::new() {
+        Ok(window) => window,
+        Err(err) => {
+          error!("Failed to listen for window events: {:?}", err);
+          return;
+        }
+      };
+
+      // Name of the monitor with a focused fullscreen window.
+      let mut fullscreen_monitor: Option<String> = None;
+
+      while let Some(event) = window.events().await {
+        let new_fullscreen_monitor = event.monitor.filter(|_| event.is_fullscreen);
+        let is_fullscreen_change = new_fullscreen_monitor != fullscreen_monitor;
+        let is_focus_change = event.kind == window_util::WindowEventKind::Focus;
+
+        if !is_fullscreen_change && !is_focus_change {
+          continue;
+        }
+
+        fullscreen_monitor = new_fullscreen_monitor;
+
+        // Windows are collected with whether to hide, show or raise them,
+        // and updated after the lock is released. Raising on Linux blocks
+        // on the main thread, which may itself be waiting on the lock.
+        let actions = widget_states
+          .lock()
+          .await
+          .values()
+          // Widgets hidden by the user stay hidden.
+          .filter(|state| state.visible)
+          .filter_map(|state| {
+            let widget_window = app_handle.get_webview_window(&state.id)?;
+
+            let hide_on_fullscreen = state
+              .placement()
+              .is_some_and(|placement| placement.hide_on_fullscreen);
+
+            let should_hide = hide_on_fullscreen
+              && fullscreen_monitor.is_some()
+              && state.monitor.name == fullscreen_monitor;
+
+            let visibility = (is_fullscreen_change && hide_on_fullscreen).then_some(!should_hide);
+
+            let should_raise =
+              is_focus_change && !should_hide && state.config.z_order == ZOrder::TopMost;
+
+            Some((widget_window, visibility, should_raise))
+          })
+          .collect::<Vec<_>>();
+
+        for (widget_window, visibility, should_raise) in actions {
+          let _ = match visibility {
+            Some(true) => widget_window.show(),
+            Some(false) => widget_window.hide(),
+            None => Ok(()),
+          };
+
+          if should_raise {
+            let _ = widget_window.as_ref().window().raise_top_most();
+          }
+        }
+      }
+    });
+  }
+
   /// Opens widget from a given config path.
   ///
   /// Config path must be absolute.
@@ -218,125 +397,336 @@ impl WidgetFactory {
       }
     };
 
-    for coordinates in self.widget_coordinates(placement).await {
-      let new_count = self.widget_count.fetch_add(1, Ordering::Relaxed) + 1;
+    let all_coordinates = self.widget_coordinates(placement).await;
 
-      // Use running widget count as a unique label for the Tauri window.
-      let widget_id = format!("widget-{}", new_count);
-
-      info!(
-        "Creating window for {} from {}",
-        widget_id,
-        config_path.display()
-      );
+    // Keep track of widgets without a matching monitor, so that they can
+    // be opened once one is connected.
+    {
+      let mut unplaced_widgets = self.unplaced_widgets.lock().await;
+      unplaced_widgets.retain(|(path, options)| !(*path == config_path && options == open_options));
 
-      let parent_dir = config_path.parent().context("No parent directory.")?;
+      if all_coordinates.is_empty() {
+        info!(
+          "No matching monitor for widget from {}.",
+          config_path.display()
+        );
 
-      let html_path = parent_dir.join(&widget_config.html_path);
+        unplaced_widgets.push((config_path.clone(), open_options.clone()));
+      }
+    }
 
-      if !html_path.exists() {
-        bail!(
-          "HTML file not found at '{}' for config '{}'.",
-          widget_config.html_path.display(),
-          config_path.display()
+    for coordinates in all_coordinates {
+      self
+        .open_widget_window(
+          &config_path,
+          &widget_config,
+          open_options,
+          placement,
+          coordinates,
         )
-      }
+        .await?;
+    }
 
-      let webview_url = WebviewUrl::External(create_init_url(&parent_dir, &html_path).await?);
+    Ok(())
+  }
 
-      let mut state = WidgetState {
-        id: widget_id.clone(),
-        window_handle: None,
-        config: widget_config.clone(),
-        config_path: config_path.clone(),
-        html_path: html_path.clone(),
-        open_options: open_options.clone(),
-      };
+  /// Creates the Tauri window for a widget on a single monitor.
+  async fn open_widget_window(
+    &self,
+    config_path: &PathBuf,
+    widget_config: &WidgetConfig,
+    open_options: &WidgetOpenOptions,
+    placement: &WidgetPlacement,
+    coordinates: WidgetCoordinates,
+  ) -> anyhow::Result<()> {
+    let widget_id = self
+      .unique_widget_id(config_path, open_options, &coordinates.monitor)
+      .await?;
+
+    let res = self
+      .create_widget_window(
+        widget_id.clone(),
+        config_path,
+        widget_config,
+        open_options,
+        placement,
+        coordinates,
+      )
+      .await;
 
-      // Widgets from the same top-level directory share their browser
-      // cache (i.e. `localStorage`, `sessionStorage`, SW cache, etc.).
-      let cache_id = BASE64_STANDARD.encode(parent_dir.to_unicode_string());
-
-      let window = WebviewWindowBuilder::new(&self.app_handle, widget_id.clone(), webview_url)
-        .title(format!(
-          "Zebar - {}",
-          self.config.formatted_widget_path(&config_path)
-        ))
-        .focused(widget_config.focused)
-        .skip_taskbar(!widget_config.shown_in_taskbar)
-        .visible_on_all_workspaces(true)
-        .transparent(widget_config.transparent)
-        .shadow(false)
-        .decorations(false)
-        .resizable(widget_config.resizable)
-        .initialization_script(&self.initialization_script(&state)?)
-        .data_directory(
-          // TODO: Add this as an ext method on the Tauri window.
-          self
-            .app_handle
-            .path()
-            .resolve(format!(".glzr/zebar/tmp-{}", cache_id), BaseDirectory::Home)
-            .context("Unable to get home directory.")
-            .unwrap(),
-        )
-        .build()?;
+    // The ID is either taken by the widget state now, or free again.
+    self.pending_widget_ids.lock().await.remove(&widget_id);
 
-      // Widget coordinates might be modified when docked to an edge.
-      let (size, position) = match placement.dock_to_edge.enabled {
-        false => (coordinates.size, coordinates.position),
-        true => self.dock_to_edge(&window, &placement.dock_to_edge, &coordinates)?,
-      };
+    res
+  }
 
-      info!("Positioning widget to {:?} {:?}", size, position);
-      let _ = window.set_size(size);
-      let _ = window.set_position(position);
+  /// Creates the window of a widget under an already reserved ID.
+  async fn create_widget_window(
+    &self,
+    widget_id: String,
+    config_path: &PathBuf,
+    widget_config: &WidgetConfig,
+    open_options: &WidgetOpenOptions,
+    placement: &WidgetPlacement,
+    coordinates: WidgetCoordinates,
+  ) -> anyhow::Result<()> {
+    let window_instance = self.window_count.fetch_add(1, Ordering::Relaxed) + 1;
+
+    // The window of a relaunched widget might still be closing, so wait
+    // for its close event. Subscribed before checking for the window, so
+    // that the event can't be missed.
+    let mut close_rx = self.close_tx.subscribe();
+
+    if self.app_handle.get_webview_window(&widget_id).is_some() {
+      let closed = async {
+        loop {
+          match close_rx.recv().await {
+            Ok(closed_id) if closed_id == widget_id => break,
+            Err(broadcast::error::RecvError::Closed) => break,
+            _ => {}
+          }
+        }
+      };
 
-      // On Windows, we need to set the position twice to account for
-      // different monitor scale factors.
-      #[cfg(target_os = "windows")]
+      if tokio::time::timeout(Duration::from_secs(1), closed)
+        .await
+        .is_err()
       {
-        let _ = window.set_size(size);
-        let _ = window.set_position(position);
+        warn!("Window of widget #{} didn't close in time.", widget_id);
       }
+    }
 
-      // On Windows, Tauri's `skip_taskbar` option isn't 100% reliable,
-      // so we also set the window as a tool window.
-      #[cfg(target_os = "windows")]
-      let _ = window
-        .as_ref()
-        .window()
-        .set_tool_window(!widget_config.shown_in_taskbar);
-
-      // On MacOS, we need to set the window as above the menu bar for it
-      // to truly be always on top.
-      #[cfg(target_os = "macos")]
-      {
-        if widget_config.z_order == crate::config::ZOrder::TopMost {
-          let _ = window.as_ref().window().set_above_menu_bar();
-        }
-      }
+    info!(
+      "Creating window for {} from {}",
+      widget_id,
+      config_path.display()
+    );
 
-      #[cfg(target_os = "windows")]
-      {
-        state.window_handle = {
-          let handle = window.hwnd().context("Failed to get window handle.")?;
+    let parent_dir = config_path.parent().context("No parent directory.")?;
 
-          Some(handle.0 as isize)
-        };
+    let html_path = parent_dir.join(&widget_config.html_path);
+
+    if !html_path.exists() {
+      bail!(
+        "HTML file not found at '{}' for config '{}'.",
+        widget_config.html_path.display(),
+        config_path.display()
+      )
+    }
+
+    let webview_url = WebviewUrl::External(create_init_url(&parent_dir, &html_path).await?);
+
+    let mut state = WidgetState {
+      id: widget_id.clone(),
+      window_handle: None,
+      config: widget_config.clone(),
+      config_path: config_path.clone(),
+      html_path: html_path.clone(),
+      open_options: open_options.clone(),
+      monitor: coordinates.monitor.clone(),
+      visible: true,
+      window_instance,
+      placed_bounds: (coordinates.size, coordinates.position),
+    };
+
+    // Widgets from the same top-level directory share their browser
+    // cache (i.e. `localStorage`, `sessionStorage`, SW cache, etc.).
+    let cache_id = BASE64_STANDARD.encode(parent_dir.to_unicode_string());
+
+    let window = WebviewWindowBuilder::new(&self.app_handle, widget_id.clone(), webview_url)
+      .title(format!(
+        "Zebar - {}",
+        self.config.formatted_widget_path(config_path)
+      ))
+      .focused(widget_config.focused)
+      .skip_taskbar(!widget_config.shown_in_taskbar)
+      .visible_on_all_workspaces(true)
+      .always_on_top(widget_config.z_order == ZOrder::TopMost)
+      .always_on_bottom(widget_config.z_order == ZOrder::BottomMost)
+      .transparent(widget_config.transparent)
+      .shadow(false)
+      .decorations(false)
+      .resizable(widget_config.resizable)
+      .initialization_script(&self.initialization_script(&state)?)
+      .data_directory(
+        // TODO: Add this as an ext method on the Tauri window.
+        self
+          .app_handle
+          .path()
+          .resolve(format!(".glzr/zebar/tmp-{}", cache_id), BaseDirectory::Home)
+          .context("Unable to get home directory.")
+          .unwrap(),
+      )
+      .build()?;
+
+    state.placed_bounds = self.placement_bounds(&window, placement, &coordinates)?;
+    Self::move_window(&window, state.placed_bounds);
+
+    self
+      .restore_saved_window(&window, &state, placement)
+      .await?;
+
+    // On Windows, Tauri's `skip_taskbar` option isn't 100% reliable,
+    // so we also set the window as a tool window.
+    #[cfg(target_os = "windows")]
+    let _ = window
+      .as_ref()
+      .window()
+      .set_tool_window(!widget_config.shown_in_taskbar);
+
+    // On MacOS, we need to set the window as above the menu bar for it
+    // to truly be always on top.
+    #[cfg(target_os = "macos")]
+    {
+      if widget_config.z_order == ZOrder::TopMost {
+        let _ = window.as_ref().window().set_above_menu_bar();
       }
+    }
 
-      {
-        let mut widget_states = self.widget_states.lock().await;
-        widget_states.insert(state.id.clone(), state.clone());
+    // Keep bottom-most widgets below windows that get activated, and
+    // visible when showing the desktop.
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    {
+      if widget_config.z_order == ZOrder::BottomMost {
+        if let Err(err) = window.as_ref().window().pin_to_desktop() {
+          error!("Failed to pin widget to desktop: {:?}", err);
+        }
       }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+      state.window_handle = {
+        let handle = window.hwnd().context("Failed to get window handle.")?;
 
-      self.register_window_events(&window, widget_id)?;
-      self.open_tx.send(state)?;
+        Some(handle.0 as isize)
+      };
     }
 
+    {
+      let mut widget_states = self.widget_states.lock().await;
+      widget_states.insert(state.id.clone(), state.clone());
+    }
+
+    self.register_window_events(&window, widget_id, window_instance)?;
+    self.open_tx.send(state)?;
+
     Ok(())
   }
 
+  /// Gets the size and position to place a widget window at, docking it
+  /// to an edge if enabled.
+  fn placement_bounds(
+    &self,
+    window: &tauri::WebviewWindow,
+    placement: &WidgetPlacement,
+    coordinates: &WidgetCoordinates,
+  ) -> anyhow::Result<(PhysicalSize<i32>, PhysicalPosition<i32>)> {
+    // Widget coordinates might be modified when docked to an edge.
+    match placement.dock_to_edge.enabled {
+      false => Ok((coordinates.size, coordinates.position)),
+      true => self.dock_to_edge(&window, &placement.dock_to_edge, &coordinates),
+    }
+  }
+
+  /// Sizes and positions a widget window.
+  fn move_window(
+    window: &tauri::WebviewWindow,
+    (size, position): (PhysicalSize<i32>, PhysicalPosition<i32>),
+  ) {
+    info!("Positioning widget to {:?} {:?}", size, position);
+    let _ = window.set_size(size);
+    let _ = window.set_position(position);
+
+    // On Windows, we need to set the position twice to account for
+    // different monitor scale factors.
+    #[cfg(target_os = "windows")]
+    {
+      let _ = window.set_size(size);
+      let _ = window.set_position(position);
+    }
+  }
+
+  /// Moves and resizes a widget window to where the user last left it.
+  ///
+  /// Docked widgets always stay at their placement, since space is
+  /// reserved for them at the monitor edge.
+  async fn restore_saved_window(
+    &self,
+    window: &tauri::WebviewWindow,
+    state: &WidgetState,
+    placement: &WidgetPlacement,
+  ) -> anyhow::Result<()> {
+    if placement.dock_to_edge.enabled {
+      return Ok(());
+    }
+
+    let Some(saved_window) = self.window_state.get(&state.id).await else {
+      return Ok(());
+    };
+
+    info!("Restoring saved window state of widget #{}.", state.id);
+
+    if let Some(size) = saved_window.size.filter(|_| state.config.resizable) {
+      window.set_size(size)?;
+    }
+
+    if let Some(position) = saved_window.position {
+      window.set_position(PhysicalPosition::new(
+        state.monitor.x + position.x,
+        state.monitor.y + position.y,
+      ))?;
+    }
+
+    Ok(())
+  }
+
+  /// Derives a widget ID that stays the same across relaunches, based on
+  /// the config path, how the widget was opened and its monitor.
+  ///
+  /// Standalone widgets can be opened multiple times with the same
+  /// placement, in which case a numbered suffix is added.
+  async fn unique_widget_id(
+    &self,
+    config_path: &PathBuf,
+    open_options: &WidgetOpenOptions,
+    monitor: &Monitor,
+  ) -> anyhow::Result<String> {
+    let open_options_key = match open_options {
+      WidgetOpenOptions::Preset(name) => format!("preset:{}", name),
+      WidgetOpenOptions::Standalone(placement) => {
+        format!("standalone:{}", serde_json::to_string(placement)?)
+      }
+    };
+
+    let key = format!(
+      "{}\n{}\n{}",
+      self.config.formatted_widget_path(config_path),
+      open_options_key,
+      monitor.id
+    );
+
+    let hash = Uuid::new_v5(&Uuid::NAMESPACE_OID, key.as_bytes())
+      .simple()
+      .to_string();
+
+    let base_id = format!("widget-{}", &hash[..12]);
+    let widget_states = self.widget_states.lock().await;
+    let mut pending_widget_ids = self.pending_widget_ids.lock().await;
+
+    let widget_id = (1..)
+      .map(|count| match count {
+        1 => base_id.clone(),
+        _ => format!("{}-{}", base_id, count),
+      })
+      .find(|id| !widget_states.contains_key(id) && !pending_widget_ids.contains(id))
+      .context("No available widget ID.")?;
+
+    pending_widget_ids.insert(widget_id.clone());
+
+    Ok(widget_id)
+  }
+
   /// Dock the widget window to a given edge. This might result in the
   /// window being resized or repositioned (e.g. if a window is already
   /// docked to the given edge).
@@ -348,12 +738,12 @@ impl WidgetFactory {
     dock_config: &DockConfig,
     coords: &WidgetCoordinates,
   ) -> anyhow::Result<(PhysicalSize<i32>, PhysicalPosition<i32>)> {
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "macos")]
     {
       return Ok((coords.size, coords.position));
     }
 
-    #[cfg(target_os = "windows")]
+    #[cfg(not(target_os = "macos"))]
     {
       // Disallow docking with a centered anchor point. Doesn't make sense.
       if coords.anchor == AnchorPoint::Center {
@@ -381,7 +771,12 @@ impl WidgetFactory {
       // not be smaller than the size of the window.
       let window_margin = dock_config
         .window_margin
-        .to_px_scaled(window_length as i32, coords.monitor.scale_factor)
+        .to_px_in_viewport(
+          window_length,
+          coords.monitor.width as i32,
+          coords.monitor.height as i32,
+          coords.monitor.scale_factor,
+        )
         .clamp(-coords.size.height, i32::MAX);
 
       let monitor_length = if edge.is_horizontal() {
@@ -420,6 +815,17 @@ impl WidgetFactory {
           .window()
           .allocate_app_bar(reserve_size, reserve_position, edge)?;
 
+      {
+        let mut dock_work_areas = self.dock_work_areas.lock().unwrap();
+
+        let work_area = dock_work_areas
+          .get(&coords.monitor.id)
+          .unwrap_or(&coords.monitor.work_area)
+          .without_reserved(edge, allocated_size, allocated_position);
+
+        dock_work_areas.insert(coords.monitor.id.clone(), work_area);
+      }
+
       // Adjust the size to account for the window margin.
       let final_size = if edge.is_horizontal() {
         PhysicalSize::new(
@@ -459,7 +865,16 @@ impl WidgetFactory {
   }
 
   /// Opens presets that are configured to be launched on startup.
+  ///
+  /// Activates the profile that matches the connected monitors first, if
+  /// any. The matched profile isn't written to the settings file.
   pub async fn startup(&self) -> anyhow::Result<()> {
+    let monitors = self.monitor_state.monitors().await;
+
+    if let Some(profile_name) = self.config.profile_by_monitors(&monitors).await {
+      self.config.set_matched_profile(&profile_name).await;
+    }
+
     let startup_configs = self.config.startup_configs().await;
 
     for startup_config in startup_configs {
@@ -474,6 +889,91 @@ impl WidgetFactory {
     Ok(())
   }
 
+  /// Switches to the given profile, and writes it to the settings file.
+  ///
+  /// Only the presets that differ are opened and closed, so that widgets
+  /// shared between profiles keep running. Widgets opened without a
+  /// preset are left as is.
+  pub async fn switch_profile(&self, profile_name: &str) -> anyhow::Result<()> {
+    info!("Switching to profile '{}'.", profile_name);
+
+    self.config.set_active_profile(profile_name).await?;
+    self.open_profile_presets().await
+  }
+
+  /// Opens the presets of the active profile, and closes the ones of
+  /// other profiles.
+  async fn open_profile_presets(&self) -> anyhow::Result<()> {
+    let profile_presets = self
+      .config
+      .startup_configs()
+      .await
+      .into_iter()
+      .filter_map(|startup_config| {
+        let config_path = self.config.to_absolute_path(&startup_config.path).ok()?;
+        Some((config_path, startup_config.preset))
+      })
+      .collect::<Vec<_>>();
+
+    // Presets that are open, or waiting for a matching monitor.
+    let open_widgets = self
+      .states()
+      .await
+      .into_values()
+      .map(|state| (state.config_path, state.open_options))
+      .collect::<Vec<_>>();
+
+    let unplaced_widgets = { self.unplaced_widgets.lock().await.clone() };
+
+    let mut open_presets = open_widgets
+      .into_iter()
+      .chain(unplaced_widgets)
+      .filter_map(|(config_path, open_options)| match open_options {
+        WidgetOpenOptions::Preset(preset_name) => Some((config_path, preset_name)),
+        WidgetOpenOptions::Standalone(_) => None,
+      })
+      .collect::<Vec<_>>();
+
+    open_presets.sort();
+    open_presets.dedup();
+
+    for (config_path, preset_name) in &open_presets {
+      if !profile_presets.contains(&(config_path.clone(), preset_name.clone())) {
+        self.stop_by_preset(config_path, preset_name).await?;
+      }
+    }
+
+    for (config_path, preset_name) in profile_presets {
+      if !open_presets.contains(&(config_path.clone(), preset_name.clone())) {
+        self
+          .start_widget(&config_path, &WidgetOpenOptions::Preset(preset_name))
+          .await?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Switches to the profile that matches the connected monitors, if it
+  /// isn't already active. The matched profile isn't written to the
+  /// settings file.
+  pub async fn switch_profile_by_monitors(&self) -> anyhow::Result<()> {
+    let monitors = self.monitor_state.monitors().await;
+
+    match self.config.profile_by_monitors(&monitors).await {
+      Some(profile_name) if Some(&profile_name) != self.config.active_profile().await.as_ref() => {
+        info!(
+          "Switching to profile '{}' matching the monitors.",
+          profile_name
+        );
+
+        self.config.set_matched_profile(&profile_name).await;
+        self.open_profile_presets().await
+      }
+      _ => Ok(()),
+    }
+  }
+
   fn initialization_script(&self, state: &WidgetState) -> anyhow::Result<String> {
     let state_script = format!("window.__ZEBAR_STATE={};", serde_json::to_string(state)?);
 
@@ -487,11 +987,51 @@ impl WidgetFactory {
     &self,
     window: &tauri::WebviewWindow,
     widget_id: String,
+    window_instance: u32,
   ) -> anyhow::Result<()> {
+    let app_handle = self.app_handle.clone();
     let widget_states = self.widget_states.clone();
+    let window_state = self.window_state.clone();
     let close_tx = self.close_tx.clone();
 
     window.on_window_event(move |event| {
+      let adjustment = match event {
+        WindowEvent::Moved(position) => Some((Some(*position), None)),
+        WindowEvent::Resized(size) => Some((None, Some(*size))),
+        _ => None,
+      };
+
+      if let Some((position, size)) = adjustment {
+        let app_handle = app_handle.clone();
+        let widget_states = widget_states.clone();
+        let window_state = window_state.clone();
+        let widget_id = widget_id.clone();
+
+        task::spawn(async move {
+          // Minimized windows are moved off-screen on Windows.
+          let is_minimized = app_handle
+            .get_webview_window(&widget_id)
+            .is_some_and(|window| window.is_minimized().unwrap_or(false));
+
+          if is_minimized {
+            return;
+          }
+
+          let widget_states = widget_states.lock().await;
+
+          let Some(state) = widget_states
+            .get(&widget_id)
+            .filter(|state| state.window_instance == window_instance)
+          else {
+            return;
+          };
+
+          if let Some((position, size)) = state.user_adjustment(position, size) {
+            window_state.update(&widget_id, position, size).await;
+          }
+        });
+      }
+
       if let WindowEvent::Destroyed = event {
         let widget_states = widget_states.clone();
         let close_tx = close_tx.clone();
@@ -500,6 +1040,15 @@ impl WidgetFactory {
         task::spawn(async move {
           let mut widget_states = widget_states.lock().await;
 
+          // The widget ID might already be in use by a reopened window.
+          let is_reopened = widget_states
+            .get(&widget_id)
+            .is_some_and(|state| state.window_instance != window_instance);
+
+          if is_reopened {
+            return;
+          }
+
           // Remove the widget state.
           let state = widget_states.remove(&widget_id);
 
@@ -524,80 +1073,92 @@ impl WidgetFactory {
 
   /// Returns coordinates for window placement based on the given config.
   async fn widget_coordinates(&self, placement: &WidgetPlacement) -> Vec<WidgetCoordinates> {
-    let mut coordinates = vec![];
-
-    let monitors = self
+    self
       .monitor_state
       .monitors_by_selection(&placement.monitor_selection)
-      .await;
-
-    for monitor in monitors {
-      let monitor_width = monitor.width as i32;
-      let monitor_height = monitor.height as i32;
-
-      // Pixel values should be scaled by the monitor's scale factor,
-      // whereas percentage values are left as-is. This is because the
-      // percentage values are already relative to the monitor's size.
-      let window_width = placement
-        .width
-        .to_px_scaled(monitor_width, monitor.scale_factor);
+      .await
+      .iter()
+      .map(|monitor| Self::monitor_coordinates(placement, monitor))
+      .collect()
+  }
 
-      let window_height = placement
-        .height
-        .to_px_scaled(monitor_height, monitor.scale_factor);
+  /// Returns coordinates for window placement on a single monitor.
+  fn monitor_coordinates(placement: &WidgetPlacement, monitor: &Monitor) -> WidgetCoordinates {
+    let monitor_width = monitor.width as i32;
+    let monitor_height = monitor.height as i32;
+
+    // Pixel values should be scaled by the monitor's scale factor,
+    // whereas percentage and viewport values are left as-is. This is
+    // because they are already relative to the monitor's size.
+    let window_width = placement.width.to_px_in_viewport(
+      monitor_width,
+      monitor_width,
+      monitor_height,
+      monitor.scale_factor,
+    );
 
-      let window_size = PhysicalSize::new(window_width, window_height);
+    let window_height = placement.height.to_px_in_viewport(
+      monitor_height,
+      monitor_width,
+      monitor_height,
+      monitor.scale_factor,
+    );
 
-      let (anchor_x, anchor_y) = match placement.anchor {
-        AnchorPoint::TopLeft => (monitor.x, monitor.y),
-        AnchorPoint::TopCenter => (
-          monitor.x + (monitor_width / 2) - (window_size.width / 2),
-          monitor.y,
-        ),
-        AnchorPoint::TopRight => (monitor.x + monitor_width - window_size.width, monitor.y),
-        AnchorPoint::CenterLeft => (
-          monitor.x,
-          monitor.y + (monitor_height / 2) - (window_size.height / 2),
-        ),
-        AnchorPoint::Center => (
-          monitor.x + (monitor_width / 2) - (window_size.width / 2),
-          monitor.y + (monitor_height / 2) - (window_size.height / 2),
-        ),
-        AnchorPoint::CenterRight => (
-          monitor.x + monitor_width - window_size.width,
-          monitor.y + (monitor_height / 2) - (window_size.height / 2),
-        ),
-        AnchorPoint::BottomLeft => (monitor.x, monitor.y + monitor_height - window_size.height),
-        AnchorPoint::BottomCenter => (
-          monitor.x + (monitor_width / 2) - (window_size.width / 2),
-          monitor.y + monitor_height - window_size.height,
-        ),
-        AnchorPoint::BottomRight => (
-          monitor.x + monitor_width - window_size.width,
-          monitor.y + monitor_height - window_size.height,
-        ),
-      };
+    let window_size = PhysicalSize::new(window_width, window_height);
+
+    let (anchor_x, anchor_y) = match placement.anchor {
+      AnchorPoint::TopLeft => (monitor.x, monitor.y),
+      AnchorPoint::TopCenter => (
+        monitor.x + (monitor_width / 2) - (window_size.width / 2),
+        monitor.y,
+      ),
+      AnchorPoint::TopRight => (monitor.x + monitor_width - window_size.width, monitor.y),
+      AnchorPoint::CenterLeft => (
+        monitor.x,
+        monitor.y + (monitor_height / 2) - (window_size.height / 2),
+      ),
+      AnchorPoint::Center => (
+        monitor.x + (monitor_width / 2) - (window_size.width / 2),
+        monitor.y + (monitor_height / 2) - (window_size.height / 2),
+      ),
+      AnchorPoint::CenterRight => (
+        monitor.x + monitor_width - window_size.width,
+        monitor.y + (monitor_height / 2) - (window_size.height / 2),
+      ),
+      AnchorPoint::BottomLeft => (monitor.x, monitor.y + monitor_height - window_size.height),
+      AnchorPoint::BottomCenter => (
+        monitor.x + (monitor_width / 2) - (window_size.width / 2),
+        monitor.y + monitor_height - window_size.height,
+      ),
+      AnchorPoint::BottomRight => (
+        monitor.x + monitor_width - window_size.width,
+        monitor.y + monitor_height - window_size.height,
+      ),
+    };
 
-      let offset_x = placement
-        .offset_x
-        .to_px_scaled(monitor_width, monitor.scale_factor);
+    let offset_x = placement.offset_x.to_px_in_viewport(
+      monitor_width,
+      monitor_width,
+      monitor_height,
+      monitor.scale_factor,
+    );
 
-      let offset_y = placement
-        .offset_y
-        .to_px_scaled(monitor_height, monitor.scale_factor);
+    let offset_y = placement.offset_y.to_px_in_viewport(
+      monitor_height,
+      monitor_width,
+      monitor_height,
+      monitor.scale_factor,
+    );
 
-      let window_position = PhysicalPosition::new(anchor_x + offset_x, anchor_y + offset_y);
+    let window_position = PhysicalPosition::new(anchor_x + offset_x, anchor_y + offset_y);
 
-      coordinates.push(WidgetCoordinates {
-        size: window_size,
-        position: window_position,
-        offset: PhysicalPosition::new(offset_x, offset_y),
-        monitor: monitor.clone(),
-        anchor: placement.anchor,
-      });
+    WidgetCoordinates {
+      size: window_size,
+      position: window_position,
+      offset: PhysicalPosition::new(offset_x, offset_y),
+      monitor: monitor.clone(),
+      anchor: placement.anchor,
     }
-
-    coordinates
   }
 
   /// Closes a single widget by a given widget ID.
@@ -633,18 +1194,34 @@ impl WidgetFactory {
     config_path: &PathBuf,
     preset_name: &str,
   ) -> anyhow::Result<()> {
+    // Forget the preset if it's waiting for a matching monitor.
+    let was_unplaced = {
+      let mut unplaced_widgets = self.unplaced_widgets.lock().await;
+      let unplaced_count = unplaced_widgets.len();
+
+      unplaced_widgets.retain(|(path, options)| {
+        !(path == config_path
+          && matches!(options, WidgetOpenOptions::Preset(name) if name == preset_name))
+      });
+
+      unplaced_widgets.len() != unplaced_count
+    };
+
     let widget_states = self.states_by_path().await;
 
-    let found_widget_states = widget_states
-      .get(config_path)
-      .context("No widgets found with the given config path.")?
-      .iter()
-      .filter(|state| {
-        matches!(
-          &state.open_options,
-          WidgetOpenOptions::Preset(name) if name == preset_name
-        )
-      });
+    let Some(widget_states) = widget_states.get(config_path) else {
+      match was_unplaced {
+        true => return Ok(()),
+        false => bail!("No widgets found with the given config path."),
+      }
+    };
+
+    let found_widget_states = widget_states.iter().filter(|state| {
+      matches!(
+        &state.open_options,
+        WidgetOpenOptions::Preset(name) if name == preset_name
+      )
+    });
 
     for widget_state in found_widget_states {
       self.stop_by_id(&widget_state.id)?;
@@ -653,6 +1230,177 @@ impl WidgetFactory {
     Ok(())
   }
 
+  /// Shows a hidden widget by a given widget ID.
+  pub async fn show_by_id(&self, widget_id: &str) -> anyhow::Result<()> {
+    self.set_visible(&[widget_id.to_string()], true).await
+  }
+
+  /// Hides a widget by a given widget ID, without destroying its
+  /// webview.
+  pub async fn hide_by_id(&self, widget_id: &str) -> anyhow::Result<()> {
+    self.set_visible(&[widget_id.to_string()], false).await
+  }
+
+  /// Shows or hides a widget by a given widget ID.
+  pub async fn toggle_by_id(&self, widget_id: &str) -> anyhow::Result<()> {
+    let state = self
+      .state_by_id(widget_id)
+      .await
+      .context("No widget found with the given widget ID.")?;
+
+    self.set_visible(&[state.id], !state.visible).await
+  }
+
+  /// Shows all widgets of the given preset, opening the preset if it
+  /// isn't open.
+  pub async fn show_by_preset(
+    &self,
+    config_path: &PathBuf,
+    preset_name: &str,
+  ) -> anyhow::Result<()> {
+    let widget_ids = self.ids_by_preset(config_path, preset_name).await?;
+
+    match widget_ids.is_empty() {
+      true => {
+        self
+          .start_widget(
+            config_path,
+            &WidgetOpenOptions::Preset(preset_name.to_string()),
+          )
+          .await
+      }
+      false => self.set_visible(&widget_ids, true).await,
+    }
+  }
+
+  /// Hides all widgets of the given preset.
+  pub async fn hide_by_preset(
+    &self,
+    config_path: &PathBuf,
+    preset_name: &str,
+  ) -> anyhow::Result<()> {
+    let widget_ids = self.ids_by_preset(config_path, preset_name).await?;
+
+    if widget_ids.is_empty() {
+      bail!("No widgets found with the given preset.");
+    }
+
+    self.set_visible(&widget_ids, false).await
+  }
+
+  /// Hides all widgets of the given preset if any of them are shown, and
+  /// shows them otherwise. Opens the preset if it isn't open.
+  pub async fn toggle_by_preset(
+    &self,
+    config_path: &PathBuf,
+    preset_name: &str,
+  ) -> anyhow::Result<()> {
+    let widget_ids = self.ids_by_preset(config_path, preset_name).await?;
+
+    let is_any_visible = {
+      let widget_states = self.widget_states.lock().await;
+
+      widget_ids
+        .iter()
+        .filter_map(|id| widget_states.get(id))
+        .any(|state| state.visible)
+    };
+
+    match is_any_visible {
+      true => self.set_visible(&widget_ids, false).await,
+      false => self.show_by_preset(config_path, preset_name).await,
+    }
+  }
+
+  /// Returns the ID's of open widgets of the given preset.
+  async fn ids_by_preset(
+    &self,
+    config_path: &PathBuf,
+    preset_name: &str,
+  ) -> anyhow::Result<Vec<String>> {
+    let config_path = self.config.to_absolute_path(config_path)?;
+
+    let widget_ids = self
+      .widget_states
+      .lock()
+      .await
+      .values()
+      .filter(|state| {
+        state.config_path == config_path
+          && matches!(
+            &state.open_options,
+            WidgetOpenOptions::Preset(name) if name == preset_name
+          )
+      })
+      .map(|state| state.id.clone())
+      .collect();
+
+    Ok(widget_ids)
+  }
+
+  /// Shows or hides the windows of the given widgets, and broadcasts
+  /// the change.
+  async fn set_visible(&self, widget_ids: &[String], visible: bool) -> anyhow::Result<()> {
+    for widget_id in widget_ids {
+      let is_changed = self
+        .state_by_id(widget_id)
+        .await
+        .is_some_and(|state| state.visible != visible);
+
+      if !is_changed {
+        continue;
+      }
+
+      let window = self
+        .app_handle
+        .get_webview_window(widget_id)
+        .context("No Tauri window found for the given widget ID.")?;
+
+      info!(
+        "{} widget #{}.",
+        if visible { "Showing" } else { "Hiding" },
+        widget_id
+      );
+
+      match visible {
+        true => window.show()?,
+        false => {
+          window.hide()?;
+
+          // Hidden widgets shouldn't keep their space reserved.
+          #[cfg(target_os = "windows")]
+          let _ = window.as_ref().window().deallocate_app_bar();
+        }
+      }
+
+      let state = {
+        let mut widget_states = self.widget_states.lock().await;
+
+        let Some(state) = widget_states.get_mut(widget_id) else {
+          continue;
+        };
+
+        state.visible = visible;
+        state.clone()
+      };
+
+      // Re-dock the widget to reserve its space again.
+      #[cfg(target_os = "windows")]
+      {
+        if let Some(placement) = state
+          .placement()
+          .filter(|placement| visible && placement.dock_to_edge.enabled)
+        {
+          self.reposition(&state, placement, &state.monitor).await?;
+        }
+      }
+
+      self.visibility_change_tx.send(state)?;
+    }
+
+    Ok(())
+  }
+
   /// Relaunches all currently open widgets.
   #[allow(dead_code)]
   pub async fn relaunch_all(&self) -> anyhow::Result<()> {
@@ -699,6 +1447,204 @@ impl WidgetFactory {
     Ok(())
   }
 
+  /// Reconciles open widgets with the current monitors.
+  ///
+  /// Widgets on monitors that no longer match their monitor selection
+  /// are closed, widgets are opened on newly matching monitors, and
+  /// widgets whose monitor changed bounds or scale factor are
+  /// repositioned. Docked widgets are also docked again when another
+  /// appbar changed the work area of their monitor.
+  pub async fn reconcile_monitors(&self) -> anyhow::Result<()> {
+    let unplaced_widgets = std::mem::take(&mut *self.unplaced_widgets.lock().await);
+
+    let changed_work_areas = {
+      let monitors = self.monitor_state.monitors().await;
+      let mut dock_work_areas = self.dock_work_areas.lock().unwrap();
+      let changed_ids = Self::changed_work_areas(&dock_work_areas, &monitors);
+
+      // Expected work areas are recorded again once the widgets are
+      // docked.
+      dock_work_areas.retain(|id, _| {
+        monitors.iter().any(|monitor| monitor.id == *id) && !changed_ids.contains(id)
+      });
+
+      changed_ids
+    };
+
+    // Group widgets that were opened together.
+    let mut groups: Vec<Vec<WidgetState>> = Vec::new();
+
+    for state in self.widget_states.lock().await.values() {
+      match groups.iter_mut().find(|group| {
+        group[0].config_path == state.config_path && group[0].open_options == state.open_options
+      }) {
+        Some(group) => group.push(state.clone()),
+        None => groups.push(vec![state.clone()]),
+      }
+    }
+
+    for states in &groups {
+      let Some(placement) = states[0].placement() else {
+        continue;
+      };
+
+      let monitors = self
+        .monitor_state
+        .monitors_by_selection(&placement.monitor_selection)
+        .await;
+
+      for state in states {
+        match monitors
+          .iter()
+          .find(|monitor| monitor.is_same_display(&state.monitor))
+        {
+          None => {
+            info!("Closing widget #{} on removed monitor.", state.id);
+            let _ = self.stop_by_id(&state.id);
+          }
+          Some(monitor) if !monitor.same_bounds(&state.monitor) => {
+            info!("Repositioning widget #{} on changed monitor.", state.id);
+            self.reposition(state, placement, monitor).await?;
+          }
+          Some(monitor)
+            if placement.dock_to_edge.enabled && changed_work_areas.contains(&monitor.id) =>
+          {
+            info!("Redocking widget #{} on changed work area.", state.id);
+            self.reposition(state, placement, monitor).await?;
+          }
+          Some(_) => {}
+        }
+      }
+
+      let added_monitors = monitors.iter().filter(|monitor| {
+        !states
+          .iter()
+          .any(|state| state.monitor.is_same_display(monitor))
+      });
+
+      for monitor in added_monitors {
+        self
+          .open_widget_window(
+            &states[0].config_path,
+            &states[0].config,
+            &states[0].open_options,
+            placement,
+            Self::monitor_coordinates(placement, monitor),
+          )
+          .await?;
+      }
+
+      // Widgets that lost all their monitors are reopened once a
+      // matching monitor is connected again.
+      if monitors.is_empty() {
+        self.unplaced_widgets.lock().await.push((
+          states[0].config_path.clone(),
+          states[0].open_options.clone(),
+        ));
+      }
+    }
+
+    for (config_path, open_options) in unplaced_widgets.iter().filter(|(path, options)| {
+      !groups
+        .iter()
+        .any(|states| states[0].config_path == *path && states[0].open_options == *options)
+    }) {
+      self.start_widget(config_path, open_options).await?;
+    }
+
+    Ok(())
+  }
+
+  /// Gets ID's of monitors whose work area differs from the one expected
+  /// after docking widgets to them.
+  fn changed_work_areas(
+    dock_work_areas: &HashMap<String, WorkArea>,
+    monitors: &[Monitor],
+  ) -> Vec<String> {
+    monitors
+      .iter()
+      .filter(|monitor| {
+        dock_work_areas
+          .get(&monitor.id)
+          .is_some_and(|work_area| *work_area != monitor.work_area)
+      })
+      .map(|monitor| monitor.id.clone())
+      .collect()
+  }
+
+  /// Moves a widget to the updated bounds of its monitor.
+  async fn reposition(
+    &self,
+    state: &WidgetState,
+    placement: &WidgetPlacement,
+    monitor: &Monitor,
+  ) -> anyhow::Result<()> {
+    let window = self
+      .app_handle
+      .get_webview_window(&state.id)
+      .context("No Tauri window found for the given widget ID.")?;
+
+    // Appbar space was reserved for the previous bounds.
+    #[cfg(target_os = "windows")]
+    {
+      if let Some(window_handle) = state.window_handle {
+        let _ = remove_app_bar(window_handle);
+      }
+    }
+
+    let placed_bounds = self.placement_bounds(
+      &window,
+      placement,
+      &Self::monitor_coordinates(placement, monitor),
+    )?;
+
+    // Update the state before moving, so that the resulting window events
+    // aren't mistaken for adjustments by the user.
+    let state = {
+      let mut widget_states = self.widget_states.lock().await;
+
+      let Some(state) = widget_states.get_mut(&state.id) else {
+        return Ok(());
+      };
+
+      state.monitor = monitor.clone();
+      state.placed_bounds = placed_bounds;
+      state.clone()
+    };
+
+    Self::move_window(&window, placed_bounds);
+
+    self.restore_saved_window(&window, &state, placement).await
+  }
+
+  /// Clears the saved window state of the given widgets, and moves them
+  /// back to their configured placement.
+  pub async fn reset_window_states(&self, widget_ids: &[String]) -> anyhow::Result<()> {
+    self.window_state.remove(widget_ids).await?;
+
+    for widget_id in widget_ids {
+      let Some(state) = self.state_by_id(widget_id).await else {
+        continue;
+      };
+
+      if let Some(placement) = state.placement() {
+        self.reposition(&state, placement, &state.monitor).await?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Clears the saved window state of all widgets.
+  pub async fn reset_all_window_states(&self) -> anyhow::Result<()> {
+    let mut widget_ids = self.window_state.widget_ids().await;
+    widget_ids.extend(self.widget_states.lock().await.keys().cloned());
+    widget_ids.sort();
+    widget_ids.dedup();
+
+    self.reset_window_states(&widget_ids).await
+  }
+
   /// Relaunches widgets with the given config paths.
   pub async fn relaunch_by_paths(&self, config_paths: &Vec<PathBuf>) -> anyhow::Result<()> {
     let widget_ids = {
@@ -715,6 +1661,36 @@ impl WidgetFactory {
     self.relaunch_by_ids(&widget_ids).await
   }
 
+  /// Reloads the webviews of widgets whose directory contains any of the
+  /// given asset paths. Unlike relaunching, the windows are kept as-is.
+  pub async fn reload_by_asset_paths(&self, asset_paths: &[PathBuf]) -> anyhow::Result<()> {
+    let widget_ids = {
+      self
+        .widget_states
+        .lock()
+        .await
+        .iter()
+        .filter(|(_, state)| {
+          state
+            .config_path
+            .parent()
+            .is_some_and(|widget_dir| asset_paths.iter().any(|path| path.starts_with(widget_dir)))
+        })
+        .map(|(id, _)| id.clone())
+        .collect::<Vec<_>>()
+    };
+
+    for widget_id in widget_ids {
+      info!("Reloading assets of widget #{}.", widget_id);
+
+      if let Some(window) = self.app_handle.get_webview_window(&widget_id) {
+        window.eval("location.reload()")?;
+      }
+    }
+
+    Ok(())
+  }
+
   /// Clears the cache for all open widgets.
   pub fn clear_cache(&self) {
     for (_, window) in self.app_handle.webview_windows() {
@@ -757,3 +1733,61 @@ impl WidgetFactory {
       })
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn monitor(id: &str, work_area_y: i32) -> Monitor {
+    Monitor {
+      id: id.to_string(),
+      name: Some(id.to_string()),
+      is_primary: false,
+      x: 0,
+      y: 0,
+      width: 1920,
+      height: 1080,
+      scale_factor: 1.0,
+      refresh_rate: None,
+      work_area: WorkArea {
+        x: 0,
+        y: work_area_y,
+        width: 1920,
+        height: (1080 - work_area_y) as u32,
+      },
+    }
+  }
+
+  #[test]
+  fn test_changed_work_areas() {
+    // Docked a 40px bar to the top of both monitors.
+    let docked = monitor("DP-1", 0).work_area.without_reserved(
+      DockEdge::Top,
+      PhysicalSize::new(1920, 40),
+      PhysicalPosition::new(0, 0),
+    );
+
+    let dock_work_areas = HashMap::from([
+      ("DP-1".to_string(), docked.clone()),
+      ("DP-2".to_string(), docked),
+    ]);
+
+    // Space reserved by the widgets themselves isn't a change, and
+    // monitors without docked widgets are left out.
+    let monitors = [
+      monitor("DP-1", 40),
+      monitor("DP-2", 40),
+      monitor("DP-3", 30),
+    ];
+
+    assert!(WidgetFactory::changed_work_areas(&dock_work_areas, &monitors).is_empty());
+
+    // Another appbar reserved space on DP-2.
+    let monitors = [monitor("DP-1", 40), monitor("DP-2", 70)];
+
+    assert_eq!(
+      WidgetFactory::changed_work_areas(&dock_work_areas, &monitors),
+      vec!["DP-2".to_string()]
+    );
+  }
+}
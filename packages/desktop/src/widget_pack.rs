@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  common::PathExt,
+  config_diagnostics::{ConfigDiagnostics, ConfigSchema},
+};
+
+/// File name of the manifest that marks a directory as a widget pack.
+pub const PACK_MANIFEST_FILE: &str = "zebar-pack.json";
+
+/// Version of the running Zebar. Development builds are `0.0.0`.
+pub const ZEBAR_VERSION: &str = env!("VERSION_NUMBER");
+
+/// A set of widget configs that are distributed together, described by a
+/// `zebar-pack.json` manifest at the root of its directory.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetPack {
+  /// Absolute path to the directory containing the manifest.
+  #[serde(skip_deserializing)]
+  pub path: PathBuf,
+
+  /// Display name of the pack.
+  pub name: String,
+
+  /// Version of the pack (e.g. `1.2.0`).
+  pub version: String,
+
+  pub author: Option<String>,
+
+  pub description: Option<String>,
+
+  /// Oldest version of Zebar that the pack's widgets work with.
+  pub min_zebar_version: Option<String>,
+
+  /// Paths to the pack's widget configs, relative to the manifest. If
+  /// not set, all widget configs within the directory are included.
+  pub widgets: Option<Vec<PathBuf>>,
+}
+
+impl WidgetPack {
+  /// Reads the manifest of the pack in the given directory.
+  ///
+  /// Replaces the manifest's diagnostics with any errors found.
+  pub fn read(dir: &Path, diagnostics: &ConfigDiagnostics) -> anyhow::Result<Self> {
+    let mut pack =
+      diagnostics.read_config::<WidgetPack>(&dir.join(PACK_MANIFEST_FILE), ConfigSchema::Pack)?;
+
+    pack.path = dir.to_path_buf();
+    Ok(pack)
+  }
+
+  /// Returns the pack that the config at the given path belongs to, if
+  /// any. Nested packs take precedence over their parents.
+  pub fn find<'a>(packs: &'a [WidgetPack], config_path: &Path) -> Option<&'a WidgetPack> {
+    packs
+      .iter()
+      .filter(|pack| config_path.starts_with(&pack.path))
+      .max_by_key(|pack| pack.path.components().count())
+  }
+
+  /// Path to the pack's manifest.
+  pub fn manifest_path(&self) -> PathBuf {
+    self.path.join(PACK_MANIFEST_FILE)
+  }
+
+  /// Paths to the widget configs listed in the manifest, if any.
+  ///
+  /// Listed configs that don't exist are skipped.
+  pub fn listed_widget_paths(&self) -> Option<Vec<PathBuf>> {
+    self.widgets.as_ref().map(|widgets| {
+      widgets
+        .iter()
+        .filter_map(|path| self.path.join(path).to_absolute().ok())
+        .collect()
+    })
+  }
+
+  /// Whether the running version of Zebar meets the pack's
+  /// `minZebarVersion`. Always true for development builds.
+  pub fn is_compatible(&self) -> bool {
+    let Some(min_version) = self.min_zebar_version.as_deref() else {
+      return true;
+    };
+
+    match (parse_version(ZEBAR_VERSION), parse_version(min_version)) {
+      (Some([0, 0, 0]), _) => true,
+      (Some(version), Some(min_version)) => version >= min_version,
+      // Versions that can't be compared are let through.
+      _ => true,
+    }
+  }
+
+  /// Describes that the pack requires a newer version of Zebar.
+  pub fn incompatible_message(&self) -> String {
+    format!(
+      "Pack '{}' requires Zebar v{} or later, but v{} is running.",
+      self.name,
+      self.min_zebar_version.as_deref().unwrap_or_default(),
+      ZEBAR_VERSION
+    )
+  }
+}
+
+/// Parses a version like `2.1.0` or `v2.1` into its major, minor and
+/// patch numbers. Pre-release and build suffixes are ignored.
+fn parse_version(version: &str) -> Option<[u64; 3]> {
+  let version = version
+    .trim()
+    .trim_start_matches('v')
+    .split(['-', '+'])
+    .next()?;
+
+  let mut parts = [0; 3];
+
+  for (index, part) in version.split('.').enumerate() {
+    *parts.get_mut(index)? = part.parse().ok()?;
+  }
+
+  Some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_versions() {
+    assert_eq!(parse_version("2.1.0"), Some([2, 1, 0]));
+    assert_eq!(parse_version("v2.1"), Some([2, 1, 0]));
+    assert_eq!(parse_version("3.0.0-beta.1"), Some([3, 0, 0]));
+    assert_eq!(parse_version("1.2.3.4"), None);
+    assert_eq!(parse_version("latest"), None);
+  }
+}
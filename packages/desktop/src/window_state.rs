@@ -0,0 +1,141 @@
+use std::{
+  collections::HashMap,
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{PhysicalPosition, PhysicalSize};
+use tokio::{sync::Mutex, task};
+use tracing::{error, info, warn};
+
+use crate::common::{read_and_parse_json, write_atomic};
+
+/// How long to wait after a change before writing the state file. Moving
+/// or resizing a window emits a burst of events.
+const WRITE_DELAY: Duration = Duration::from_millis(500);
+
+/// Window bounds of a widget that was moved or resized by the user.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedWindow {
+  /// Position relative to the top-left corner of the widget's monitor.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub position: Option<PhysicalPosition<i32>>,
+
+  /// Size of the window. Only saved for resizable widgets.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub size: Option<PhysicalSize<u32>>,
+}
+
+/// Name of the state file within the config directory.
+pub const WINDOW_STATE_FILE: &str = "window-state.json";
+
+/// Persists user adjustments to widget windows across relaunches.
+///
+/// Saved to `WINDOW_STATE_FILE` within the config directory, keyed by
+/// widget ID.
+#[derive(Debug)]
+pub struct WindowState {
+  /// Path to the state file.
+  path: PathBuf,
+
+  /// Map of widget ID's to their saved window bounds.
+  windows: Arc<Mutex<HashMap<String, SavedWindow>>>,
+
+  /// Whether a write of the state file is already scheduled.
+  is_write_pending: Arc<AtomicBool>,
+}
+
+impl WindowState {
+  /// Creates a new `WindowState` instance from the state file in the
+  /// given config directory.
+  pub fn new(config_dir: &PathBuf) -> Self {
+    let path = config_dir.join(WINDOW_STATE_FILE);
+
+    let windows = match path.exists() {
+      false => HashMap::new(),
+      true => read_and_parse_json(&path).unwrap_or_else(|err| {
+        warn!("Ignoring invalid window state file: {:?}", err);
+        HashMap::new()
+      }),
+    };
+
+    Self {
+      path,
+      windows: Arc::new(Mutex::new(windows)),
+      is_write_pending: Arc::new(AtomicBool::new(false)),
+    }
+  }
+
+  /// Returns the saved window bounds of a widget.
+  pub async fn get(&self, widget_id: &str) -> Option<SavedWindow> {
+    self.windows.lock().await.get(widget_id).cloned()
+  }
+
+  /// Returns the ID's of all widgets with saved window bounds.
+  pub async fn widget_ids(&self) -> Vec<String> {
+    self.windows.lock().await.keys().cloned().collect()
+  }
+
+  /// Updates the saved position and/or size of a widget.
+  ///
+  /// The state file is written after a short delay.
+  pub async fn update(
+    &self,
+    widget_id: &str,
+    position: Option<PhysicalPosition<i32>>,
+    size: Option<PhysicalSize<u32>>,
+  ) {
+    {
+      let mut windows = self.windows.lock().await;
+      let window = windows.entry(widget_id.to_string()).or_default();
+
+      if position.is_some() {
+        window.position = position;
+      }
+
+      if size.is_some() {
+        window.size = size;
+      }
+    }
+
+    if self.is_write_pending.swap(true, Ordering::AcqRel) {
+      return;
+    }
+
+    let path = self.path.clone();
+    let windows = self.windows.clone();
+    let is_write_pending = self.is_write_pending.clone();
+
+    task::spawn(async move {
+      tokio::time::sleep(WRITE_DELAY).await;
+      is_write_pending.store(false, Ordering::Release);
+
+      if let Err(err) = Self::write(&path, &*windows.lock().await) {
+        error!("Failed to write window state: {:?}", err);
+      }
+    });
+  }
+
+  /// Removes the saved window bounds of the given widgets.
+  pub async fn remove(&self, widget_ids: &[String]) -> anyhow::Result<()> {
+    let mut windows = self.windows.lock().await;
+
+    for widget_id in widget_ids {
+      if windows.remove(widget_id).is_some() {
+        info!("Reset window state of widget #{}.", widget_id);
+      }
+    }
+
+    Self::write(&self.path, &windows)
+  }
+
+  fn write(path: &PathBuf, windows: &HashMap<String, SavedWindow>) -> anyhow::Result<()> {
+    write_atomic(path, serde_json::to_string_pretty(windows)? + "\n")
+  }
+}
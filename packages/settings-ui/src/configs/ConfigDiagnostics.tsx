@@ -0,0 +1,65 @@
+import { IconAlertTriangle } from '@tabler/icons-solidjs';
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type Event } from '@tauri-apps/api/event';
+import { createResource, For, onCleanup, Show } from 'solid-js';
+
+/**
+ * An error within a config file, as reported by the backend.
+ */
+export interface ConfigDiagnostic {
+  path: string;
+  line: number | null;
+  column: number | null;
+  pointer: string;
+  message: string;
+}
+
+/**
+ * Lists config files that failed to validate. Renders nothing if all
+ * configs are valid.
+ */
+export function ConfigDiagnostics() {
+  const [diagnostics, { mutate }] = createResource(
+    async () => invoke<ConfigDiagnostic[]>('config_diagnostics'),
+    { initialValue: [] },
+  );
+
+  const unlisten = listen(
+    'config-diagnostics-changed',
+    (event: Event<ConfigDiagnostic[]>) => mutate(event.payload),
+  );
+
+  onCleanup(() => unlisten.then(unlisten => unlisten()));
+
+  return (
+    <Show when={diagnostics().length > 0}>
+      <div class="border-b border-destructive/50 bg-destructive/10 p-4 max-h-48 overflow-y-auto">
+        <h2 class="flex items-center text-sm font-semibold text-destructive mb-2">
+          <IconAlertTriangle class="size-4 mr-2" />
+          {diagnostics().length} config error(s)
+        </h2>
+
+        <ul class="space-y-1">
+          <For each={diagnostics()}>
+            {diagnostic => (
+              <li class="text-xs">
+                <span class="font-mono text-muted-foreground">
+                  {diagnostic.path}
+                  {diagnostic.line !== null &&
+                    `:${diagnostic.line}:${diagnostic.column}`}
+                </span>{' '}
+                {diagnostic.message}
+                <Show when={diagnostic.pointer}>
+                  {' '}
+                  <code class="bg-muted rounded-sm px-1">
+                    {diagnostic.pointer}
+                  </code>
+                </Show>
+              </li>
+            )}
+          </For>
+        </ul>
+      </div>
+    </Show>
+  );
+}
@@ -90,6 +90,7 @@ export function WidgetConfigForm(props: WidgetConfigFormProps) {
           edge: null,
           windowMargin: '0px',
         },
+        hideOnFullscreen: false,
       },
     ]);
   }
@@ -369,6 +370,9 @@ export function WidgetConfigForm(props: WidgetConfigFormProps) {
                           { value: 'primary', label: 'Primary' },
                           { value: 'secondary', label: 'Secondary' },
                           { value: 'all', label: 'All' },
+                          { value: 'largest', label: 'Largest' },
+                          { value: 'portrait', label: 'Portrait' },
+                          { value: 'landscape', label: 'Landscape' },
                         ] as const
                       }
                       {...inputProps()}
@@ -434,7 +438,7 @@ export function WidgetConfigForm(props: WidgetConfigFormProps) {
                     <SwitchField
                       id={`dock-enabled-${index}`}
                       class="flex flex-wrap items-center gap-x-4 [&>:last-child]:w-full"
-                      label="Dock to edge (Windows-only)"
+                      label="Dock to edge (Windows & Linux)"
                       description="Whether to dock the widget to the monitor edge and reserve screen space for it."
                       {...inputProps()}
                     />
@@ -530,6 +534,21 @@ export function WidgetConfigForm(props: WidgetConfigFormProps) {
                     </Field>
                   </>
                 )}
+
+              <Field
+                of={configForm}
+                path={`presets.${index}.hideOnFullscreen`}
+              >
+                {inputProps => (
+                  <SwitchField
+                    id={`hide-on-fullscreen-${index}`}
+                    class="flex flex-wrap items-center gap-x-4 [&>:last-child]:w-full"
+                    label="Hide on fullscreen (Windows & Linux)"
+                    description="Whether to hide the widget while a fullscreen window is focused on its monitor."
+                    {...inputProps()}
+                  />
+                )}
+              </Field>
             </div>
           ))}
 
@@ -9,8 +9,22 @@ import { IconChevronDown, IconDiamonds } from '@tabler/icons-solidjs';
 import { createMemo, For, Show } from 'solid-js';
 import { Widget, WidgetConfig } from 'zebar';
 
+/**
+ * A set of widget configs described by a `zebar-pack.json` manifest.
+ */
+export interface WidgetPack {
+  path: string;
+  name: string;
+  version: string;
+  author: string | null;
+  description: string | null;
+  minZebarVersion: string | null;
+  widgets: string[] | null;
+}
+
 export interface WidgetConfigSidebarProps {
   configs: Record<string, WidgetConfig>;
+  packs: WidgetPack[];
   widgetStates: Record<string, Widget>;
   selectedConfig: WidgetConfig | null;
   selectedConfigPath: string | null;
@@ -18,13 +32,18 @@ export interface WidgetConfigSidebarProps {
 }
 
 export function WidgetConfigSidebar(props: WidgetConfigSidebarProps) {
+  // Configs are grouped by their pack, or otherwise by their folder.
   const configTree = createMemo(() => {
     const tree: Record<string, Record<string, WidgetConfig>> = {};
 
     Object.keys(props.configs)
       .sort()
       .forEach(configPath => {
-        const folder = configPath.split(/[/\\]/).at(-2);
+        const pack = findPack(props.packs, configPath);
+
+        const folder = pack
+          ? `${pack.name} v${pack.version}`
+          : configPath.split(/[/\\]/).at(-2);
 
         tree[folder] = {
           ...(tree[folder] ?? {}),
@@ -74,7 +93,7 @@ export function WidgetConfigSidebar(props: WidgetConfigSidebarProps) {
                         {configPath
                           .split(/[/\\]/)
                           .at(-1)
-                          .replace('.zebar.json', '')}
+                          .replace(/\.zebar\.(json|json5|yaml|toml)$/, '')}
 
                         <Show when={widgetCounts().get(configPath)}>
                           <Badge
@@ -100,3 +119,24 @@ export function WidgetConfigSidebar(props: WidgetConfigSidebarProps) {
     </div>
   );
 }
+
+/**
+ * Returns the pack that the config belongs to, if any. Nested packs take
+ * precedence over their parents.
+ */
+function findPack(
+  packs: WidgetPack[],
+  configPath: string,
+): WidgetPack | null {
+  return packs
+    .filter(
+      pack =>
+        configPath.startsWith(pack.path) &&
+        /[/\\]/.test(configPath.charAt(pack.path.length)),
+    )
+    .reduce<WidgetPack | null>(
+      (nearest, pack) =>
+        !nearest || pack.path.length > nearest.path.length ? pack : nearest,
+      null,
+    );
+}
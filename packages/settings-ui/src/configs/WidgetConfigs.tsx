@@ -21,17 +21,31 @@ import {
 } from 'solid-js';
 import { Widget, WidgetConfig } from 'zebar';
 
-import { WidgetConfigSidebar } from './WidgetConfigSidebar';
+import { ConfigDiagnostics } from './ConfigDiagnostics';
+import { WidgetConfigSidebar, WidgetPack } from './WidgetConfigSidebar';
 import { WidgetConfigForm } from './WidgetConfigForm';
 
 export function WidgetConfigs() {
   const params = useParams();
 
-  const [configs, { mutate: mutateWidgetConfigs }] = createResource(
+  const [
+    configs,
+    { mutate: mutateWidgetConfigs, refetch: refetchWidgetConfigs },
+  ] = createResource(
     async () => invoke<Record<string, WidgetConfig>>('widget_configs'),
     { initialValue: {} },
   );
 
+  const [canUndo, { refetch: refetchCanUndo }] = createResource(
+    async () => invoke<boolean>('can_undo_config_change'),
+    { initialValue: false },
+  );
+
+  const [packs] = createResource(
+    async () => invoke<WidgetPack[]>('widget_packs'),
+    { initialValue: [] },
+  );
+
   const [widgetStates, { mutate: mutateWidgetStates }] = createResource(
     async () => invoke<Record<string, Widget>>('widget_states'),
     { initialValue: {} },
@@ -76,6 +90,14 @@ export function WidgetConfigs() {
     }));
   });
 
+  // Update widget states on show and hide.
+  listen('widget-visibility-changed', (event: Event<any>) => {
+    mutateWidgetStates(states => ({
+      ...states,
+      [event.payload.id]: event.payload,
+    }));
+  });
+
   // Update widget states on close.
   listen('widget-closed', (event: Event<any>) => {
     mutateWidgetStates(states => {
@@ -151,6 +173,16 @@ export function WidgetConfigs() {
       configPath,
       newConfig,
     });
+
+    refetchCanUndo();
+  }
+
+  // Restore the config file that was last changed to its previous
+  // version.
+  async function undoConfigChange() {
+    await invoke<string | null>('undo_config_change');
+    refetchWidgetConfigs();
+    refetchCanUndo();
   }
 
   async function togglePreset(configPath: string, presetName: string) {
@@ -172,90 +204,104 @@ export function WidgetConfigs() {
       {/* Sidebar. */}
       <WidgetConfigSidebar
         configs={configs()}
+        packs={packs()}
         widgetStates={widgetStates()}
         selectedConfig={selectedConfig()}
         selectedConfigPath={selectedConfigPath()}
         onSelect={setSelectedConfigPath}
       />
 
-      {/* Main content. */}
-      <Show
-        when={selectedConfig()}
-        fallback={<WidgetSettingsEmptyState />}
-      >
-        {config => (
-          <main class="flex-1 grid grid-rows-[1fr_auto] overflow-hidden">
-            <div id="form-container" class="container p-4 overflow-y-auto">
-              <h1 class="text-2xl font-bold mb-1">
-                {selectedConfigPath().split(/[/\\]/).at(-1)}
-              </h1>
-
-              <p class="bg-muted text-xs font-mono rounded-sm mb-6 p-1 text-muted-foreground inline-block">
-                {selectedConfigPath()}
-              </p>
-
-              <WidgetConfigForm
-                config={config()}
-                configPath={selectedConfigPath()}
-                onChange={config =>
-                  onConfigChange(selectedConfigPath(), config)
-                }
-              />
-            </div>
-
-            {/* Action bar. */}
-            <div class="flex items-center justify-end border-t p-4">
-              <div class="flex items-center">
-                <span class="text-sm font-normal text-muted-foreground mr-2">
-                  {selectedConfigStates().length} open (
-                  {selectedPresetStates().length} for preset)
-                </span>
+      <div class="flex-1 flex flex-col overflow-hidden">
+        {/* Errors of config files that failed to validate. */}
+        <ConfigDiagnostics />
 
-                <Button
-                  class="rounded-r-none self-end"
-                  disabled={presetNames().length === 0}
-                  onClick={() =>
-                    togglePreset(selectedConfigPath(), selectedPreset())
+        {/* Main content. */}
+        <Show
+          when={selectedConfig()}
+          fallback={<WidgetSettingsEmptyState />}
+        >
+          {config => (
+            <main class="flex-1 grid grid-rows-[1fr_auto] overflow-hidden">
+              <div id="form-container" class="container p-4 overflow-y-auto">
+                <h1 class="text-2xl font-bold mb-1">
+                  {selectedConfigPath().split(/[/\\]/).at(-1)}
+                </h1>
+
+                <p class="bg-muted text-xs font-mono rounded-sm mb-6 p-1 text-muted-foreground inline-block">
+                  {selectedConfigPath()}
+                </p>
+
+                <WidgetConfigForm
+                  config={config()}
+                  configPath={selectedConfigPath()}
+                  onChange={config =>
+                    onConfigChange(selectedConfigPath(), config)
                   }
+                />
+              </div>
+
+              {/* Action bar. */}
+              <div class="flex items-center justify-between border-t p-4">
+                <Button
+                  variant="outline"
+                  disabled={!canUndo()}
+                  onClick={() => undoConfigChange()}
                 >
-                  <Show when={selectedPreset()} fallback="No presets">
-                    {selectedPresetStates().length === 0
-                      ? `Open ${selectedPreset()}`
-                      : `Close ${selectedPreset()}`}
-                  </Show>
+                  Undo last change
                 </Button>
 
-                <DropdownMenu>
-                  <DropdownMenuTrigger>
-                    <Button
-                      class="rounded-l-none border-l-0 px-2"
-                      disabled={presetNames().length === 0}
-                    >
-                      <IconChevronDown class="size-3" />
-                    </Button>
-                  </DropdownMenuTrigger>
-
-                  <DropdownMenuContent>
-                    <For each={presetNames()}>
-                      {presetName => (
-                        <DropdownMenuItem
-                          onClick={() => setSelectedPreset(presetName)}
-                          class={cn({
-                            'bg-accent text-accent-foreground':
-                              presetName === selectedPreset(),
-                          })}
-                        >
-                          {presetName}
-                        </DropdownMenuItem>
-                      )}
-                    </For>
-                  </DropdownMenuContent>
-                </DropdownMenu>
+                <div class="flex items-center">
+                  <span class="text-sm font-normal text-muted-foreground mr-2">
+                    {selectedConfigStates().length} open (
+                    {selectedPresetStates().length} for preset)
+                  </span>
+
+                  <Button
+                    class="rounded-r-none self-end"
+                    disabled={presetNames().length === 0}
+                    onClick={() =>
+                      togglePreset(selectedConfigPath(), selectedPreset())
+                    }
+                  >
+                    <Show when={selectedPreset()} fallback="No presets">
+                      {selectedPresetStates().length === 0
+                        ? `Open ${selectedPreset()}`
+                        : `Close ${selectedPreset()}`}
+                    </Show>
+                  </Button>
+
+                  <DropdownMenu>
+                    <DropdownMenuTrigger>
+                      <Button
+                        class="rounded-l-none border-l-0 px-2"
+                        disabled={presetNames().length === 0}
+                      >
+                        <IconChevronDown class="size-3" />
+                      </Button>
+                    </DropdownMenuTrigger>
+
+                    <DropdownMenuContent>
+                      <For each={presetNames()}>
+                        {presetName => (
+                          <DropdownMenuItem
+                            onClick={() => setSelectedPreset(presetName)}
+                            class={cn({
+                              'bg-accent text-accent-foreground':
+                                presetName === selectedPreset(),
+                            })}
+                          >
+                            {presetName}
+                          </DropdownMenuItem>
+                        )}
+                      </For>
+                    </DropdownMenuContent>
+                  </DropdownMenu>
+                </div>
               </div>
-            </div>
-          </main>
-        )}
-      </Show>
+            </main>
+          )}
+        </Show>
+      </div>
     </div>
   );
 }